@@ -0,0 +1,90 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ClipboardError {
+    #[error("Failed to read clipboard: {0}")]
+    ReadFailed(String),
+    #[error("Failed to write clipboard: {0}")]
+    WriteFailed(String),
+}
+
+/// Read the plain-text contents of the general pasteboard
+pub fn read() -> Result<String, ClipboardError> {
+    let output = Command::new("pbpaste")
+        .output()
+        .map_err(|e| ClipboardError::ReadFailed(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(ClipboardError::ReadFailed(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// How many times the general pasteboard has changed since login
+///
+/// Goes up whenever any app copies, so a copy can be detected without
+/// clearing the pasteboard first.
+pub fn change_count() -> Result<i64, ClipboardError> {
+    pasteboard("$.NSPasteboard.generalPasteboard.changeCount")?
+        .parse()
+        .map_err(|e: std::num::ParseIntError| ClipboardError::ReadFailed(e.to_string()))
+}
+
+/// Whether the general pasteboard holds nothing but plain text, so saving
+/// it with `read` and putting it back with `write` loses nothing
+pub fn holds_only_text() -> Result<bool, ClipboardError> {
+    let types = pasteboard("ObjC.deepUnwrap($.NSPasteboard.generalPasteboard.types).join('\\n')")?;
+    Ok(types.lines().all(|t| PLAIN_TEXT_TYPES.contains(&t)))
+}
+
+/// Pasteboard types that only carry plain text
+const PLAIN_TEXT_TYPES: &[&str] = &[
+    "public.utf8-plain-text",
+    "public.utf16-external-plain-text",
+    "public.utf16-plain-text",
+    "NSStringPboardType",
+];
+
+/// Evaluate a JavaScript for Automation expression against AppKit
+fn pasteboard(expression: &str) -> Result<String, ClipboardError> {
+    let output = Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", "ObjC.import('AppKit')", "-e"])
+        .arg(expression)
+        .output()
+        .map_err(|e| ClipboardError::ReadFailed(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(ClipboardError::ReadFailed(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Replace the general pasteboard's contents with plain text
+pub fn write(text: &str) -> Result<(), ClipboardError> {
+    let mut child = Command::new("pbcopy")
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| ClipboardError::WriteFailed(e.to_string()))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| ClipboardError::WriteFailed(e.to_string()))?;
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| ClipboardError::WriteFailed(e.to_string()))?;
+    if !status.success() {
+        return Err(ClipboardError::WriteFailed(format!(
+            "pbcopy exited with {}",
+            status
+        )));
+    }
+    Ok(())
+}
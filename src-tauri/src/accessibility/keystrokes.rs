@@ -0,0 +1,61 @@
+use rdev::{EventType, Key};
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum KeystrokeError {
+    #[error("Failed to send keystroke: {0}")]
+    SimulateFailed(String),
+}
+
+/// Pause between synthesized events; macOS drops events sent back to back
+const EVENT_DELAY: Duration = Duration::from_millis(5);
+
+/// Press Backspace `count` times
+pub fn backspace(count: usize) -> Result<(), KeystrokeError> {
+    for _ in 0..count {
+        tap(Key::Backspace)?;
+    }
+    Ok(())
+}
+
+/// Extend the selection `count` characters to the left (Shift+Left)
+pub fn select_backward(count: usize) -> Result<(), KeystrokeError> {
+    send(EventType::KeyPress(Key::ShiftLeft))?;
+    let selected = (0..count).try_for_each(|_| tap(Key::LeftArrow));
+    // Always let go of Shift, even if an arrow press failed
+    send(EventType::KeyRelease(Key::ShiftLeft))?;
+    selected
+}
+
+/// Press `key` while holding `modifiers`, releasing them in reverse order
+pub fn chord(modifiers: &[Key], key: Key) -> Result<(), KeystrokeError> {
+    let mut held = Vec::with_capacity(modifiers.len());
+    let mut pressed = Ok(());
+    for modifier in modifiers {
+        pressed = send(EventType::KeyPress(*modifier));
+        if pressed.is_err() {
+            break;
+        }
+        held.push(*modifier);
+    }
+    if pressed.is_ok() {
+        pressed = tap(key);
+    }
+    // Never leave a modifier stuck down
+    for modifier in held.iter().rev() {
+        send(EventType::KeyRelease(*modifier))?;
+    }
+    pressed
+}
+
+fn tap(key: Key) -> Result<(), KeystrokeError> {
+    send(EventType::KeyPress(key))?;
+    send(EventType::KeyRelease(key))
+}
+
+fn send(event: EventType) -> Result<(), KeystrokeError> {
+    rdev::simulate(&event).map_err(|_| KeystrokeError::SimulateFailed(format!("{:?}", event)))?;
+    std::thread::sleep(EVENT_DELAY);
+    Ok(())
+}
@@ -1,3 +1,5 @@
+pub mod clipboard;
+pub mod keystrokes;
 pub mod permissions;
 pub mod text_insertion;
 
@@ -1,5 +1,13 @@
+use super::{clipboard, keystrokes};
+use crate::system::tray;
+use rdev::Key;
+use std::process::Command;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
+/// How long to wait for the focused app to answer Cmd+C
+const COPY_TIMEOUT: Duration = Duration::from_millis(300);
+
 #[derive(Error, Debug)]
 pub enum InsertionError {
     #[error("Failed to insert text: {0}")]
@@ -19,6 +27,73 @@ pub fn insert(text: &str) -> Result<(), InsertionError> {
     }
 }
 
+/// Read up to `max_chars` characters before the cursor in the focused field
+///
+/// Returns `None` when the focused element doesn't expose its value.
+pub fn text_before_cursor(max_chars: usize) -> Result<Option<String>, InsertionError> {
+    // AXValue up to AXSelectedTextRange's start (1-based), through System
+    // Events; slicing by code points keeps the count in Rust chars
+    let script = format!(
+        "let before = null; \
+         try {{ \
+             const field = Application('System Events').processes.whose({{ frontmost: true }})[0] \
+                 .attributes.byName('AXFocusedUIElement').value(); \
+             const value = field.attributes.byName('AXValue').value(); \
+             const range = field.attributes.byName('AXSelectedTextRange').value(); \
+             if (typeof value === 'string' && range) {{ \
+                 before = Array.from(value.slice(0, range[0] - 1)).slice(-{}).join(''); \
+             }} \
+         }} catch (e) {{}} \
+         JSON.stringify(before)",
+        max_chars
+    );
+    let output = Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", &script])
+        .output()
+        .map_err(|e| InsertionError::InsertionFailed(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(InsertionError::InsertionFailed(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    serde_json::from_str(String::from_utf8_lossy(&output.stdout).trim())
+        .map_err(|e| InsertionError::InsertionFailed(e.to_string()))
+}
+
+/// Read the text selected in the focused app
+///
+/// Returns `None` when nothing is selected. Apps that don't expose their
+/// selection are asked to copy it, and the clipboard is put back afterwards;
+/// that's skipped when the clipboard holds more than plain text, which
+/// couldn't be put back, and in editors that copy the whole line when
+/// nothing is selected.
+pub fn selected_text() -> Result<Option<String>, InsertionError> {
+    match selected_text_via_accessibility() {
+        Ok(selected) => Ok(selected),
+        Err(_) => selected_text_via_clipboard(),
+    }
+}
+
+/// Delete `count` characters before the cursor
+pub fn delete_before_cursor(count: usize) -> Result<(), InsertionError> {
+    match delete_via_accessibility(count) {
+        Ok(()) => Ok(()),
+        Err(_) => {
+            keystrokes::backspace(count).map_err(|e| InsertionError::InsertionFailed(e.to_string()))
+        }
+    }
+}
+
+/// Select `count` characters before the cursor
+pub fn select_before_cursor(count: usize) -> Result<(), InsertionError> {
+    match select_via_accessibility(count) {
+        Ok(()) => Ok(()),
+        Err(_) => keystrokes::select_backward(count)
+            .map_err(|e| InsertionError::InsertionFailed(e.to_string())),
+    }
+}
+
 /// Insert text using macOS Accessibility API (AXUIElement)
 fn insert_via_accessibility(text: &str) -> Result<(), InsertionError> {
     // TODO: Implement using accessibility-sys or core-foundation
@@ -34,6 +109,87 @@ fn insert_via_accessibility(text: &str) -> Result<(), InsertionError> {
     ))
 }
 
+/// Delete before the cursor by setting the focused element's selected text
+fn delete_via_accessibility(count: usize) -> Result<(), InsertionError> {
+    // TODO: Implement using accessibility-sys or core-foundation
+    // 1. Read AXSelectedTextRange for the cursor location
+    // 2. Set AXSelectedTextRange to the `count` characters before it
+    // 3. Set AXSelectedText to an empty string
+    let _ = count;
+    Err(InsertionError::InsertionFailed(
+        "AX API not yet implemented".to_string(),
+    ))
+}
+
+/// Select before the cursor by setting the focused element's selected range
+fn select_via_accessibility(count: usize) -> Result<(), InsertionError> {
+    // TODO: Implement using accessibility-sys or core-foundation
+    // 1. Read AXSelectedTextRange for the cursor location
+    // 2. Set AXSelectedTextRange to the `count` characters before it
+    let _ = count;
+    Err(InsertionError::InsertionFailed(
+        "AX API not yet implemented".to_string(),
+    ))
+}
+
+/// Read the focused element's AXSelectedText
+fn selected_text_via_accessibility() -> Result<Option<String>, InsertionError> {
+    // TODO: Implement using accessibility-sys or core-foundation
+    // 1. Get the focused element's AXFocusedUIElement
+    // 2. Read AXSelectedText; an empty string means no selection
+    Err(InsertionError::InsertionFailed(
+        "AX API not yet implemented".to_string(),
+    ))
+}
+
+/// Fallback: copy the selection (Cmd+C) and read it off the clipboard
+fn selected_text_via_clipboard() -> Result<Option<String>, InsertionError> {
+    let focused = tray::get_focused_app().ok().flatten();
+    if let Some(app) = focused.filter(|app| copies_line_without_selection(&app.bundle_id)) {
+        log::debug!("Not copying in {}, which copies the line", app.bundle_id);
+        return Ok(None);
+    }
+    let failed = |e: clipboard::ClipboardError| InsertionError::InsertionFailed(e.to_string());
+    if !clipboard::holds_only_text().map_err(failed)? {
+        return Err(InsertionError::InsertionFailed(
+            "the clipboard holds more than text and can't be put back".to_string(),
+        ));
+    }
+
+    let saved = clipboard::read().map_err(failed)?;
+    let before = clipboard::change_count().map_err(failed)?;
+    keystrokes::chord(&[Key::MetaLeft], Key::KeyC)
+        .map_err(|e| InsertionError::InsertionFailed(e.to_string()))?;
+
+    // An app with nothing selected copies nothing, leaving the count as is
+    let started = Instant::now();
+    while clipboard::change_count().map_err(failed)? == before {
+        if started.elapsed() >= COPY_TIMEOUT {
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    let copied = clipboard::read().map_err(failed);
+    if let Err(e) = clipboard::write(&saved) {
+        log::warn!("Couldn't restore the clipboard: {}", e);
+    }
+    let copied = copied?;
+    Ok((!copied.is_empty()).then_some(copied))
+}
+
+/// Editors whose Cmd+C copies the current line when nothing is selected
+fn copies_line_without_selection(bundle_id: &str) -> bool {
+    matches!(
+        bundle_id,
+        "com.microsoft.VSCode"
+            | "com.microsoft.VSCodeInsiders"
+            | "com.todesktop.230313mzl4w4u92" // Cursor
+            | "dev.zed.Zed"
+            | "com.sublimetext.4"
+    ) || bundle_id.starts_with("com.jetbrains.")
+}
+
 /// Fallback: Insert text via clipboard (Cmd+V)
 fn insert_via_clipboard(text: &str) -> Result<(), InsertionError> {
     // TODO: Implement clipboard-based insertion
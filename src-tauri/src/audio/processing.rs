@@ -67,3 +67,251 @@ pub fn apply_gain(samples: &mut [f32], gain_db: f32) {
         *sample = (*sample * gain_linear).clamp(-1.0, 1.0);
     }
 }
+
+/// Duration of a WAV-encoded buffer in milliseconds, read from its header
+pub fn wav_duration_ms(wav: &[u8]) -> Option<u64> {
+    if wav.len() < 12 || &wav[0..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+        return None;
+    }
+
+    let mut byte_rate = None;
+    let mut offset = 12;
+    while offset + 8 <= wav.len() {
+        let id = &wav[offset..offset + 4];
+        let size = u32::from_le_bytes(wav[offset + 4..offset + 8].try_into().ok()?) as usize;
+        let body = offset + 8;
+
+        match id {
+            b"fmt " if body + 12 <= wav.len() => {
+                byte_rate = Some(u32::from_le_bytes(
+                    wav[body + 8..body + 12].try_into().ok()?,
+                ));
+            }
+            b"data" => {
+                let rate = byte_rate.filter(|r| *r > 0)?;
+                // Streaming writers may leave the size unset; use what's actually there
+                let size = size.min(wav.len() - body);
+                return Some(size as u64 * 1000 / rate as u64);
+            }
+            _ => {}
+        }
+        // Chunks are word-aligned
+        offset = body + size + (size & 1);
+    }
+    None
+}
+
+/// A stretch of silence between words
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pause {
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+impl Pause {
+    pub fn duration_ms(&self) -> u64 {
+        self.end_ms - self.start_ms
+    }
+}
+
+/// When speech starts and ends in a recording and where it pauses
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpeechTiming {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub pauses: Vec<Pause>,
+}
+
+impl SpeechTiming {
+    /// Time spent speaking, without pauses
+    pub fn speaking_ms(&self) -> u64 {
+        let paused: u64 = self.pauses.iter().map(Pause::duration_ms).sum();
+        (self.end_ms - self.start_ms).saturating_sub(paused)
+    }
+}
+
+/// Length of the frames silence is measured over
+const PAUSE_FRAME_MS: u64 = 20;
+
+/// Find speech and the silences of at least `min_pause_ms` within it, using
+/// the noise gate threshold
+///
+/// Returns `None` when nothing rises above the gate.
+pub fn detect_speech(
+    samples: &[f32],
+    sample_rate: u32,
+    config: &SilenceConfig,
+    min_pause_ms: u64,
+) -> Option<SpeechTiming> {
+    let frame_len = (sample_rate as u64 * PAUSE_FRAME_MS / 1000).max(1) as usize;
+    let mut speech: Option<SpeechTiming> = None;
+    let mut silence_start: Option<u64> = None;
+
+    for (i, frame) in samples.chunks(frame_len).enumerate() {
+        let at_ms = i as u64 * PAUSE_FRAME_MS;
+        if is_silence(frame, config.noise_gate_threshold_db) {
+            silence_start.get_or_insert(at_ms);
+            continue;
+        }
+        let timing = speech.get_or_insert_with(|| SpeechTiming {
+            start_ms: at_ms,
+            ..Default::default()
+        });
+        if let Some(start_ms) = silence_start.take() {
+            if timing.end_ms > 0 && at_ms - start_ms >= min_pause_ms {
+                timing.pauses.push(Pause {
+                    start_ms,
+                    end_ms: at_ms,
+                });
+            }
+        }
+        timing.end_ms = at_ms + PAUSE_FRAME_MS;
+    }
+    speech
+}
+
+/// Decode a 16-bit PCM WAV buffer into mono samples and its sample rate
+pub fn wav_samples(wav: &[u8]) -> Option<(Vec<f32>, u32)> {
+    if wav.len() < 12 || &wav[0..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+        return None;
+    }
+
+    let mut format = None;
+    let mut offset = 12;
+    while offset + 8 <= wav.len() {
+        let id = &wav[offset..offset + 4];
+        let size = u32::from_le_bytes(wav[offset + 4..offset + 8].try_into().ok()?) as usize;
+        let body = offset + 8;
+
+        match id {
+            b"fmt " if body + 16 <= wav.len() => {
+                let tag = u16::from_le_bytes(wav[body..body + 2].try_into().ok()?);
+                let channels = u16::from_le_bytes(wav[body + 2..body + 4].try_into().ok()?);
+                let rate = u32::from_le_bytes(wav[body + 4..body + 8].try_into().ok()?);
+                let bits = u16::from_le_bytes(wav[body + 14..body + 16].try_into().ok()?);
+                format = Some((tag, channels, rate, bits));
+            }
+            b"data" => {
+                let (tag, channels, rate, bits) = format?;
+                if tag != 1 || bits != 16 || channels == 0 || rate == 0 {
+                    return None;
+                }
+                let end = body + size.min(wav.len() - body);
+                let samples = wav[body..end]
+                    .chunks_exact(2 * channels as usize)
+                    .map(|frame| {
+                        let sum: f32 = frame
+                            .chunks_exact(2)
+                            .map(|s| i16::from_le_bytes([s[0], s[1]]) as f32 / i16::MAX as f32)
+                            .sum();
+                        sum / channels as f32
+                    })
+                    .collect();
+                return Some((samples, rate));
+            }
+            _ => {}
+        }
+        offset = body + size + (size & 1);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 16-bit PCM WAV with interleaved samples
+    fn wav(rate: u32, channels: u16, samples: &[i16]) -> Vec<u8> {
+        let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        let mut out = Vec::new();
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        out.extend_from_slice(b"WAVEfmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&1u16.to_le_bytes());
+        out.extend_from_slice(&channels.to_le_bytes());
+        out.extend_from_slice(&rate.to_le_bytes());
+        out.extend_from_slice(&(rate * channels as u32 * 2).to_le_bytes());
+        out.extend_from_slice(&(channels * 2).to_le_bytes());
+        out.extend_from_slice(&16u16.to_le_bytes());
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(&data);
+        out
+    }
+
+    #[test]
+    fn reads_wav_buffers() {
+        let mono = wav(16000, 1, &[0; 8000]);
+        assert_eq!(wav_duration_ms(&mono), Some(500));
+        let (samples, rate) = wav_samples(&mono).unwrap();
+        assert_eq!((samples.len(), rate), (8000, 16000));
+
+        let stereo = wav(8000, 2, &[i16::MAX, 0, 0, i16::MAX, i16::MAX, i16::MAX]);
+        assert_eq!(wav_samples(&stereo).unwrap(), (vec![0.5, 0.5, 1.0], 8000));
+
+        // Streaming writers may leave the data size unset
+        let mut streamed = wav(16000, 1, &[0; 1600]);
+        streamed[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(wav_duration_ms(&streamed), Some(100));
+        assert_eq!(wav_samples(&streamed).unwrap().0.len(), 1600);
+    }
+
+    #[test]
+    fn rejects_malformed_wavs() {
+        let valid = wav(16000, 1, &[0; 160]);
+        let mut not_pcm = valid.clone();
+        not_pcm[20..22].copy_from_slice(&3u16.to_le_bytes());
+        let mut no_format = valid.clone();
+        no_format[12..16].copy_from_slice(b"junk");
+
+        let cases: &[(&str, &[u8])] = &[
+            ("empty", b""),
+            ("not RIFF", b"RIFX\0\0\0\0WAVE"),
+            ("header only", &valid[..12]),
+            ("cut in the format", &valid[..30]),
+            ("no format chunk", &no_format),
+        ];
+        for (name, bytes) in cases {
+            assert_eq!(wav_duration_ms(bytes), None, "case: {}", name);
+            assert_eq!(wav_samples(bytes), None, "case: {}", name);
+        }
+        assert_eq!(wav_samples(&not_pcm), None);
+    }
+
+    #[test]
+    fn finds_speech_and_pauses() {
+        // 1 kHz, so each 20 ms frame is 20 samples
+        let silence = |ms: usize| vec![0.0f32; ms];
+        let tone = |ms: usize| vec![0.5f32; ms];
+        let samples = [
+            silence(200),
+            tone(300),
+            silence(600),
+            tone(200),
+            silence(100),
+            tone(100),
+        ]
+        .concat();
+        let config = SilenceConfig::default();
+
+        let timing = detect_speech(&samples, 1000, &config, 500).unwrap();
+        assert_eq!(
+            timing,
+            SpeechTiming {
+                start_ms: 200,
+                end_ms: 1500,
+                pauses: vec![Pause {
+                    start_ms: 500,
+                    end_ms: 1100,
+                }],
+            }
+        );
+        assert_eq!(timing.speaking_ms(), 700);
+
+        let timing = detect_speech(&samples, 1000, &config, 100).unwrap();
+        assert_eq!(timing.pauses.len(), 2);
+
+        assert_eq!(detect_speech(&silence(1000), 1000, &config, 500), None);
+    }
+}
@@ -0,0 +1,712 @@
+pub mod queue;
+
+use crate::accessibility::text_insertion;
+use crate::audio::processing;
+use crate::editing::grammar::{self, EditCommand};
+use crate::editing::{self, EditError};
+use crate::keyboard::shortcuts::{self, ShortcutError};
+use crate::postprocess::translation::{
+    self, TranslationBackend, TranslationOptions, TranslationOutcome,
+};
+use crate::postprocess::{
+    self, PostprocessError, PostprocessOptions, PostprocessOutcome, TransformOutcome,
+};
+use crate::storage::app_profiles::{self, AppProfile};
+use crate::storage::database::DatabaseError;
+use crate::storage::dictionary::{self, DictionaryReplacement};
+use crate::storage::history::{self, NewHistoryItem};
+use crate::system::{notifications, tray};
+use crate::text::diff::{self, Stage};
+use crate::text::paragraphs::{self, Timing};
+use crate::text::{self, ProcessedText, ProcessingOptions};
+use crate::transcription::language::{self, LanguageDecision, LanguagePreferences, LanguageSource};
+use crate::transcription::{self, TranscribeOptions, TranscriptionError, TranscriptionResult};
+use crate::usage::{self, LimitLevel, UsageRecord, UsageWarning};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum DictationError {
+    #[error(transparent)]
+    Transcription(#[from] TranscriptionError),
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+    #[error("Dictation queue error: {0}")]
+    QueueError(String),
+    #[error(transparent)]
+    Editing(#[from] EditError),
+    #[error(transparent)]
+    Shortcut(#[from] ShortcutError),
+    #[error(transparent)]
+    Postprocess(#[from] PostprocessError),
+    #[error("No text is selected")]
+    NothingSelected,
+    #[error("No instruction was heard")]
+    NoInstruction,
+}
+
+/// Everything needed to turn captured audio into a history item
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DictationOptions {
+    #[serde(default)]
+    pub transcription: TranscribeOptions,
+    #[serde(default)]
+    pub language: LanguagePreferences,
+    #[serde(default)]
+    pub processing: ProcessingOptions,
+    /// Rewrite the locally processed text with an LLM
+    #[serde(default)]
+    pub postprocess: PostprocessOptions,
+    /// Language to insert the text in, as when started with a translation
+    /// hotkey; overrides the app profile's
+    #[serde(default)]
+    pub translate_to: Option<String>,
+    #[serde(default)]
+    pub translation: TranslationOptions,
+    /// Text before the cursor where the result will be inserted; read from
+    /// the focused field when not given
+    #[serde(default)]
+    pub context_before: Option<String>,
+    /// Run short utterances like "delete that" or "press escape" as commands
+    #[serde(default)]
+    pub voice_commands: bool,
+    /// Read the whole dictation letter by letter, as when started with the
+    /// spelling hotkey; otherwise only after a "spell" prefix
+    #[serde(default)]
+    pub spelling: bool,
+}
+
+/// Per-stage wall-clock timings for a single dictation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PipelineTimings {
+    pub transcription_ms: u64,
+    pub processing_ms: u64,
+    pub postprocess_ms: u64,
+    pub translation_ms: u64,
+    pub total_ms: u64,
+    /// Transcript came from the local cache rather than the provider
+    pub cache_hit: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DictationResult {
+    pub history_id: String,
+    pub text: String,
+    pub language: String,
+    pub language_confidence: Option<f32>,
+    pub language_source: LanguageSource,
+    pub timings: PipelineTimings,
+    /// Words auto-fixed from the custom dictionary
+    pub replacements: Vec<DictionaryReplacement>,
+    /// LLM rewrite, when enabled
+    pub postprocess: Option<PostprocessOutcome>,
+    /// Translation into the app's or hotkey's language, when set
+    pub translation: Option<TranslationOutcome>,
+    /// Set when this dictation pushed spend past a soft limit threshold
+    pub usage_warning: Option<UsageWarning>,
+}
+
+/// Selected text rewritten by a spoken instruction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformResult {
+    pub history_id: String,
+    /// What the user asked for, as transcribed
+    pub instruction: String,
+    /// The text that replaced the selection
+    pub text: String,
+    pub timings: PipelineTimings,
+    pub transform: TransformOutcome,
+    pub usage_warning: Option<UsageWarning>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum DictationOutcome {
+    /// Transcribed and saved to history
+    Completed(Box<DictationResult>),
+    /// The provider was unreachable; the job will be retried in the background
+    Queued { job_id: String },
+    /// The utterance was an editing command and was applied instead of inserted
+    Command {
+        command: EditCommand,
+        confidence: f32,
+        usage_warning: Option<UsageWarning>,
+    },
+    /// The utterance named a key chord, which was pressed
+    Shortcut {
+        chord: String,
+        usage_warning: Option<UsageWarning>,
+    },
+}
+
+/// A transcript and what's needed to process it
+struct Transcribed {
+    profile: Option<AppProfile>,
+    transcript: TranscriptionResult,
+    decision: LanguageDecision,
+    started: Instant,
+    transcription_ms: u64,
+}
+
+/// Run a dictation, queueing it for later if the provider can't be reached
+pub async fn dictate(
+    audio: &[u8],
+    target_app: Option<String>,
+    mut options: DictationOptions,
+) -> Result<DictationOutcome, DictationError> {
+    let target_app = target_app.or_else(|| {
+        tray::get_focused_app()
+            .ok()
+            .flatten()
+            .map(|app| app.bundle_id)
+    });
+
+    if options.context_before.is_none() {
+        options.context_before = text_insertion::text_before_cursor(text::context::CONTEXT_CHARS)
+            .unwrap_or_else(|e| {
+                log::debug!("Couldn't read text before cursor: {}", e);
+                None
+            });
+    }
+
+    let transcribed = match transcribe(audio, target_app.as_deref(), &options).await {
+        Ok(transcribed) => transcribed,
+        Err(DictationError::Transcription(e)) if e.is_retryable() => {
+            log::warn!("Transcription unavailable, queueing dictation: {}", e);
+            // The cursor will have moved on by the time the job completes,
+            // so a late "delete that" would hit the wrong text
+            options.context_before = None;
+            options.voice_commands = false;
+            let job_id = queue::enqueue(audio, target_app.as_deref(), &options)?;
+            return Ok(DictationOutcome::Queued { job_id });
+        }
+        Err(e) => return Err(e),
+    };
+
+    if options.voice_commands && !options.spelling {
+        if let Some(chord) = shortcuts::parse(&transcribed.transcript.text) {
+            // Metered first: the audio was transcribed even if the press is
+            // blocked
+            let usage_warning = meter_command(&transcribed, &options, audio);
+            log::info!("Pressing voice shortcut {}", chord.label());
+            shortcuts::press(&chord)?;
+            return Ok(DictationOutcome::Shortcut {
+                chord: chord.label(),
+                usage_warning,
+            });
+        }
+
+        let parsed = grammar::parse(&transcribed.transcript.text)
+            .filter(|p| p.confidence >= grammar::MIN_CONFIDENCE);
+        if let Some(parsed) = parsed {
+            log::info!(
+                "Running editing command {:?} ({:.2})",
+                parsed.command,
+                parsed.confidence
+            );
+            // Metered first: the audio was transcribed even if there's
+            // nothing to edit
+            let usage_warning = meter_command(&transcribed, &options, audio);
+            editing::apply(parsed.command)?;
+            return Ok(DictationOutcome::Command {
+                command: parsed.command,
+                confidence: parsed.confidence,
+                usage_warning,
+            });
+        }
+    }
+
+    let result = finish(transcribed, audio, target_app.as_deref(), &options).await?;
+    Ok(DictationOutcome::Completed(Box::new(result)))
+}
+
+/// Transcribe audio and record the result in history
+pub async fn run(
+    audio: &[u8],
+    target_app: Option<&str>,
+    options: &DictationOptions,
+) -> Result<DictationResult, DictationError> {
+    let transcribed = transcribe(audio, target_app, options).await?;
+    finish(transcribed, audio, target_app, options).await
+}
+
+/// Apply a spoken instruction to the selected text and put the result in
+/// its place
+///
+/// `selection` should be read when the transform hotkey goes down, before
+/// recording; it's read now if not given. Nothing is queued when the
+/// provider is unreachable since the selection won't survive until a retry.
+pub async fn transform_selection(
+    audio: &[u8],
+    selection: Option<String>,
+    target_app: Option<&str>,
+    options: &DictationOptions,
+) -> Result<TransformResult, DictationError> {
+    let selection = match selection {
+        Some(selection) => selection,
+        None => text_insertion::selected_text()
+            .map_err(EditError::from)?
+            .unwrap_or_default(),
+    };
+    if selection.trim().is_empty() {
+        return Err(DictationError::NothingSelected);
+    }
+
+    let transcribed = transcribe(audio, target_app, options).await?;
+    let instruction = transcribed.transcript.text.trim().to_string();
+    if instruction.is_empty() {
+        return Err(DictationError::NoInstruction);
+    }
+
+    let postprocess_started = Instant::now();
+    let transform = postprocess::transform(&selection, &instruction, &options.postprocess)
+        .await
+        .inspect_err(|e| {
+            if let Some((model, input_tokens, output_tokens)) = e.billed() {
+                meter_llm(None, options, model, input_tokens, output_tokens);
+            }
+        })?;
+    let postprocess_ms = postprocess_started.elapsed().as_millis() as u64;
+
+    editing::replace_selection(&selection, &transform.text)?;
+
+    let stages = [diff::StageText {
+        stage: Stage::Rewrite,
+        text: transform.text.clone(),
+    }];
+    let history_id = history::insert(&NewHistoryItem {
+        text: transform.text.clone(),
+        original_text: Some(selection.clone()),
+        was_corrected: false,
+        language: transcribed.decision.language.clone(),
+        language_confidence: transcribed.decision.confidence,
+        duration_ms: transcribed.transcript.duration_ms,
+        target_app: target_app.map(str::to_string),
+        source_language: None,
+        diff: diff::compute(&selection, &stages),
+    })?;
+
+    meter_llm(
+        Some(&history_id),
+        options,
+        &transform.model,
+        transform.input_tokens,
+        transform.output_tokens,
+    );
+    let usage_warning = if transcribed.transcript.cached {
+        None
+    } else {
+        meter(Some(&history_id), options, &transcribed.transcript, audio)
+    };
+
+    Ok(TransformResult {
+        history_id,
+        instruction,
+        text: transform.text.clone(),
+        timings: PipelineTimings {
+            transcription_ms: transcribed.transcription_ms,
+            postprocess_ms,
+            total_ms: transcribed.started.elapsed().as_millis() as u64,
+            cache_hit: transcribed.transcript.cached,
+            ..Default::default()
+        },
+        transform,
+        usage_warning,
+    })
+}
+
+/// Transcribe audio and settle on its language
+async fn transcribe(
+    audio: &[u8],
+    target_app: Option<&str>,
+    options: &DictationOptions,
+) -> Result<Transcribed, DictationError> {
+    let started = Instant::now();
+
+    let profile = match target_app {
+        Some(bundle_id) => app_profiles::get(bundle_id)?,
+        None => None,
+    };
+    let app_language = profile.as_ref().and_then(|p| p.language.clone());
+    let mut transcribe_options = options.transcription.clone();
+    transcribe_options.language =
+        language::request_language(&options.language, app_language.as_deref());
+
+    let mut transcript = transcription::transcribe(audio, &transcribe_options).await?;
+
+    // Only trust the provider's language when it was asked to detect one
+    let detected = transcribe_options.language.is_none().then_some((
+        transcript.language.as_str(),
+        transcript.language_probability,
+    ));
+    let decision = language::resolve(detected, app_language.as_deref(), &options.language);
+
+    // A rejected detection means the text was recognized as the wrong
+    // language, so ask again with the one chosen instead
+    if detected.is_some() && decision.source != LanguageSource::Detected {
+        log::info!(
+            "Detected {} rejected, transcribing again as {}",
+            transcript.language,
+            decision.language
+        );
+        transcribe_options.language = Some(decision.language.clone());
+        // The first request is billed too; the retry is metered with the
+        // dictation
+        if !transcript.cached {
+            meter(None, options, &transcript, audio);
+        }
+        transcript = transcription::transcribe(audio, &transcribe_options).await?;
+    }
+    let transcription_ms = started.elapsed().as_millis() as u64;
+
+    Ok(Transcribed {
+        profile,
+        transcript,
+        decision,
+        started,
+        transcription_ms,
+    })
+}
+
+/// Process a transcript and record it in history
+async fn finish(
+    transcribed: Transcribed,
+    audio: &[u8],
+    target_app: Option<&str>,
+    options: &DictationOptions,
+) -> Result<DictationResult, DictationError> {
+    let Transcribed {
+        profile,
+        transcript,
+        decision,
+        started,
+        transcription_ms,
+    } = transcribed;
+
+    let processing_started = Instant::now();
+    let mut processing = options.processing.clone();
+    if profile.as_ref().is_some_and(|p| p.keep_spoken_forms) {
+        processing.normalize_spoken_forms = false;
+    }
+    match profile.as_ref().and_then(|p| p.code_mode) {
+        Some(enabled) => processing.code_mode = enabled,
+        None => processing.code_mode |= target_app.is_some_and(tray::is_code_app),
+    }
+    processing.emoji = match profile.as_ref().and_then(|p| p.emoji) {
+        Some(enabled) => enabled,
+        None => target_app.is_some_and(tray::is_chat_app),
+    };
+    if let Some(formatting) = profile.as_ref().and_then(|p| p.formatting) {
+        processing.formatting = formatting;
+    }
+    if let Some(variant) = profile.as_ref().and_then(|p| p.spelling_variant) {
+        processing.spelling_variant = variant;
+    }
+    if let Some(enabled) = profile.as_ref().and_then(|p| p.house_style) {
+        processing.house_style = enabled;
+    }
+    match profile.as_ref().and_then(|p| p.paragraph_breaks) {
+        Some(enabled) => processing.paragraph_breaks = enabled,
+        None => processing.paragraph_breaks &= !target_app.is_some_and(tray::is_chat_app),
+    }
+    // Spelled input bypasses every stage, including the ones below
+    let spelled = if options.spelling {
+        Some(transcript.text.as_str())
+    } else {
+        text::spelling::strip_prefix(&transcript.text)
+    };
+    // Each stage's output, for the history diff
+    let mut stages = Vec::new();
+    let mut processed = match spelled {
+        Some(letters) => {
+            let text = text::spelling::spell(letters);
+            diff::record(&mut stages, &transcript.text, Stage::SpelledOut, &text);
+            ProcessedText {
+                text,
+                ..Default::default()
+            }
+        }
+        None if processing.paragraph_breaks && !processing.code_mode => {
+            let broken = break_at_pauses(&transcript, audio, &processing);
+            diff::record(&mut stages, &transcript.text, Stage::Paragraphs, &broken);
+            text::process(&broken, &decision.language, &processing)
+        }
+        None => text::process(&transcript.text, &decision.language, &processing),
+    };
+    stages.append(&mut processed.stages);
+    let mut processing_ms = processing_started.elapsed().as_millis() as u64;
+
+    let postprocess_started = Instant::now();
+    // A rewrite would undo code formatting or spelled characters
+    let rewritten = if options.postprocess.enabled && !processing.code_mode && spelled.is_none() {
+        let tone = options.postprocess.tone_for(target_app);
+        Some(
+            postprocess::run(
+                &processed.text,
+                &decision.language,
+                tone,
+                &options.postprocess,
+            )
+            .await,
+        )
+    } else {
+        None
+    };
+    let postprocess_ms = postprocess_started.elapsed().as_millis() as u64;
+    let rewritten_text = rewritten.as_ref().map_or(&processed.text, |r| &r.text);
+
+    let translation_started = Instant::now();
+    let translate_to = options
+        .translate_to
+        .as_deref()
+        .or_else(|| profile.as_ref().and_then(|p| p.translate_to.as_deref()));
+    // Code and spelled characters read the same in any language
+    let translation = match translate_to {
+        Some(target) if !processing.code_mode && spelled.is_none() => Some(
+            translation::run(
+                rewritten_text,
+                &decision.language,
+                target,
+                &options.translation,
+                &options.postprocess,
+            )
+            .await,
+        ),
+        _ => None,
+    };
+    let translated = translation.clone().filter(|t| t.applied);
+    let translation_ms = translation_started.elapsed().as_millis() as u64;
+
+    let fit_started = Instant::now();
+    let keep: Vec<&str> = processed
+        .replacements
+        .iter()
+        .map(|r| r.word.as_str())
+        .chain(processed.snippets.iter().map(|m| m.expansion.as_str()))
+        .collect();
+    let final_text = match spelled {
+        Some(_) => processed.text.clone(),
+        None => text::context::fit(
+            translated.as_ref().map_or(rewritten_text, |t| &t.text),
+            options.context_before.as_deref(),
+            translated
+                .as_ref()
+                .map_or(&decision.language, |t| &t.target_language),
+            &keep,
+            &processing,
+        ),
+    };
+    processing_ms += fit_started.elapsed().as_millis() as u64;
+
+    if let Some(rewritten) = rewritten.as_ref().filter(|r| r.applied) {
+        diff::record(
+            &mut stages,
+            &transcript.text,
+            Stage::Rewrite,
+            &rewritten.text,
+        );
+    }
+    if let Some(translated) = &translated {
+        diff::record(
+            &mut stages,
+            &transcript.text,
+            Stage::Translation,
+            &translated.text,
+        );
+    }
+    diff::record(
+        &mut stages,
+        &transcript.text,
+        Stage::Context,
+        final_text.trim_start(),
+    );
+
+    let history_id = history::insert(&NewHistoryItem {
+        // The leading space only matters at the cursor
+        text: final_text.trim_start().to_string(),
+        original_text: (final_text.trim_start() != transcript.text)
+            .then(|| transcript.text.clone()),
+        was_corrected: !processed.corrections.is_empty(),
+        language: translated
+            .as_ref()
+            .map_or(&decision.language, |t| &t.target_language)
+            .clone(),
+        language_confidence: decision.confidence,
+        duration_ms: transcript.duration_ms,
+        target_app: target_app.map(str::to_string),
+        source_language: translated.as_ref().map(|t| t.source_language.clone()),
+        diff: diff::compute(&transcript.text, &stages),
+    })?;
+
+    if !processed.replacements.is_empty() {
+        dictionary::record_replacements(&history_id, &processed.replacements)?;
+    }
+
+    // Requests that failed or timed out are billed all the same
+    if let Some(rewritten) = rewritten.as_ref().filter(|r| r.sent) {
+        meter_llm(
+            Some(&history_id),
+            options,
+            &rewritten.model,
+            rewritten.input_tokens,
+            rewritten.output_tokens,
+        );
+    }
+    if let Some(translation) = translation
+        .as_ref()
+        .filter(|t| t.sent && t.backend == TranslationBackend::Llm)
+    {
+        meter_llm(
+            Some(&history_id),
+            options,
+            &translation.model,
+            translation.input_tokens,
+            translation.output_tokens,
+        );
+    }
+
+    let usage_warning = if transcript.cached {
+        None
+    } else {
+        meter(Some(&history_id), options, &transcript, audio)
+    };
+
+    Ok(DictationResult {
+        history_id,
+        text: final_text,
+        language: decision.language,
+        language_confidence: decision.confidence,
+        language_source: decision.source,
+        timings: PipelineTimings {
+            transcription_ms,
+            processing_ms,
+            postprocess_ms,
+            translation_ms,
+            total_ms: started.elapsed().as_millis() as u64,
+            cache_hit: transcript.cached,
+        },
+        replacements: processed.replacements,
+        postprocess: rewritten,
+        translation: translated,
+        usage_warning,
+    })
+}
+
+/// Add paragraph and sentence breaks at pauses, timed by the provider's words
+/// or, without them, by silences in the audio
+fn break_at_pauses(
+    transcript: &TranscriptionResult,
+    audio: &[u8],
+    options: &ProcessingOptions,
+) -> String {
+    let paragraph_ms = options
+        .paragraph_pause_ms
+        .unwrap_or(paragraphs::DEFAULT_PARAGRAPH_PAUSE_MS);
+    if !transcript.words.is_empty() {
+        let timing = Timing::Words(&transcript.words);
+        return paragraphs::insert_breaks(&transcript.text, &timing, paragraph_ms);
+    }
+
+    let Some((samples, sample_rate)) = processing::wav_samples(audio) else {
+        log::debug!("No word timings and audio isn't 16-bit PCM; skipping paragraph breaks");
+        return transcript.text.clone();
+    };
+    let speech = processing::detect_speech(
+        &samples,
+        sample_rate,
+        &processing::SilenceConfig::default(),
+        paragraphs::min_pause_ms(paragraph_ms),
+    );
+    match speech {
+        Some(speech) => {
+            paragraphs::insert_breaks(&transcript.text, &Timing::Speech(&speech), paragraph_ms)
+        }
+        None => transcript.text.clone(),
+    }
+}
+
+/// Record billed tokens for an LLM request, whether or not its answer was used
+///
+/// Runs before `meter` so the limit check includes it.
+fn meter_llm(
+    history_id: Option<&str>,
+    options: &DictationOptions,
+    model: &str,
+    input_tokens: u64,
+    output_tokens: u64,
+) {
+    let recorded = usage::record(&UsageRecord {
+        history_id: history_id.map(str::to_string),
+        provider: options.postprocess.provider.as_str().to_string(),
+        model: model.to_string(),
+        audio_seconds: 0.0,
+        input_tokens,
+        output_tokens,
+    });
+    if let Err(e) = recorded {
+        log::error!("Failed to record post-processing usage: {}", e);
+    }
+}
+
+/// Record billed usage for an utterance run as a command, which has no
+/// history item
+fn meter_command(
+    transcribed: &Transcribed,
+    options: &DictationOptions,
+    audio: &[u8],
+) -> Option<UsageWarning> {
+    if transcribed.transcript.cached {
+        return None;
+    }
+    meter(None, options, &transcribed.transcript, audio)
+}
+
+/// Record billed usage for a transcription and warn if a soft limit is crossed
+///
+/// Metering failures are logged rather than failing the dictation.
+fn meter(
+    history_id: Option<&str>,
+    options: &DictationOptions,
+    transcript: &TranscriptionResult,
+    audio: &[u8],
+) -> Option<UsageWarning> {
+    let duration_ms = match transcript.duration_ms {
+        0 => processing::wav_duration_ms(audio).unwrap_or(0),
+        ms => ms,
+    };
+
+    let recorded = usage::record(&UsageRecord {
+        history_id: history_id.map(str::to_string),
+        provider: options.transcription.provider.as_str().to_string(),
+        model: transcript.model.clone(),
+        audio_seconds: duration_ms as f64 / 1000.0,
+        input_tokens: transcript.input_tokens,
+        output_tokens: transcript.output_tokens,
+    });
+    if let Err(e) = recorded {
+        log::error!("Failed to record usage: {}", e);
+        return None;
+    }
+
+    let warning = match usage::check_limit() {
+        Ok(warning) => warning?,
+        Err(e) => {
+            log::error!("Failed to check usage limit: {}", e);
+            return None;
+        }
+    };
+
+    let body = match warning.level {
+        LimitLevel::Approaching => format!(
+            "You've used ${:.2} of your ${:.2} monthly limit.",
+            warning.spent_usd, warning.limit_usd
+        ),
+        LimitLevel::Exceeded => format!(
+            "You've spent ${:.2}, over your ${:.2} monthly limit.",
+            warning.spent_usd, warning.limit_usd
+        ),
+    };
+    notifications::send("REDE usage", &body).ok();
+    Some(warning)
+}
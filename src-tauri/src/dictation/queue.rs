@@ -0,0 +1,360 @@
+use super::{DictationError, DictationOptions};
+use crate::storage::database::{self, DatabaseError};
+use crate::system::notifications;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// How often the worker looks for due jobs
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// First retry delay; doubles per attempt up to `MAX_BACKOFF_SECS`
+const BASE_BACKOFF_SECS: i64 = 30;
+const MAX_BACKOFF_SECS: i64 = 30 * 60;
+
+/// Hosts probed to decide whether the machine is back online
+const CONNECTIVITY_PROBES: &[&str] = &[
+    "generativelanguage.googleapis.com:443",
+    "api.openai.com:443",
+];
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long a failed job stays listed before it's purged
+const FAILED_RETENTION_DAYS: u32 = 7;
+
+static QUEUE_DIR: once_cell::sync::Lazy<Mutex<Option<PathBuf>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(None));
+
+/// A dictation waiting for (or abandoned after) transcription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedJob {
+    pub id: String,
+    pub target_app: Option<String>,
+    pub status: String,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: String,
+    pub created_at: String,
+}
+
+/// Payload for `queued-dictation-completed` / `queued-dictation-failed` events
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueEvent {
+    pub job_id: String,
+    pub history_id: Option<String>,
+    pub text: Option<String>,
+    pub error: Option<String>,
+}
+
+struct DueJob {
+    id: String,
+    audio_path: String,
+    target_app: Option<String>,
+    options: String,
+    attempts: u32,
+}
+
+/// Set the directory where queued audio is kept
+pub fn initialize(dir: &Path) -> Result<(), DictationError> {
+    std::fs::create_dir_all(dir).map_err(|e| DictationError::QueueError(e.to_string()))?;
+
+    let mut queue_dir = QUEUE_DIR
+        .lock()
+        .map_err(|e| DictationError::QueueError(e.to_string()))?;
+    *queue_dir = Some(dir.to_path_buf());
+    Ok(())
+}
+
+/// Persist audio and options for a dictation that couldn't be transcribed
+pub fn enqueue(
+    audio: &[u8],
+    target_app: Option<&str>,
+    options: &DictationOptions,
+) -> Result<String, DictationError> {
+    let dir = QUEUE_DIR
+        .lock()
+        .map_err(|e| DictationError::QueueError(e.to_string()))?
+        .clone()
+        .ok_or_else(|| DictationError::QueueError("queue not initialized".to_string()))?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let audio_path = dir.join(format!("{}.wav", id));
+    std::fs::write(&audio_path, audio).map_err(|e| DictationError::QueueError(e.to_string()))?;
+
+    let options_json =
+        serde_json::to_string(options).map_err(|e| DictationError::QueueError(e.to_string()))?;
+
+    let conn = database::connect()?;
+    conn.execute(
+        "INSERT INTO transcription_queue (id, audio_path, target_app, options)
+         VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![id, audio_path.to_string_lossy(), target_app, options_json],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    Ok(id)
+}
+
+/// List jobs that are still pending or have permanently failed
+pub fn list() -> Result<Vec<QueuedJob>, DictationError> {
+    let conn = database::connect()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, target_app, status, attempts, last_error, next_attempt_at, created_at
+             FROM transcription_queue
+             WHERE status != 'completed'
+             ORDER BY created_at ASC",
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(QueuedJob {
+                id: row.get(0)?,
+                target_app: row.get(1)?,
+                status: row.get(2)?,
+                attempts: row.get(3)?,
+                last_error: row.get(4)?,
+                next_attempt_at: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    Ok(rows
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?)
+}
+
+/// Start the background worker that retries queued dictations
+pub fn start_worker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut online = true;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+
+            if let Err(e) = purge() {
+                log::error!("Failed to purge finished dictations: {}", e);
+            }
+
+            let now_online = is_online().await;
+            if now_online && !online {
+                log::info!("Connectivity restored, retrying queued dictations");
+                if let Err(e) = reset_backoff() {
+                    log::error!("Failed to reschedule queued dictations: {}", e);
+                }
+            }
+            online = now_online;
+
+            if online {
+                process_due(&app).await;
+            }
+        }
+    });
+}
+
+async fn is_online() -> bool {
+    for host in CONNECTIVITY_PROBES {
+        let connect = tokio::net::TcpStream::connect(*host);
+        if let Ok(Ok(_)) = tokio::time::timeout(PROBE_TIMEOUT, connect).await {
+            return true;
+        }
+    }
+    false
+}
+
+async fn process_due(app: &AppHandle) {
+    let jobs = match due_jobs() {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            log::error!("Failed to load queued dictations: {}", e);
+            return;
+        }
+    };
+
+    for job in jobs {
+        let audio = match std::fs::read(&job.audio_path) {
+            Ok(audio) => audio,
+            Err(e) => {
+                fail(app, &job, &format!("Queued audio is missing: {}", e));
+                continue;
+            }
+        };
+        let options: DictationOptions = match serde_json::from_str(&job.options) {
+            Ok(options) => options,
+            Err(e) => {
+                fail(app, &job, &format!("Invalid queued options: {}", e));
+                continue;
+            }
+        };
+
+        match super::run(&audio, job.target_app.as_deref(), &options).await {
+            Ok(result) => {
+                if let Err(e) = mark_completed(&job.id, &result.history_id) {
+                    log::error!("Failed to update queued dictation {}: {}", job.id, e);
+                }
+                std::fs::remove_file(&job.audio_path).ok();
+
+                let _ = app.emit(
+                    "queued-dictation-completed",
+                    QueueEvent {
+                        job_id: job.id.clone(),
+                        history_id: Some(result.history_id),
+                        text: Some(result.text),
+                        error: None,
+                    },
+                );
+                notifications::send(
+                    "Dictation ready",
+                    "A dictation recorded while offline has been transcribed. Paste it from History.",
+                )
+                .ok();
+            }
+            Err(DictationError::Transcription(e)) if e.is_retryable() => {
+                if let Err(e) = reschedule(&job, &e.to_string()) {
+                    log::error!("Failed to reschedule queued dictation {}: {}", job.id, e);
+                }
+                // The provider is still unreachable; leave the rest for the next round
+                break;
+            }
+            Err(e) => fail(app, &job, &e.to_string()),
+        }
+    }
+}
+
+fn due_jobs() -> Result<Vec<DueJob>, DatabaseError> {
+    let conn = database::connect()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, audio_path, target_app, options, attempts
+             FROM transcription_queue
+             WHERE status = 'pending' AND next_attempt_at <= datetime('now')
+             ORDER BY created_at ASC",
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(DueJob {
+                id: row.get(0)?,
+                audio_path: row.get(1)?,
+                target_app: row.get(2)?,
+                options: row.get(3)?,
+                attempts: row.get(4)?,
+            })
+        })
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
+
+/// Exponential backoff in seconds for the given number of failed attempts
+fn backoff_secs(attempts: u32) -> i64 {
+    BASE_BACKOFF_SECS
+        .saturating_mul(1i64 << attempts.min(16))
+        .min(MAX_BACKOFF_SECS)
+}
+
+fn reschedule(job: &DueJob, error: &str) -> Result<(), DatabaseError> {
+    let delay = format!("+{} seconds", backoff_secs(job.attempts));
+    let conn = database::connect()?;
+    conn.execute(
+        "UPDATE transcription_queue
+         SET attempts = attempts + 1, last_error = ?2,
+             next_attempt_at = datetime('now', ?3), updated_at = datetime('now')
+         WHERE id = ?1",
+        rusqlite::params![job.id, error, delay],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(())
+}
+
+/// Make every pending job due immediately
+fn reset_backoff() -> Result<(), DatabaseError> {
+    let conn = database::connect()?;
+    conn.execute(
+        "UPDATE transcription_queue SET next_attempt_at = datetime('now')
+         WHERE status = 'pending'",
+        [],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(())
+}
+
+fn mark_completed(id: &str, history_id: &str) -> Result<(), DatabaseError> {
+    let conn = database::connect()?;
+    conn.execute(
+        "UPDATE transcription_queue
+         SET status = 'completed', history_id = ?2, last_error = NULL, updated_at = datetime('now')
+         WHERE id = ?1",
+        rusqlite::params![id, history_id],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(())
+}
+
+/// Delete completed jobs, and failed ones past their retention, along with
+/// any audio left behind
+fn purge() -> Result<(), DatabaseError> {
+    let conn = database::connect()?;
+    let retention = format!("-{} days", FAILED_RETENTION_DAYS);
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, audio_path FROM transcription_queue
+             WHERE status = 'completed'
+                OR (status = 'failed' AND updated_at <= datetime('now', ?1))",
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    let finished = stmt
+        .query_map([&retention], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    for (id, audio_path) in finished {
+        if let Err(e) = std::fs::remove_file(&audio_path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("Failed to delete queued audio {}: {}", audio_path, e);
+                continue;
+            }
+        }
+        conn.execute("DELETE FROM transcription_queue WHERE id = ?1", [&id])
+            .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    }
+    Ok(())
+}
+
+fn fail(app: &AppHandle, job: &DueJob, error: &str) {
+    log::error!("Queued dictation {} failed: {}", job.id, error);
+    // Nothing retries a failed job, so its recording isn't kept
+    std::fs::remove_file(&job.audio_path).ok();
+
+    let updated = database::connect().and_then(|conn| {
+        conn.execute(
+            "UPDATE transcription_queue
+             SET status = 'failed', attempts = attempts + 1, last_error = ?2,
+                 updated_at = datetime('now')
+             WHERE id = ?1",
+            rusqlite::params![job.id, error],
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+    });
+    if let Err(e) = updated {
+        log::error!("Failed to update queued dictation {}: {}", job.id, e);
+    }
+
+    let _ = app.emit(
+        "queued-dictation-failed",
+        QueueEvent {
+            job_id: job.id.clone(),
+            history_id: None,
+            text: None,
+            error: Some(error.to_string()),
+        },
+    );
+}
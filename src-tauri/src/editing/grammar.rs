@@ -0,0 +1,194 @@
+use crate::text::similarity;
+use serde::{Deserialize, Serialize};
+
+/// What an editing command acts on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Target {
+    /// Everything REDE inserted last
+    That,
+    LastWord,
+    LastSentence,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", content = "target", rename_all = "snake_case")]
+pub enum EditCommand {
+    Delete(Target),
+    Select(Target),
+    Capitalize(Target),
+    Uppercase(Target),
+    Lowercase(Target),
+    Undo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ParsedCommand {
+    pub command: EditCommand,
+    /// 1.0 for an exact phrase, lower for a near miss ("delete dat")
+    pub confidence: f32,
+}
+
+/// Below this an utterance is inserted as text rather than run
+pub const MIN_CONFIDENCE: f32 = 0.85;
+
+/// Longest utterance considered a command, in words
+const MAX_WORDS: usize = 6;
+
+/// Builds a command from the target it acts on
+type Verb = fn(Target) -> EditCommand;
+
+const VERBS: &[(&str, Verb)] = &[
+    ("delete", EditCommand::Delete),
+    ("remove", EditCommand::Delete),
+    ("erase", EditCommand::Delete),
+    ("scratch", EditCommand::Delete),
+    ("select", EditCommand::Select),
+    ("highlight", EditCommand::Select),
+    ("capitalize", EditCommand::Capitalize),
+    ("cap", EditCommand::Capitalize),
+    ("uppercase", EditCommand::Uppercase),
+    ("all caps", EditCommand::Uppercase),
+    ("lowercase", EditCommand::Lowercase),
+];
+
+const TARGETS: &[(&str, Target)] = &[
+    ("that", Target::That),
+    ("this", Target::That),
+    ("it", Target::That),
+    ("last word", Target::LastWord),
+    ("previous word", Target::LastWord),
+    ("the last word", Target::LastWord),
+    ("last sentence", Target::LastSentence),
+    ("previous sentence", Target::LastSentence),
+    ("the last sentence", Target::LastSentence),
+];
+
+const UNDO_PHRASES: &[&str] = &["undo", "undo that", "undo it", "undo last"];
+
+/// Politeness around a command ("okay delete that please")
+const LEADING_FILLER: &[&str] = &["please", "ok", "okay", "now"];
+const TRAILING_FILLER: &[&str] = &["please", "now"];
+
+/// Read a whole utterance as an editing command
+///
+/// Returns the closest command and how sure the match is; callers compare
+/// `confidence` against `MIN_CONFIDENCE`. Anything longer than a few words
+/// is dictation, however close it looks.
+pub fn parse(utterance: &str) -> Option<ParsedCommand> {
+    let cleaned: String = utterance
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '\'' {
+                c.to_ascii_lowercase()
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    let mut words: Vec<&str> = cleaned.split_whitespace().collect();
+    while words.first().is_some_and(|w| LEADING_FILLER.contains(w)) {
+        words.remove(0);
+    }
+    while words.last().is_some_and(|w| TRAILING_FILLER.contains(w)) {
+        words.pop();
+    }
+    if words.is_empty() || words.len() > MAX_WORDS {
+        return None;
+    }
+    let phrase = words.join(" ");
+
+    let mut best: Option<ParsedCommand> = None;
+    let mut consider = |candidate: &str, command: EditCommand| {
+        let confidence = similarity::ratio(&phrase, candidate);
+        let better = match best {
+            Some(b) => confidence > b.confidence,
+            None => true,
+        };
+        if better {
+            best = Some(ParsedCommand {
+                command,
+                confidence,
+            });
+        }
+    };
+
+    for undo in UNDO_PHRASES {
+        consider(undo, EditCommand::Undo);
+    }
+    for (verb, command) in VERBS {
+        for (target_phrase, target) in TARGETS {
+            consider(&format!("{} {}", verb, target_phrase), command(*target));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The command an utterance runs, if it's confident enough to run
+    fn command(utterance: &str) -> Option<EditCommand> {
+        parse(utterance)
+            .filter(|p| p.confidence >= MIN_CONFIDENCE)
+            .map(|p| p.command)
+    }
+
+    #[test]
+    fn reads_editing_commands() {
+        let cases: &[(&str, EditCommand)] = &[
+            ("delete that", EditCommand::Delete(Target::That)),
+            ("Delete that.", EditCommand::Delete(Target::That)),
+            (
+                "okay, scratch that please",
+                EditCommand::Delete(Target::That),
+            ),
+            (
+                "remove the last word",
+                EditCommand::Delete(Target::LastWord),
+            ),
+            (
+                "select last sentence",
+                EditCommand::Select(Target::LastSentence),
+            ),
+            ("capitalize that", EditCommand::Capitalize(Target::That)),
+            (
+                "all caps last word",
+                EditCommand::Uppercase(Target::LastWord),
+            ),
+            ("lowercase it", EditCommand::Lowercase(Target::That)),
+            ("undo", EditCommand::Undo),
+            ("Undo that!", EditCommand::Undo),
+        ];
+        for (utterance, expected) in cases {
+            assert_eq!(
+                command(utterance),
+                Some(*expected),
+                "utterance: {:?}",
+                utterance
+            );
+        }
+        assert_eq!(parse("delete that").map(|p| p.confidence), Some(1.0));
+
+        // A near miss is still the closest command, just less sure
+        let near = parse("delete dat").unwrap();
+        assert_eq!(near.command, EditCommand::Delete(Target::That));
+        assert!(near.confidence < 1.0);
+    }
+
+    #[test]
+    fn leaves_dictation_alone() {
+        let cases: &[&str] = &[
+            "",
+            "please",
+            "hello there",
+            "select a good time",
+            "delete the old files from the shared drive",
+            "I want to undo that change we made yesterday",
+        ];
+        for utterance in cases {
+            assert_eq!(command(utterance), None, "utterance: {:?}", utterance);
+        }
+    }
+}
@@ -0,0 +1,437 @@
+pub mod grammar;
+
+use crate::accessibility::text_insertion::{self, InsertionError};
+use grammar::{EditCommand, Target};
+use std::sync::Mutex;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum EditError {
+    #[error("Nothing dictated to edit")]
+    NothingToEdit,
+    #[error("The cursor has moved since the last dictation")]
+    CursorMoved,
+    #[error("Edit journal unavailable: {0}")]
+    JournalError(String),
+    #[error(transparent)]
+    Insertion(#[from] InsertionError),
+}
+
+/// Most edits kept for "undo that", and inserted segments kept with them
+const MAX_ACTIONS: usize = 50;
+
+const SENTENCE_ENDS: &[char] = &['.', '!', '?', '…'];
+
+/// Something REDE did to the focused field, and enough to reverse it
+#[derive(Debug, Clone)]
+enum Action {
+    Inserted(String),
+    /// `whole` when the deletion removed an entire inserted segment
+    Deleted {
+        text: String,
+        whole: bool,
+    },
+    Replaced {
+        before: String,
+        after: String,
+    },
+}
+
+/// REDE's record of the text it has put before the cursor
+///
+/// Commands only ever touch the end of the last segment, which is where the
+/// cursor was left. Anything that moves the cursor elsewhere invalidates the
+/// record, so it is cleared rather than trusted.
+#[derive(Debug, Default)]
+struct Journal {
+    segments: Vec<String>,
+    actions: Vec<Action>,
+}
+
+impl Journal {
+    fn push(&mut self, action: Action) {
+        if self.actions.len() == MAX_ACTIONS {
+            self.actions.remove(0);
+        }
+        self.actions.push(action);
+    }
+
+    /// Remember an inserted segment; no more are kept than undo can reach
+    fn push_segment(&mut self, text: String) {
+        if self.segments.len() == MAX_ACTIONS {
+            self.segments.remove(0);
+        }
+        self.segments.push(text);
+    }
+
+    fn clear(&mut self) {
+        self.segments.clear();
+        self.actions.clear();
+    }
+
+    fn set_last(&mut self, text: String) {
+        if let Some(last) = self.segments.last_mut() {
+            *last = text;
+        }
+    }
+
+    /// Remember text typed at the cursor
+    fn inserted(&mut self, text: &str) {
+        if !text.is_empty() {
+            self.push_segment(text.to_string());
+            self.push(Action::Inserted(text.to_string()));
+        }
+    }
+
+    /// Drop the target from the end of the last segment
+    fn delete(&mut self, target: Target) -> FieldEdit {
+        let last = self.segments.last().cloned().unwrap_or_default();
+        let (kept, removed) = split_target(&last, target);
+        let whole = kept.is_empty();
+        if whole {
+            self.segments.pop();
+        } else {
+            self.set_last(kept);
+        }
+        let edit = FieldEdit {
+            delete: removed.chars().count(),
+            insert: String::new(),
+        };
+        self.push(Action::Deleted {
+            text: removed,
+            whole,
+        });
+        edit
+    }
+
+    /// Apply `change` to the target at the end of the last segment
+    fn transform(&mut self, target: Target, change: fn(&str) -> String) -> FieldEdit {
+        let last = self.segments.last().cloned().unwrap_or_default();
+        let (kept, before) = split_target(&last, target);
+        let after = change(&before);
+        if after == before {
+            return FieldEdit::default();
+        }
+
+        let edit = FieldEdit {
+            delete: before.chars().count(),
+            insert: after.clone(),
+        };
+        self.set_last(format!("{}{}", kept, after));
+        self.push(Action::Replaced { before, after });
+        edit
+    }
+
+    /// Reverse the latest action
+    fn undo(&mut self) -> Option<FieldEdit> {
+        let edit = match self.actions.pop()? {
+            Action::Inserted(text) => {
+                self.segments.pop();
+                FieldEdit {
+                    delete: text.chars().count(),
+                    insert: String::new(),
+                }
+            }
+            Action::Deleted { text, whole } => {
+                if whole {
+                    self.push_segment(text.clone());
+                } else {
+                    let last = self.segments.last().cloned().unwrap_or_default();
+                    self.set_last(format!("{}{}", last, text));
+                }
+                FieldEdit {
+                    delete: 0,
+                    insert: text,
+                }
+            }
+            Action::Replaced { before, after } => {
+                let last = self.segments.last().cloned().unwrap_or_default();
+                let kept = last.strip_suffix(after.as_str()).unwrap_or(&last);
+                self.set_last(format!("{}{}", kept, before));
+                FieldEdit {
+                    delete: after.chars().count(),
+                    insert: before,
+                }
+            }
+        };
+        Some(edit)
+    }
+
+    /// Whether undoing the latest action removes text from the field
+    fn undo_deletes(&self) -> bool {
+        matches!(
+            self.actions.last(),
+            Some(Action::Inserted(_) | Action::Replaced { .. })
+        )
+    }
+}
+
+/// What to type to bring the field in line with the journal: `delete`
+/// characters before the cursor, then `insert`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct FieldEdit {
+    delete: usize,
+    insert: String,
+}
+
+static JOURNAL: once_cell::sync::Lazy<Mutex<Journal>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(Journal::default()));
+
+/// Insert text at the cursor and remember it for editing commands
+pub fn insert(text: &str) -> Result<(), EditError> {
+    let mut journal = JOURNAL
+        .lock()
+        .map_err(|e| EditError::JournalError(e.to_string()))?;
+    text_insertion::insert(text)?;
+    journal.inserted(text);
+    Ok(())
+}
+
+/// Replace the focused app's selection, which was `selection`, with text
+///
+/// The selection can be anywhere, so earlier dictations are forgotten;
+/// "undo that" puts the selection's text back.
+pub fn replace_selection(selection: &str, text: &str) -> Result<(), EditError> {
+    let mut journal = JOURNAL
+        .lock()
+        .map_err(|e| EditError::JournalError(e.to_string()))?;
+    text_insertion::insert(text)?;
+
+    journal.clear();
+    journal.push_segment(text.to_string());
+    journal.push(Action::Replaced {
+        before: selection.to_string(),
+        after: text.to_string(),
+    });
+    Ok(())
+}
+
+/// Run an editing command against the last inserted text
+///
+/// Commands that delete are refused when the field can't be read, since
+/// backspacing blind would erase anything typed since the dictation.
+pub fn apply(command: EditCommand) -> Result<(), EditError> {
+    let mut journal = JOURNAL
+        .lock()
+        .map_err(|e| EditError::JournalError(e.to_string()))?;
+    let last = journal
+        .segments
+        .last()
+        .cloned()
+        .ok_or(EditError::NothingToEdit)?;
+    let deletes = match command {
+        EditCommand::Undo => journal.undo_deletes(),
+        EditCommand::Select(_) => false,
+        _ => true,
+    };
+    match cursor_after(&last) {
+        Some(true) => {}
+        Some(false) => {
+            journal.clear();
+            return Err(EditError::CursorMoved);
+        }
+        None if deletes => return Err(EditError::CursorMoved),
+        None => {}
+    }
+
+    let edit = match command {
+        EditCommand::Undo => journal.undo().ok_or(EditError::NothingToEdit)?,
+        EditCommand::Delete(target) => journal.delete(target),
+        EditCommand::Select(target) => {
+            let (_, selected) = split_target(&last, target);
+            text_insertion::select_before_cursor(selected.trim_start().chars().count())?;
+            // Whatever is typed next replaces the selection
+            journal.clear();
+            return Ok(());
+        }
+        EditCommand::Capitalize(target) => journal.transform(target, title_case),
+        EditCommand::Uppercase(target) => journal.transform(target, str::to_uppercase),
+        EditCommand::Lowercase(target) => journal.transform(target, str::to_lowercase),
+    };
+    retype(&mut journal, &edit)
+}
+
+/// Type an edit into the field; the journal can't be trusted if that fails
+fn retype(journal: &mut Journal, edit: &FieldEdit) -> Result<(), EditError> {
+    let mut typed = Ok(());
+    if edit.delete > 0 {
+        typed = text_insertion::delete_before_cursor(edit.delete);
+    }
+    if typed.is_ok() && !edit.insert.is_empty() {
+        typed = text_insertion::insert(&edit.insert);
+    }
+    if typed.is_err() {
+        journal.clear();
+    }
+    Ok(typed?)
+}
+
+/// Whether the field still ends with what REDE last inserted, or `None`
+/// when it can't be read
+fn cursor_after(last: &str) -> Option<bool> {
+    match text_insertion::text_before_cursor(last.chars().count()) {
+        Ok(before) => before.map(|before| before.ends_with(last)),
+        Err(e) => {
+            log::debug!("Couldn't read text before cursor: {}", e);
+            None
+        }
+    }
+}
+
+/// Split a segment into the text that stays and the target at its end
+///
+/// The target takes the space in front of it, so deleting the last word of
+/// "Hello world." leaves "Hello".
+fn split_target(segment: &str, target: Target) -> (String, String) {
+    let start = match target {
+        Target::That => 0,
+        Target::LastWord => {
+            let end = segment.trim_end().len();
+            segment[..end].rfind(char::is_whitespace).unwrap_or(0)
+        }
+        Target::LastSentence => {
+            let body = segment.trim_end().trim_end_matches(SENTENCE_ENDS);
+            body.char_indices()
+                .rev()
+                .find(|(i, c)| {
+                    *c == '\n'
+                        || (SENTENCE_ENDS.contains(c)
+                            && body[i + c.len_utf8()..].starts_with(char::is_whitespace))
+                })
+                .map_or(0, |(i, c)| i + c.len_utf8())
+        }
+    };
+    let start = segment[..start].trim_end().len();
+    (segment[..start].to_string(), segment[start..].to_string())
+}
+
+/// "hello world" to "Hello World"
+fn title_case(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut word_start = true;
+    for c in text.chars() {
+        if word_start && c.is_alphabetic() {
+            out.extend(c.to_uppercase());
+        } else {
+            out.push(c);
+        }
+        word_start = c.is_whitespace();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(delete: usize, insert: &str) -> FieldEdit {
+        FieldEdit {
+            delete,
+            insert: insert.to_string(),
+        }
+    }
+
+    #[test]
+    fn splits_targets() {
+        let cases: &[(&str, Target, (&str, &str))] = &[
+            ("Hello world.", Target::That, ("", "Hello world.")),
+            ("Hello world.", Target::LastWord, ("Hello", " world.")),
+            ("Hello", Target::LastWord, ("", "Hello")),
+            ("Hello world. ", Target::LastWord, ("Hello", " world. ")),
+            (
+                "One. Two three.",
+                Target::LastSentence,
+                ("One.", " Two three."),
+            ),
+            ("Is it? Yes!", Target::LastSentence, ("Is it?", " Yes!")),
+            (
+                "Version 2.5 is out.",
+                Target::LastSentence,
+                ("", "Version 2.5 is out."),
+            ),
+            (
+                "line one\nline two",
+                Target::LastSentence,
+                ("line one", "\nline two"),
+            ),
+        ];
+        for (segment, target, (kept, removed)) in cases {
+            assert_eq!(
+                split_target(segment, *target),
+                (kept.to_string(), removed.to_string()),
+                "segment: {:?}, target: {:?}",
+                segment,
+                target
+            );
+        }
+    }
+
+    #[test]
+    fn title_cases() {
+        let cases: &[(&str, &str)] = &[
+            ("hello world", "Hello World"),
+            (" hello", " Hello"),
+            ("it's a well-known fact", "It's A Well-known Fact"),
+            ("élan vital", "Élan Vital"),
+            ("line one\nline two", "Line One\nLine Two"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(title_case(input), *expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn undoes_deletions() {
+        let mut journal = Journal::default();
+        journal.inserted("Hello world.");
+
+        assert_eq!(journal.delete(Target::LastWord), edit(7, ""));
+        assert_eq!(journal.segments, ["Hello"]);
+        assert!(!journal.undo_deletes());
+        assert_eq!(journal.undo(), Some(edit(0, " world.")));
+        assert_eq!(journal.segments, ["Hello world."]);
+
+        assert_eq!(journal.delete(Target::That), edit(12, ""));
+        assert!(journal.segments.is_empty());
+        assert_eq!(journal.undo(), Some(edit(0, "Hello world.")));
+        assert_eq!(journal.segments, ["Hello world."]);
+
+        assert!(journal.undo_deletes());
+        assert_eq!(journal.undo(), Some(edit(12, "")));
+        assert!(journal.segments.is_empty());
+        assert_eq!(journal.undo(), None);
+    }
+
+    #[test]
+    fn undoes_transforms() {
+        let mut journal = Journal::default();
+        journal.inserted("see you there");
+
+        assert_eq!(
+            journal.transform(Target::LastWord, str::to_uppercase),
+            edit(6, " THERE")
+        );
+        assert_eq!(journal.segments, ["see you THERE"]);
+        assert_eq!(
+            journal.transform(Target::LastWord, str::to_uppercase),
+            FieldEdit::default()
+        );
+        assert!(journal.undo_deletes());
+        assert_eq!(journal.undo(), Some(edit(6, " there")));
+        assert_eq!(journal.segments, ["see you there"]);
+    }
+
+    #[test]
+    fn keeps_a_bounded_history() {
+        let mut journal = Journal::default();
+        for i in 0..MAX_ACTIONS + 5 {
+            journal.inserted(&format!("{} ", i));
+        }
+        assert_eq!(journal.segments.len(), MAX_ACTIONS);
+        assert_eq!(journal.actions.len(), MAX_ACTIONS);
+        assert_eq!(
+            journal.segments.last().unwrap(),
+            &format!("{} ", MAX_ACTIONS + 4)
+        );
+    }
+}
@@ -1,10 +1,32 @@
+use crate::storage::database::DatabaseError;
+use crate::storage::settings;
+use rdev::Key;
 use serde::{Deserialize, Serialize};
 
+const CONFIG_KEY: &str = "hotkey_config";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HotkeyConfig {
     pub key: String,
     pub modifiers: Vec<String>,
     pub mode: ActivationMode,
+    /// Held instead of `key` to dictate letter by letter
+    #[serde(default)]
+    pub spelling_key: Option<String>,
+    /// Held instead of `key` to dictate and insert the text in another language
+    #[serde(default)]
+    pub translation_keys: Vec<TranslationHotkey>,
+    /// Held instead of `key` to speak an instruction for the selected text
+    /// ("make this more polite")
+    #[serde(default)]
+    pub transform_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationHotkey {
+    pub key: String,
+    /// Language the text is inserted in ("en", "pt-BR")
+    pub language: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,10 +41,121 @@ impl Default for HotkeyConfig {
             key: "Control".to_string(),
             modifiers: vec![],
             mode: ActivationMode::Push,
+            spelling_key: None,
+            translation_keys: vec![],
+            transform_key: None,
         }
     }
 }
 
+/// The saved hotkey configuration, or the default
+pub fn load() -> Result<HotkeyConfig, DatabaseError> {
+    let stored = settings::get(CONFIG_KEY)?
+        .and_then(|json| serde_json::from_str::<HotkeyConfig>(&json).ok());
+    Ok(stored.unwrap_or_default())
+}
+
+/// Save the hotkey configuration; the listener picks it up on `reload`
+pub fn save(config: &HotkeyConfig) -> Result<(), DatabaseError> {
+    let json =
+        serde_json::to_string(config).map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    settings::set(CONFIG_KEY, &json)
+}
+
+/// Modifier names accepted in `HotkeyConfig::modifiers`, in the order
+/// macOS shows them
+pub const MODIFIERS: &[(&str, Key)] = &[
+    ("Control", Key::ControlLeft),
+    ("Option", Key::Alt),
+    ("Shift", Key::ShiftLeft),
+    ("Command", Key::MetaLeft),
+];
+
+/// Key names accepted in `HotkeyConfig::key`, besides the modifiers
+///
+/// Names follow the Mac keyboard: "Delete" is the key left of the cursor
+/// (Backspace elsewhere) and "ForwardDelete" the one right of it.
+pub const KEYS: &[(&str, Key)] = &[
+    ("A", Key::KeyA),
+    ("B", Key::KeyB),
+    ("C", Key::KeyC),
+    ("D", Key::KeyD),
+    ("E", Key::KeyE),
+    ("F", Key::KeyF),
+    ("G", Key::KeyG),
+    ("H", Key::KeyH),
+    ("I", Key::KeyI),
+    ("J", Key::KeyJ),
+    ("K", Key::KeyK),
+    ("L", Key::KeyL),
+    ("M", Key::KeyM),
+    ("N", Key::KeyN),
+    ("O", Key::KeyO),
+    ("P", Key::KeyP),
+    ("Q", Key::KeyQ),
+    ("R", Key::KeyR),
+    ("S", Key::KeyS),
+    ("T", Key::KeyT),
+    ("U", Key::KeyU),
+    ("V", Key::KeyV),
+    ("W", Key::KeyW),
+    ("X", Key::KeyX),
+    ("Y", Key::KeyY),
+    ("Z", Key::KeyZ),
+    ("0", Key::Num0),
+    ("1", Key::Num1),
+    ("2", Key::Num2),
+    ("3", Key::Num3),
+    ("4", Key::Num4),
+    ("5", Key::Num5),
+    ("6", Key::Num6),
+    ("7", Key::Num7),
+    ("8", Key::Num8),
+    ("9", Key::Num9),
+    ("F1", Key::F1),
+    ("F2", Key::F2),
+    ("F3", Key::F3),
+    ("F4", Key::F4),
+    ("F5", Key::F5),
+    ("F6", Key::F6),
+    ("F7", Key::F7),
+    ("F8", Key::F8),
+    ("F9", Key::F9),
+    ("F10", Key::F10),
+    ("F11", Key::F11),
+    ("F12", Key::F12),
+    ("Escape", Key::Escape),
+    ("Return", Key::Return),
+    ("Tab", Key::Tab),
+    ("Space", Key::Space),
+    ("Delete", Key::Backspace),
+    ("ForwardDelete", Key::Delete),
+    ("Up", Key::UpArrow),
+    ("Down", Key::DownArrow),
+    ("Left", Key::LeftArrow),
+    ("Right", Key::RightArrow),
+    ("Home", Key::Home),
+    ("End", Key::End),
+    ("PageUp", Key::PageUp),
+    ("PageDown", Key::PageDown),
+];
+
+/// Look up a modifier by name, ignoring case
+pub fn modifier(name: &str) -> Option<(&'static str, Key)> {
+    MODIFIERS
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .copied()
+}
+
+/// Look up a key by name, ignoring case; a modifier on its own is a key too
+pub fn key(name: &str) -> Option<(&'static str, Key)> {
+    KEYS.iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .copied()
+        .or_else(|| modifier(name))
+}
+
 /// State tracking for push-to-talk debouncing
 pub struct HotkeyState {
     pub is_pressed: bool,
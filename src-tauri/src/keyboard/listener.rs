@@ -1,26 +1,169 @@
+use super::hotkey::{self, HotkeyConfig, HotkeyState};
+use crate::accessibility::text_insertion;
+use once_cell::sync::Lazy;
+use rdev::{Event, EventType, Key};
+use std::collections::HashMap;
+use std::sync::Mutex;
 use tauri::{AppHandle, Emitter};
 
+/// What a configured key starts
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Action {
+    Dictate,
+    Spelling,
+    /// A dictation translated into this language
+    Translation(String),
+    Transform,
+}
+
+/// Keys bound by the current `HotkeyConfig`
+#[derive(Default)]
+struct Bindings {
+    modifiers: Vec<Key>,
+    keys: Vec<(Key, Action)>,
+}
+
+impl Bindings {
+    fn from_config(config: &HotkeyConfig) -> Self {
+        let lookup = |name: &str| {
+            let key = hotkey::key(name).map(|(_, key)| key);
+            if key.is_none() {
+                log::warn!("Unknown hotkey {:?}", name);
+            }
+            key
+        };
+
+        let mut keys = Vec::new();
+        keys.extend(lookup(&config.key).map(|key| (key, Action::Dictate)));
+        if let Some(key) = config.spelling_key.as_deref().and_then(lookup) {
+            keys.push((key, Action::Spelling));
+        }
+        for translation in &config.translation_keys {
+            if let Some(key) = lookup(&translation.key) {
+                keys.push((key, Action::Translation(translation.language.clone())));
+            }
+        }
+        if let Some(key) = config.transform_key.as_deref().and_then(lookup) {
+            keys.push((key, Action::Transform));
+        }
+
+        let modifiers = config
+            .modifiers
+            .iter()
+            .filter_map(|name| hotkey::modifier(name).map(|(_, key)| key))
+            .collect();
+        Self { modifiers, keys }
+    }
+
+    fn action(&self, key: Key) -> Option<&Action> {
+        self.keys
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, action)| action)
+    }
+}
+
+static BINDINGS: Lazy<Mutex<Bindings>> = Lazy::new(|| Mutex::new(Bindings::default()));
+
 /// Start the global keyboard listener
 /// Runs in a dedicated thread and emits events to the frontend
 pub fn start(app: AppHandle) {
-    // TODO: Implement using rdev
-    // use rdev::{listen, Event, EventType, Key};
-    //
-    // listen(move |event: Event| {
-    //     match event.event_type {
-    //         EventType::KeyPress(Key::ControlLeft) | EventType::KeyPress(Key::ControlRight) => {
-    //             app.emit("hotkey-pressed", ()).ok();
-    //         }
-    //         EventType::KeyRelease(Key::ControlLeft) | EventType::KeyRelease(Key::ControlRight) => {
-    //             app.emit("hotkey-released", ()).ok();
-    //         }
-    //         EventType::KeyPress(Key::Escape) => {
-    //             app.emit("recording-cancelled", ()).ok();
-    //         }
-    //         _ => {}
-    //     }
-    // }).expect("Failed to start keyboard listener");
-
+    reload();
     log::info!("Keyboard listener started");
     let _ = app.emit("keyboard-listener-ready", ());
+
+    let mut held: Vec<Key> = Vec::new();
+    let mut states: HashMap<Action, HotkeyState> = HashMap::new();
+    let listened = rdev::listen(move |event: Event| match event.event_type {
+        EventType::KeyPress(Key::Escape) => {
+            app.emit("recording-cancelled", ()).ok();
+        }
+        EventType::KeyPress(key) => {
+            let key = left_hand(key);
+            if !held.contains(&key) {
+                held.push(key);
+            }
+            let bindings = BINDINGS.lock().unwrap();
+            let Some(action) = bindings.action(key) else {
+                return;
+            };
+            let modifiers_held = bindings
+                .modifiers
+                .iter()
+                .all(|modifier| *modifier == key || held.contains(modifier));
+            // Key repeat sends presses while the key is held
+            if modifiers_held && states.entry(action.clone()).or_default().on_press() {
+                pressed(&app, action);
+            }
+        }
+        EventType::KeyRelease(key) => {
+            let key = left_hand(key);
+            held.retain(|k| *k != key);
+            let bindings = BINDINGS.lock().unwrap();
+            let Some(action) = bindings.action(key) else {
+                return;
+            };
+            if states.get_mut(action).is_some_and(HotkeyState::on_release) {
+                released(&app, action);
+            }
+        }
+        _ => {}
+    });
+
+    if let Err(e) = listened {
+        log::error!("Keyboard listener stopped: {:?}", e);
+    }
+}
+
+/// Rebind keys from the saved `HotkeyConfig`
+pub fn reload() {
+    let config = hotkey::load().unwrap_or_else(|e| {
+        log::warn!("Couldn't load the hotkey config, using the default: {}", e);
+        HotkeyConfig::default()
+    });
+    *BINDINGS.lock().unwrap() = Bindings::from_config(&config);
+}
+
+fn pressed(app: &AppHandle, action: &Action) {
+    match action {
+        Action::Dictate => app.emit("hotkey-pressed", ()),
+        Action::Spelling => app.emit("spelling-hotkey-pressed", ()),
+        Action::Translation(language) => app.emit("translation-hotkey-pressed", language),
+        Action::Transform => {
+            // The selection is read before recording starts, off this thread
+            // so key events keep flowing while the app answers
+            let app = app.clone();
+            std::thread::spawn(move || {
+                let selection = text_insertion::selected_text().unwrap_or_else(|e| {
+                    log::warn!("Couldn't read the selection: {}", e);
+                    None
+                });
+                app.emit("transform-hotkey-pressed", selection).ok();
+            });
+            Ok(())
+        }
+    }
+    .ok();
+}
+
+fn released(app: &AppHandle, action: &Action) {
+    match action {
+        Action::Dictate => app.emit("hotkey-released", ()),
+        Action::Spelling => app.emit("spelling-hotkey-released", ()),
+        Action::Translation(language) => app.emit("translation-hotkey-released", language),
+        Action::Transform => app.emit("transform-hotkey-released", ()),
+    }
+    .ok();
+}
+
+/// The left-hand key for a right-hand modifier, which `hotkey` names by
+/// the left one ("Control" is either Control key)
+fn left_hand(key: Key) -> Key {
+    match key {
+        Key::ControlRight => Key::ControlLeft,
+        Key::ShiftRight => Key::ShiftLeft,
+        Key::MetaRight => Key::MetaLeft,
+        Key::AltGr => Key::Alt,
+        key => key,
+    }
 }
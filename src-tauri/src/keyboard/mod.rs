@@ -1,2 +1,3 @@
 pub mod hotkey;
 pub mod listener;
+pub mod shortcuts;
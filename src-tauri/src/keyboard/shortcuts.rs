@@ -0,0 +1,326 @@
+use super::hotkey;
+use crate::accessibility::keystrokes;
+use crate::storage::database::DatabaseError;
+use crate::storage::settings;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ShortcutError {
+    #[error("Unknown key or modifier: {0}")]
+    UnknownKey(String),
+    #[error("Shortcut {0} is not in the allowlist")]
+    Blocked(String),
+    #[error("Failed to press shortcut: {0}")]
+    KeystrokeFailed(String),
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+}
+
+/// Settings key holding the allowed chords as a JSON array of labels
+const ALLOWLIST_KEY: &str = "shortcut_allowlist";
+
+/// Chords that can be pressed by voice until the user sets their own list
+///
+/// Editing and navigation only. Anything that quits, logs out, locks the
+/// screen or deletes files (Command+Q, Control+Command+Q,
+/// Command+Option+Escape, Command+Delete) is left off.
+const DEFAULT_ALLOWLIST: &[&str] = &[
+    "Escape",
+    "Return",
+    "Tab",
+    "Space",
+    "Delete",
+    "Up",
+    "Down",
+    "Left",
+    "Right",
+    "Home",
+    "End",
+    "PageUp",
+    "PageDown",
+    "Shift+Return",
+    "Shift+Tab",
+    "Command+A",
+    "Command+B",
+    "Command+C",
+    "Command+F",
+    "Command+I",
+    "Command+K",
+    "Command+L",
+    "Command+N",
+    "Command+S",
+    "Command+T",
+    "Command+U",
+    "Command+V",
+    "Command+X",
+    "Command+Z",
+    "Command+Return",
+    "Command+Left",
+    "Command+Right",
+    "Command+Up",
+    "Command+Down",
+    "Option+Left",
+    "Option+Right",
+    "Shift+Command+T",
+    "Shift+Command+Z",
+];
+
+/// Spoken names for keys, mapped to the names `HotkeyConfig` uses
+///
+/// Letters, digits and function keys ("t", "7", "f5") are looked up as is.
+const SPOKEN: &[(&str, &str)] = &[
+    ("command", "Command"),
+    ("cmd", "Command"),
+    ("control", "Control"),
+    ("ctrl", "Control"),
+    ("option", "Option"),
+    ("alt", "Option"),
+    ("shift", "Shift"),
+    ("escape", "Escape"),
+    ("esc", "Escape"),
+    ("enter", "Return"),
+    ("return", "Return"),
+    ("tab", "Tab"),
+    ("space", "Space"),
+    ("spacebar", "Space"),
+    ("space bar", "Space"),
+    ("delete", "Delete"),
+    ("backspace", "Delete"),
+    ("back space", "Delete"),
+    ("forward delete", "ForwardDelete"),
+    ("up", "Up"),
+    ("up arrow", "Up"),
+    ("down", "Down"),
+    ("down arrow", "Down"),
+    ("left", "Left"),
+    ("left arrow", "Left"),
+    ("right", "Right"),
+    ("right arrow", "Right"),
+    ("home", "Home"),
+    ("end", "End"),
+    ("page up", "PageUp"),
+    ("page down", "PageDown"),
+    ("zero", "0"),
+    ("one", "1"),
+    ("two", "2"),
+    ("three", "3"),
+    ("four", "4"),
+    ("five", "5"),
+    ("six", "6"),
+    ("seven", "7"),
+    ("eight", "8"),
+    ("nine", "9"),
+];
+
+/// Words that introduce a shortcut ("press command t")
+const TRIGGERS: &[&str] = &["press", "hit"];
+
+/// Words between key names that carry no meaning ("control and c")
+const JOINERS: &[&str] = &["and", "plus", "the", "key"];
+
+/// A key pressed while holding zero or more modifiers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chord {
+    /// Modifier names, in the order macOS shows them
+    pub modifiers: Vec<String>,
+    pub key: String,
+}
+
+impl Chord {
+    /// "Shift+Command+T"
+    pub fn label(&self) -> String {
+        let mut parts: Vec<&str> = self.modifiers.iter().map(String::as_str).collect();
+        parts.push(&self.key);
+        parts.join("+")
+    }
+
+    /// Parse a label such as "command+shift+t", validating every name
+    pub fn from_label(label: &str) -> Result<Self, ShortcutError> {
+        let names: Vec<&str> = label.split('+').map(str::trim).collect();
+        Self::from_names(&names).ok_or_else(|| ShortcutError::UnknownKey(label.to_string()))
+    }
+
+    /// All names but the last must be modifiers, each used once
+    fn from_names(names: &[&str]) -> Option<Self> {
+        let (key, modifiers) = names.split_last()?;
+        let (key, _) = hotkey::key(key)?;
+        let mut held = Vec::with_capacity(modifiers.len());
+        for name in modifiers {
+            let (name, _) = hotkey::modifier(name)?;
+            if held.contains(&name) || name == key {
+                return None;
+            }
+            held.push(name);
+        }
+
+        Some(Self {
+            modifiers: hotkey::MODIFIERS
+                .iter()
+                .map(|(name, _)| *name)
+                .filter(|name| held.contains(name))
+                .map(str::to_string)
+                .collect(),
+            key: key.to_string(),
+        })
+    }
+}
+
+/// Read an utterance like "press command shift T" as a key chord
+///
+/// Every word after "press" has to name a key or modifier; anything else
+/// means the utterance was dictation and `None` is returned.
+pub fn parse(utterance: &str) -> Option<Chord> {
+    let cleaned: String = utterance
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    let mut words: Vec<&str> = cleaned.split_whitespace().collect();
+    if words.first() == Some(&"please") {
+        words.remove(0);
+    }
+    if !words.first().is_some_and(|w| TRIGGERS.contains(w)) {
+        return None;
+    }
+
+    let mut names: Vec<&str> = Vec::new();
+    let mut i = 1;
+    while i < words.len() {
+        if JOINERS.contains(&words[i]) {
+            i += 1;
+            continue;
+        }
+        let pair = words
+            .get(i + 1)
+            .map(|next| format!("{} {}", words[i], next));
+        if let Some(name) = pair.as_deref().and_then(spoken) {
+            names.push(name);
+            i += 2;
+            continue;
+        }
+        names.push(spoken(words[i]).or_else(|| hotkey::key(words[i]).map(|(name, _)| name))?);
+        i += 1;
+    }
+
+    Chord::from_names(&names)
+}
+
+fn spoken(phrase: &str) -> Option<&'static str> {
+    SPOKEN
+        .iter()
+        .find(|(spoken, _)| *spoken == phrase)
+        .map(|(_, name)| *name)
+}
+
+/// Chords that may be pressed by voice
+pub fn allowlist() -> Result<Vec<String>, DatabaseError> {
+    let stored = settings::get(ALLOWLIST_KEY)?
+        .and_then(|json| serde_json::from_str::<Vec<String>>(&json).ok());
+    Ok(stored.unwrap_or_else(|| DEFAULT_ALLOWLIST.iter().map(|c| c.to_string()).collect()))
+}
+
+/// Replace the allowlist, or restore the default with `None`
+///
+/// Labels are stored in canonical form so "command+shift+t" matches
+/// "Shift+Command+T".
+pub fn set_allowlist(chords: Option<Vec<String>>) -> Result<(), ShortcutError> {
+    let Some(chords) = chords else {
+        settings::remove(ALLOWLIST_KEY)?;
+        return Ok(());
+    };
+    let labels = chords
+        .iter()
+        .map(|label| Chord::from_label(label).map(|chord| chord.label()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let json =
+        serde_json::to_string(&labels).map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    settings::set(ALLOWLIST_KEY, &json)?;
+    Ok(())
+}
+
+/// Press a chord if the allowlist permits it
+pub fn press(chord: &Chord) -> Result<(), ShortcutError> {
+    let label = chord.label();
+    let allowed = allowlist()?
+        .iter()
+        .filter_map(|entry| Chord::from_label(entry).ok())
+        .any(|entry| entry == *chord);
+    if !allowed {
+        log::warn!("Blocked voice shortcut {}", label);
+        return Err(ShortcutError::Blocked(label));
+    }
+
+    let lookup = |name: &str| hotkey::key(name).map(|(_, key)| key);
+    let modifiers = chord
+        .modifiers
+        .iter()
+        .map(|name| lookup(name).ok_or_else(|| ShortcutError::UnknownKey(name.clone())))
+        .collect::<Result<Vec<_>, _>>()?;
+    let key = lookup(&chord.key).ok_or_else(|| ShortcutError::UnknownKey(chord.key.clone()))?;
+
+    keystrokes::chord(&modifiers, key).map_err(|e| ShortcutError::KeystrokeFailed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_spoken_chords() {
+        let cases: &[(&str, Option<&str>)] = &[
+            ("press control alt delete", Some("Control+Option+Delete")),
+            ("hit enter", Some("Return")),
+            ("Press command shift T.", Some("Shift+Command+T")),
+            ("press command and shift and t", Some("Shift+Command+T")),
+            ("please press the escape key", Some("Escape")),
+            ("press command plus left arrow", Some("Command+Left")),
+            ("press page down", Some("PageDown")),
+            ("press command seven", Some("Command+7")),
+            ("press the button now", None),
+            ("press command command t", None),
+            ("press", None),
+            ("command t", None),
+            ("I hit the road", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                parse(input).map(|chord| chord.label()).as_deref(),
+                *expected,
+                "input: {:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn reads_labels() {
+        let cases: &[(&str, Option<&str>)] = &[
+            ("command+shift+t", Some("Shift+Command+T")),
+            ("Shift + Command + T", Some("Shift+Command+T")),
+            ("Escape", Some("Escape")),
+            ("t+command", None),
+            ("command+nope", None),
+            ("", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                Chord::from_label(input)
+                    .ok()
+                    .map(|chord| chord.label())
+                    .as_deref(),
+                *expected,
+                "input: {:?}",
+                input
+            );
+        }
+        for label in DEFAULT_ALLOWLIST {
+            assert_eq!(Chord::from_label(label).unwrap().label(), *label);
+        }
+    }
+}
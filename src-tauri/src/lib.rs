@@ -1,8 +1,14 @@
 mod audio;
+mod dictation;
+mod editing;
 mod keyboard;
 mod accessibility;
+mod postprocess;
 mod storage;
 mod system;
+mod text;
+mod transcription;
+mod usage;
 
 use tauri::Manager;
 
@@ -26,9 +32,197 @@ fn get_audio_level() -> Result<audio::AudioLevelInfo, String> {
     audio::capture::get_level().map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+async fn transcribe_audio(
+    audio: Vec<u8>,
+    options: transcription::TranscribeOptions,
+) -> Result<transcription::TranscriptionResult, String> {
+    transcription::transcribe(&audio, &options)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn dictate(
+    audio: Vec<u8>,
+    target_app: Option<String>,
+    options: dictation::DictationOptions,
+) -> Result<dictation::DictationOutcome, String> {
+    dictation::dictate(&audio, target_app, options)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn transform_selection(
+    audio: Vec<u8>,
+    selection: Option<String>,
+    target_app: Option<String>,
+    options: dictation::DictationOptions,
+) -> Result<dictation::TransformResult, String> {
+    dictation::transform_selection(&audio, selection, target_app.as_deref(), &options)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_transcription_queue() -> Result<Vec<dictation::queue::QueuedJob>, String> {
+    dictation::queue::list().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_usage_summary(days: Option<u32>) -> Result<usage::UsageSummary, String> {
+    usage::summary(days.unwrap_or(30)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_usage_limit(limit_usd: Option<f64>) -> Result<(), String> {
+    usage::set_monthly_limit(limit_usd).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn remove_fillers(text: String, language: String) -> Result<String, String> {
+    let fillers = text::fillers::FillerList::load(&language).map_err(|e| e.to_string())?;
+    Ok(text::fillers::remove(&text, &fillers))
+}
+
+#[tauri::command]
+fn get_filler_words(language: String) -> Result<Vec<text::fillers::FillerWord>, String> {
+    text::fillers::list(&language).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn save_filler_word(word: text::fillers::FillerWord) -> Result<(), String> {
+    text::fillers::save(&word).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_filler_word(language: String, phrase: String) -> Result<(), String> {
+    text::fillers::delete(&language, &phrase).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn normalize_text(text: String, language: String, locale: Option<String>) -> String {
+    let locale = match locale {
+        Some(tag) => text::normalization::Locale::parse(&tag),
+        None => text::normalization::Locale::new(&language, None),
+    };
+    text::normalization::normalize(&text, &language, &locale)
+}
+
+#[tauri::command]
+fn apply_spoken_punctuation(text: String, language: String) -> Result<String, String> {
+    let commands = text::punctuation::CommandList::load(&language).map_err(|e| e.to_string())?;
+    Ok(text::punctuation::apply(&text, &commands))
+}
+
+#[tauri::command]
+fn get_punctuation_commands(
+    language: String,
+) -> Result<Vec<text::punctuation::PunctuationCommand>, String> {
+    text::punctuation::list(&language).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn save_punctuation_command(command: text::punctuation::PunctuationCommand) -> Result<(), String> {
+    text::punctuation::save(&command).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_punctuation_command(language: String, phrase: String) -> Result<(), String> {
+    text::punctuation::delete(&language, &phrase).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn convert_spelling(text: String, variant: text::style::SpellingVariant) -> String {
+    text::style::convert(&text, variant)
+}
+
+#[tauri::command]
+fn get_style_rules() -> Result<Vec<text::style::StyleRule>, String> {
+    text::style::list().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn save_style_rule(rule: text::style::StyleRule) -> Result<text::style::StyleRule, String> {
+    text::style::save(&rule).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_style_rule(id: String) -> Result<(), String> {
+    text::style::delete(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn preview_snippets(text: String, fuzzy: bool) -> Result<text::snippets::SnippetExpansion, String> {
+    let snippets = storage::snippets::list_enabled().map_err(|e| e.to_string())?;
+    Ok(text::snippets::expand(&text, &snippets, fuzzy))
+}
+
+#[tauri::command]
+fn get_dictionary_replacements(
+    limit: Option<u32>,
+) -> Result<Vec<storage::dictionary::RecordedReplacement>, String> {
+    storage::dictionary::recent_replacements(limit.unwrap_or(50)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_history_diff(history_id: String) -> Result<Option<storage::history::HistoryDiff>, String> {
+    storage::history::get_diff(&history_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn revert_history_edits(
+    history_id: String,
+    stages: Vec<text::diff::Stage>,
+) -> Result<Option<String>, String> {
+    storage::history::revert_stages(&history_id, &stages).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn resolve_corrections(text: String, language: String) -> text::corrections::CorrectionOutcome {
+    text::corrections::resolve(&text, &language)
+}
+
+#[tauri::command]
+fn fit_to_cursor(text: String, language: String, options: text::ProcessingOptions) -> String {
+    let before = accessibility::text_insertion::text_before_cursor(text::context::CONTEXT_CHARS)
+        .ok()
+        .flatten();
+    text::context::fit(&text, before.as_deref(), &language, &[], &options)
+}
+
+#[tauri::command]
+async fn postprocess_text(
+    text: String,
+    language: String,
+    target_app: Option<String>,
+    options: postprocess::PostprocessOptions,
+) -> Result<postprocess::PostprocessOutcome, String> {
+    let tone = options.tone_for(target_app.as_deref());
+    postprocess::rewrite(&text, &language, tone, &options)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn insert_text(text: String) -> Result<(), String> {
-    accessibility::text_insertion::insert(&text).map_err(|e| e.to_string())
+    editing::insert(&text).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_selected_text() -> Result<Option<String>, String> {
+    accessibility::text_insertion::selected_text().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_shortcut_allowlist() -> Result<Vec<String>, String> {
+    keyboard::shortcuts::allowlist().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_shortcut_allowlist(chords: Option<Vec<String>>) -> Result<(), String> {
+    keyboard::shortcuts::set_allowlist(chords).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -41,11 +235,38 @@ fn request_permission(permission: String) -> Result<bool, String> {
     accessibility::permissions::request(&permission).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+fn get_hotkey_config() -> Result<keyboard::hotkey::HotkeyConfig, String> {
+    keyboard::hotkey::load().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_hotkey_config(config: keyboard::hotkey::HotkeyConfig) -> Result<(), String> {
+    keyboard::hotkey::save(&config).map_err(|e| e.to_string())?;
+    keyboard::listener::reload();
+    Ok(())
+}
+
 #[tauri::command]
 fn get_focused_app() -> Result<Option<system::AppInfo>, String> {
     system::tray::get_focused_app().map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+fn get_app_profiles() -> Result<Vec<storage::app_profiles::AppProfile>, String> {
+    storage::app_profiles::list().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn save_app_profile(profile: storage::app_profiles::AppProfile) -> Result<(), String> {
+    storage::app_profiles::save(&profile).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_app_profile(bundle_id: String) -> Result<(), String> {
+    storage::app_profiles::delete(&bundle_id).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn db_execute(query: String, params: Vec<String>) -> Result<(), String> {
     storage::database::execute(&query, &params).map_err(|e| e.to_string())
@@ -89,6 +310,14 @@ pub fn run() {
             storage::database::initialize(db_path.to_str().unwrap())
                 .expect("Failed to initialize database");
 
+            system::notifications::initialize(app.handle().clone())
+                .expect("Failed to initialize notifications");
+
+            // Retry dictations that couldn't be transcribed while offline
+            dictation::queue::initialize(&app_dir.join("queue"))
+                .expect("Failed to initialize transcription queue");
+            dictation::queue::start_worker(app.handle().clone());
+
             // Set up keyboard listener
             let app_handle = app.handle().clone();
             std::thread::spawn(move || {
@@ -102,10 +331,44 @@ pub fn run() {
             start_recording,
             stop_recording,
             get_audio_level,
+            transcribe_audio,
+            dictate,
+            transform_selection,
+            get_transcription_queue,
+            get_usage_summary,
+            set_usage_limit,
+            remove_fillers,
+            get_filler_words,
+            save_filler_word,
+            delete_filler_word,
+            resolve_corrections,
+            get_dictionary_replacements,
+            get_history_diff,
+            revert_history_edits,
+            fit_to_cursor,
+            postprocess_text,
+            preview_snippets,
+            normalize_text,
+            apply_spoken_punctuation,
+            get_punctuation_commands,
+            save_punctuation_command,
+            delete_punctuation_command,
+            convert_spelling,
+            get_style_rules,
+            save_style_rule,
+            delete_style_rule,
             insert_text,
+            get_selected_text,
+            get_shortcut_allowlist,
+            set_shortcut_allowlist,
+            get_hotkey_config,
+            set_hotkey_config,
             check_permissions,
             request_permission,
             get_focused_app,
+            get_app_profiles,
+            save_app_profile,
+            delete_app_profile,
             db_execute,
             db_query,
             keychain_set,
@@ -0,0 +1,67 @@
+use super::{Completion, CompletionRequest, LlmProvider, PostprocessError};
+use serde_json::json;
+
+const ANTHROPIC_MESSAGES_URL: &str = "https://api.anthropic.com/v1/messages";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// Fast enough to answer inside the dictation timeout
+const DEFAULT_MODEL: &str = "claude-3-5-haiku-latest";
+
+/// Anthropic Messages API
+pub struct Anthropic {
+    api_key: String,
+}
+
+impl Anthropic {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key }
+    }
+}
+
+impl LlmProvider for Anthropic {
+    fn default_model(&self) -> &'static str {
+        DEFAULT_MODEL
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion, PostprocessError> {
+        let body = json!({
+            "model": request.model,
+            "max_tokens": request.max_tokens,
+            "temperature": 0.2,
+            "system": request.system,
+            "messages": [{ "role": "user", "content": request.user }],
+        });
+
+        let response = reqwest::Client::new()
+            .post(ANTHROPIC_MESSAGES_URL)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(PostprocessError::ApiError {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        let data: serde_json::Value = response.json().await?;
+
+        let text = data["content"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|block| block["type"] == "text")
+            .filter_map(|block| block["text"].as_str())
+            .collect::<String>();
+
+        Ok(Completion {
+            text,
+            input_tokens: data["usage"]["input_tokens"].as_u64().unwrap_or(0),
+            output_tokens: data["usage"]["output_tokens"].as_u64().unwrap_or(0),
+        })
+    }
+}
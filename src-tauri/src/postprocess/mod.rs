@@ -0,0 +1,450 @@
+pub mod anthropic;
+pub mod openai;
+pub mod prompts;
+pub mod redaction;
+pub mod translation;
+
+use crate::storage::{dictionary, keychain};
+use crate::system::tray;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PostprocessError {
+    #[error("Post-processing request failed: {0}")]
+    RequestError(String),
+    #[error("Provider returned an error ({status}): {body}")]
+    ApiError { status: u16, body: String },
+    #[error("No API key configured for {0}")]
+    MissingApiKey(String),
+    #[error("Post-processing timed out after {0} ms")]
+    Timeout(u64),
+    #[error("Unknown prompt version: {0}")]
+    UnknownPrompt(u32),
+    #[error("Provider dropped a redaction placeholder")]
+    PlaceholderLost,
+    /// The request reached the provider but nothing usable came back; it
+    /// may still be billed
+    #[error("{reason}")]
+    Unused {
+        reason: Box<PostprocessError>,
+        model: String,
+        input_tokens: u64,
+        output_tokens: u64,
+    },
+}
+
+impl PostprocessError {
+    fn unused(self, model: &str, input_tokens: u64, output_tokens: u64) -> Self {
+        PostprocessError::Unused {
+            reason: Box::new(self),
+            model: model.to_string(),
+            input_tokens,
+            output_tokens,
+        }
+    }
+
+    /// Model and tokens of a request that was sent before failing
+    pub fn billed(&self) -> Option<(&str, u64, u64)> {
+        match self {
+            PostprocessError::Unused {
+                model,
+                input_tokens,
+                output_tokens,
+                ..
+            } => Some((model, *input_tokens, *output_tokens)),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for PostprocessError {
+    fn from(e: reqwest::Error) -> Self {
+        PostprocessError::RequestError(e.to_string())
+    }
+}
+
+/// How long dictation waits for the model before inserting the text as is
+pub const DEFAULT_TIMEOUT_MS: u64 = 4000;
+
+const MAX_OUTPUT_TOKENS: u32 = 4096;
+
+/// Rough size of a token, for requests abandoned before the provider said
+const CHARS_PER_TOKEN: usize = 4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    #[default]
+    Anthropic,
+    /// OpenAI or any server speaking its chat completions API
+    OpenaiCompatible,
+}
+
+impl ProviderKind {
+    /// Provider name used for usage metering and pricing
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProviderKind::Anthropic => "anthropic",
+            ProviderKind::OpenaiCompatible => "openai",
+        }
+    }
+
+    /// Keychain entry holding this provider's API key
+    fn api_key_name(&self) -> &'static str {
+        match self {
+            ProviderKind::Anthropic => "anthropic_api_key",
+            ProviderKind::OpenaiCompatible => "openai_api_key",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tone {
+    Casual,
+    Professional,
+    #[default]
+    Neutral,
+}
+
+impl Tone {
+    /// Tone for the app the text will be inserted into
+    pub fn for_app(bundle_id: &str) -> Self {
+        match tray::get_app_tone(bundle_id) {
+            "casual" => Tone::Casual,
+            "professional" => Tone::Professional,
+            _ => Tone::Neutral,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PostprocessOptions {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub provider: ProviderKind,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Endpoint for OpenAI-compatible servers ("http://localhost:11434/v1");
+    /// defaults to OpenAI
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Prompt template to use; defaults to `prompts::LATEST`
+    #[serde(default)]
+    pub prompt_version: Option<u32>,
+    /// Overrides the tone picked from the focused app
+    #[serde(default)]
+    pub tone: Option<Tone>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Replace names, emails, phone and card numbers and addresses with
+    /// placeholders before the text leaves the device
+    #[serde(default)]
+    pub private_mode: bool,
+}
+
+impl PostprocessOptions {
+    /// The configured tone, or the one for the target app
+    pub fn tone_for(&self, target_app: Option<&str>) -> Tone {
+        self.tone
+            .or_else(|| target_app.map(Tone::for_app))
+            .unwrap_or_default()
+    }
+}
+
+/// A single-turn prompt for a provider
+#[derive(Debug, Clone)]
+pub struct CompletionRequest {
+    pub model: String,
+    pub system: String,
+    pub user: String,
+    pub max_tokens: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub text: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// A chat model that can rewrite a transcript
+pub(crate) trait LlmProvider {
+    fn default_model(&self) -> &'static str;
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion, PostprocessError>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostprocessOutcome {
+    pub text: String,
+    /// False when the model failed or timed out and `text` is the input as is
+    pub applied: bool,
+    pub tone: Tone,
+    pub prompt_version: u32,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Whether a request went to the provider, even if its answer wasn't used
+    #[serde(default)]
+    pub sent: bool,
+    /// Personal details hidden from the provider
+    #[serde(default)]
+    pub redactions: usize,
+}
+
+/// Rewrite text with the configured model, falling back to the input
+///
+/// Never fails: a missing key, provider error or timeout is logged and the
+/// text comes back unchanged so dictation isn't held up by the model.
+pub async fn run(
+    text: &str,
+    language: &str,
+    tone: Tone,
+    options: &PostprocessOptions,
+) -> PostprocessOutcome {
+    match rewrite(text, language, tone, options).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            log::warn!("Post-processing skipped, using local text: {}", e);
+            let (model, input_tokens, output_tokens) =
+                e.billed()
+                    .unwrap_or((options.model.as_deref().unwrap_or_default(), 0, 0));
+            PostprocessOutcome {
+                text: text.to_string(),
+                applied: false,
+                tone,
+                prompt_version: options.prompt_version.unwrap_or(prompts::LATEST),
+                model: model.to_string(),
+                input_tokens,
+                output_tokens,
+                sent: e.billed().is_some(),
+                redactions: 0,
+            }
+        }
+    }
+}
+
+/// Rewrite text with the configured model
+pub async fn rewrite(
+    text: &str,
+    language: &str,
+    tone: Tone,
+    options: &PostprocessOptions,
+) -> Result<PostprocessOutcome, PostprocessError> {
+    let template = prompts::get(options.prompt_version.unwrap_or(prompts::LATEST))?;
+    if text.trim().is_empty() {
+        return Ok(PostprocessOutcome {
+            text: text.to_string(),
+            applied: false,
+            tone,
+            prompt_version: template.version,
+            model: options.model.clone().unwrap_or_default(),
+            input_tokens: 0,
+            output_tokens: 0,
+            sent: false,
+            redactions: 0,
+        });
+    }
+
+    let system = template.render(tone, language);
+    let completion = complete_private(system, text, None, options).await?;
+
+    Ok(PostprocessOutcome {
+        text: completion.text,
+        applied: true,
+        tone,
+        prompt_version: template.version,
+        model: completion.model,
+        input_tokens: completion.input_tokens,
+        output_tokens: completion.output_tokens,
+        sent: true,
+        redactions: completion.redactions,
+    })
+}
+
+/// Selected text rewritten by a spoken instruction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformOutcome {
+    pub text: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Personal details hidden from the provider
+    #[serde(default)]
+    pub redactions: usize,
+}
+
+/// Apply a spoken instruction ("make this more polite") to selected text
+///
+/// Unlike `run` this fails outright, since there's no sensible text to fall
+/// back to and the selection is left as it was.
+pub async fn transform(
+    selection: &str,
+    instruction: &str,
+    options: &PostprocessOptions,
+) -> Result<TransformOutcome, PostprocessError> {
+    let completion = complete_private(
+        prompts::TRANSFORM.to_string(),
+        selection,
+        Some(instruction.trim()),
+        options,
+    )
+    .await?;
+
+    // The model's answer is trimmed; keep the spacing around the selection
+    let leading = &selection[..selection.len() - selection.trim_start().len()];
+    let trailing = &selection[selection.trim_end().len()..];
+    Ok(TransformOutcome {
+        text: format!("{}{}{}", leading, completion.text, trailing),
+        model: completion.model,
+        input_tokens: completion.input_tokens,
+        output_tokens: completion.output_tokens,
+        redactions: completion.redactions,
+    })
+}
+
+/// A model's answer, with any redacted details put back
+struct PrivateCompletion {
+    text: String,
+    model: String,
+    input_tokens: u64,
+    output_tokens: u64,
+    redactions: usize,
+}
+
+/// Send text to the configured model, hiding personal details first when
+/// private mode is on
+///
+/// An `instruction` goes alongside the text unredacted, so "translate to
+/// French" reaches the model intact.
+async fn complete_private(
+    system: String,
+    text: &str,
+    instruction: Option<&str>,
+    options: &PostprocessOptions,
+) -> Result<PrivateCompletion, PostprocessError> {
+    let redacted = options.private_mode.then(|| {
+        let redacted = redaction::redact(text, &known_names());
+        if !redacted.entities.is_empty() {
+            let counts: Vec<String> = redacted
+                .counts()
+                .iter()
+                .map(|(kind, n)| format!("{:?}: {}", kind, n))
+                .collect();
+            log::info!("Redacted before post-processing ({})", counts.join(", "));
+        }
+        redacted
+    });
+    let text = redacted.as_ref().map_or(text, |r| r.text.as_str());
+    let user = match instruction {
+        Some(instruction) => format!(
+            "<instruction>\n{}\n</instruction>\n<text>\n{}\n</text>",
+            instruction, text
+        ),
+        None => text.to_string(),
+    };
+
+    let (model, completion) = match options.provider {
+        ProviderKind::Anthropic => {
+            let api_key = api_key(options.provider)?
+                .ok_or_else(|| PostprocessError::MissingApiKey("anthropic".to_string()))?;
+            let provider = anthropic::Anthropic::new(api_key);
+            complete(&provider, options, system, &user).await?
+        }
+        ProviderKind::OpenaiCompatible => {
+            let api_key = api_key(options.provider)?;
+            // Local servers usually don't need a key, OpenAI itself does
+            if api_key.is_none() && options.base_url.is_none() {
+                return Err(PostprocessError::MissingApiKey("openai".to_string()));
+            }
+            let provider = openai::OpenAiCompatible::new(options.base_url.clone(), api_key);
+            complete(&provider, options, system, &user).await?
+        }
+    };
+
+    let unused =
+        |e: PostprocessError| e.unused(&model, completion.input_tokens, completion.output_tokens);
+    let answer = completion.text.trim();
+    if answer.is_empty() {
+        return Err(unused(PostprocessError::RequestError(
+            "model returned no text".to_string(),
+        )));
+    }
+    let answer = match &redacted {
+        Some(redacted) => redacted
+            .restore(answer)
+            .ok_or_else(|| unused(PostprocessError::PlaceholderLost))?,
+        None => answer.to_string(),
+    };
+
+    Ok(PrivateCompletion {
+        text: answer,
+        model,
+        input_tokens: completion.input_tokens,
+        output_tokens: completion.output_tokens,
+        redactions: redacted.map_or(0, |r| r.entities.len()),
+    })
+}
+
+async fn complete<P: LlmProvider>(
+    provider: &P,
+    options: &PostprocessOptions,
+    system: String,
+    text: &str,
+) -> Result<(String, Completion), PostprocessError> {
+    let model = options
+        .model
+        .clone()
+        .unwrap_or_else(|| provider.default_model().to_string());
+    let request = CompletionRequest {
+        model: model.clone(),
+        system,
+        user: text.to_string(),
+        max_tokens: MAX_OUTPUT_TOKENS,
+    };
+
+    let timeout_ms = options.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS);
+    match tokio::time::timeout(
+        Duration::from_millis(timeout_ms),
+        provider.complete(&request),
+    )
+    .await
+    {
+        Ok(Ok(completion)) => Ok((model, completion)),
+        Ok(Err(e)) => Err(e.unused(&model, 0, 0)),
+        // The provider still reads, and bills, a prompt we stopped waiting on
+        Err(_) => {
+            let prompt_chars = request.system.chars().count() + request.user.chars().count();
+            let input_tokens = (prompt_chars / CHARS_PER_TOKEN) as u64;
+            Err(PostprocessError::Timeout(timeout_ms).unused(&model, input_tokens, 0))
+        }
+    }
+}
+
+/// People's names from the custom dictionary, to redact wherever they appear
+fn known_names() -> Vec<String> {
+    match dictionary::list() {
+        Ok(words) => words
+            .into_iter()
+            .filter(|w| {
+                w.category
+                    .as_deref()
+                    .is_some_and(|c| matches!(c, "name" | "person" | "contact"))
+            })
+            .map(|w| w.word)
+            .collect(),
+        Err(e) => {
+            log::warn!("Failed to load dictionary names for redaction: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn api_key(provider: ProviderKind) -> Result<Option<String>, PostprocessError> {
+    keychain::get(keychain::SERVICE, provider.api_key_name())
+        .map_err(|e| PostprocessError::RequestError(e.to_string()))
+}
@@ -0,0 +1,69 @@
+use super::{Completion, CompletionRequest, LlmProvider, PostprocessError};
+use serde_json::json;
+
+const OPENAI_API_BASE: &str = "https://api.openai.com/v1";
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+/// OpenAI chat completions, or any server that implements the same API
+/// (Ollama, LM Studio, vLLM, OpenRouter)
+pub struct OpenAiCompatible {
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiCompatible {
+    pub fn new(base_url: Option<String>, api_key: Option<String>) -> Self {
+        let base_url = base_url
+            .as_deref()
+            .unwrap_or(OPENAI_API_BASE)
+            .trim_end_matches('/')
+            .to_string();
+        Self { base_url, api_key }
+    }
+}
+
+impl LlmProvider for OpenAiCompatible {
+    fn default_model(&self) -> &'static str {
+        DEFAULT_MODEL
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion, PostprocessError> {
+        let body = json!({
+            "model": request.model,
+            "max_tokens": request.max_tokens,
+            "temperature": 0.2,
+            "messages": [
+                { "role": "system", "content": request.system },
+                { "role": "user", "content": request.user },
+            ],
+        });
+
+        let mut builder = reqwest::Client::new()
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&body);
+        if let Some(api_key) = &self.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+        let response = builder.send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(PostprocessError::ApiError {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        let data: serde_json::Value = response.json().await?;
+
+        Ok(Completion {
+            text: data["choices"][0]["message"]["content"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            input_tokens: data["usage"]["prompt_tokens"].as_u64().unwrap_or(0),
+            output_tokens: data["usage"]["completion_tokens"].as_u64().unwrap_or(0),
+        })
+    }
+}
@@ -0,0 +1,651 @@
+use crate::text::common_words;
+use crate::text::tokens::{self, Token};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Kinds of personal detail kept off cloud providers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PiiKind {
+    Name,
+    Email,
+    Phone,
+    Card,
+    Address,
+}
+
+impl PiiKind {
+    fn label(&self) -> &'static str {
+        match self {
+            PiiKind::Name => "NAME",
+            PiiKind::Email => "EMAIL",
+            PiiKind::Phone => "PHONE",
+            PiiKind::Card => "CARD",
+            PiiKind::Address => "ADDRESS",
+        }
+    }
+}
+
+/// One detected value and the placeholder standing in for it
+#[derive(Debug, Clone)]
+pub struct Entity {
+    pub kind: PiiKind,
+    pub placeholder: String,
+    value: String,
+}
+
+/// Text with its personal details swapped for placeholders
+#[derive(Debug, Clone, Default)]
+pub struct Redacted {
+    pub text: String,
+    pub entities: Vec<Entity>,
+}
+
+impl Redacted {
+    /// How many values of each kind were hidden, for logging
+    pub fn counts(&self) -> Vec<(PiiKind, usize)> {
+        let mut counts: Vec<(PiiKind, usize)> = Vec::new();
+        for entity in &self.entities {
+            match counts.iter_mut().find(|(kind, _)| *kind == entity.kind) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((entity.kind, 1)),
+            }
+        }
+        counts
+    }
+
+    /// Put the original values back into text returned by the provider
+    ///
+    /// Returns `None` if the provider dropped or altered a placeholder, since
+    /// the value it stood for would otherwise be lost.
+    pub fn restore(&self, text: &str) -> Option<String> {
+        let mut out = text.to_string();
+        for entity in &self.entities {
+            if !out.contains(&entity.placeholder) {
+                return None;
+            }
+            out = out.replace(&entity.placeholder, &entity.value);
+        }
+        Some(out)
+    }
+
+    /// Placeholder for a value, reusing the one already given to it
+    fn placeholder(&mut self, kind: PiiKind, value: &str) -> String {
+        if let Some(entity) = self
+            .entities
+            .iter()
+            .find(|e| e.kind == kind && e.value == value)
+        {
+            return entity.placeholder.clone();
+        }
+        let n = self.entities.iter().filter(|e| e.kind == kind).count() + 1;
+        let placeholder = format!("[{}_{}]", kind.label(), n);
+        self.entities.push(Entity {
+            kind,
+            placeholder: placeholder.clone(),
+            value: value.to_string(),
+        });
+        placeholder
+    }
+}
+
+/// Shortest and longest digit runs read as phone numbers
+const PHONE_DIGITS: Range<usize> = 7..16;
+
+/// Digit counts of payment card numbers
+const CARD_DIGITS: Range<usize> = 13..20;
+
+/// Characters that can appear inside a written phone or card number
+const NUMBER_CHARS: &[char] = &[' ', '(', ')', '+', '-', '.'];
+
+/// Last word of a street address
+const STREET_SUFFIXES: &[&str] = &[
+    "street",
+    "st",
+    "avenue",
+    "ave",
+    "road",
+    "rd",
+    "boulevard",
+    "blvd",
+    "lane",
+    "ln",
+    "drive",
+    "dr",
+    "court",
+    "ct",
+    "way",
+    "place",
+    "pl",
+    "terrace",
+    "parkway",
+    "highway",
+    "square",
+];
+
+/// Courtesy titles that mark the next capitalized words as a name
+const HONORIFICS: &[&str] = &["mr", "mrs", "ms", "miss", "dr", "prof", "sir"];
+
+/// Capitalized words that aren't names: dates, languages and
+/// nationalities, well-known products and words that open sentences
+const NOT_NAMES: &[&str] = &[
+    "i",
+    "i'm",
+    "i'll",
+    "i've",
+    "i'd",
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+    "ok",
+    "okay",
+    "english",
+    "french",
+    "spanish",
+    "german",
+    "italian",
+    "portuguese",
+    "dutch",
+    "chinese",
+    "mandarin",
+    "cantonese",
+    "japanese",
+    "korean",
+    "russian",
+    "arabic",
+    "hindi",
+    "polish",
+    "swedish",
+    "danish",
+    "norwegian",
+    "finnish",
+    "turkish",
+    "greek",
+    "hebrew",
+    "ukrainian",
+    "czech",
+    "vietnamese",
+    "thai",
+    "indonesian",
+    "latin",
+    "american",
+    "british",
+    "canadian",
+    "australian",
+    "mexican",
+    "brazilian",
+    "indian",
+    "irish",
+    "scottish",
+    "welsh",
+    "swiss",
+    "european",
+    "african",
+    "asian",
+    "slack",
+    "github",
+    "gitlab",
+    "google",
+    "gmail",
+    "zoom",
+    "teams",
+    "outlook",
+    "excel",
+    "notion",
+    "jira",
+    "figma",
+    "chrome",
+    "safari",
+    "iphone",
+    "ipad",
+    "mac",
+    "macos",
+    "windows",
+    "linux",
+    "android",
+    "youtube",
+    "linkedin",
+    "whatsapp",
+    "dropbox",
+    "hi",
+    "hello",
+    "hey",
+    "dear",
+    "maybe",
+    "perhaps",
+    "however",
+    "anyway",
+    "yesterday",
+    "tomorrow",
+    "tonight",
+    "morning",
+    "afternoon",
+    "evening",
+    "sorry",
+    "yeah",
+    "yep",
+    "sounds",
+    "looks",
+    "seems",
+    "happy",
+    "glad",
+    "welcome",
+    "congratulations",
+    "remind",
+    "remember",
+    "schedule",
+    "reply",
+    "forward",
+    "email",
+    "text",
+    "message",
+    "cancel",
+    "confirm",
+    "translate",
+    "rewrite",
+    "rephrase",
+    "summarize",
+    "summarise",
+    "shorten",
+    "proofread",
+    "convert",
+    "fix",
+];
+
+/// Apostrophe endings of contractions ("don't", "we'll"), which names don't
+/// take; "'s" is left out since "Sarah's" is a name
+const CONTRACTIONS: &[&str] = &["t", "ll", "re", "ve", "d", "m"];
+
+/// Words before a number that make it a reference rather than a phone
+/// number ("order number 12345678")
+const REFERENCE_LABELS: &[&str] = &[
+    "order",
+    "invoice",
+    "ticket",
+    "reference",
+    "ref",
+    "tracking",
+    "case",
+    "confirmation",
+    "booking",
+    "item",
+    "issue",
+    "version",
+    "build",
+    "id",
+];
+
+/// Replace names, emails, phone and card numbers and street addresses with
+/// placeholders
+///
+/// Detection is local and errs towards hiding too much. Names are the
+/// dictionary's `names` and each of their parts, words after a title ("Dr.
+/// Patel") and capitalized words; a word opening a sentence counts unless
+/// it's everyday English ("Please", "Let's").
+pub fn redact(text: &str, names: &[String]) -> Redacted {
+    let mut redacted = Redacted::default();
+    let text = redact_numbers(text, &mut redacted);
+
+    let tokens = tokens::tokenize(&text);
+    let names: Vec<Vec<String>> = names
+        .iter()
+        .map(|n| n.split_whitespace().map(str::to_lowercase).collect())
+        .filter(|n: &Vec<String>| !n.is_empty())
+        .collect();
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let found = email(&tokens, i)
+            .map(|len| (len, PiiKind::Email))
+            .or_else(|| address(&tokens, i).map(|len| (len, PiiKind::Address)))
+            .or_else(|| name(&tokens, i, &names).map(|len| (len, PiiKind::Name)));
+
+        match found {
+            Some((len, kind)) => {
+                let window = &tokens[i..i + len];
+                let value = window
+                    .iter()
+                    .enumerate()
+                    .map(|(k, t)| match (k, k == len - 1) {
+                        (0, true) => t.core.clone(),
+                        (0, false) => format!("{}{}", t.core, t.trail),
+                        (_, true) => format!("{}{}", t.lead, t.core),
+                        _ => t.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                out.push(Token {
+                    lead: window[0].lead.clone(),
+                    core: redacted.placeholder(kind, &value),
+                    trail: window[len - 1].trail.clone(),
+                });
+                i += len;
+            }
+            None => {
+                out.push(tokens[i].clone());
+                i += 1;
+            }
+        }
+    }
+
+    redacted.text = tokens::join(&out);
+    redacted
+}
+
+/// Swap written phone and card numbers for placeholders
+fn redact_numbers(text: &str, redacted: &mut Redacted) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some((range, kind)) = next_number(rest) {
+        out.push_str(&rest[..range.start]);
+        out.push_str(&redacted.placeholder(kind, &rest[range.clone()]));
+        rest = &rest[range.end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The first phone or card number in `text`
+fn next_number(text: &str) -> Option<(Range<usize>, PiiKind)> {
+    let mut from = 0;
+    while let Some(offset) = text[from..].find(|c: char| c.is_ascii_digit() || c == '+' || c == '(')
+    {
+        let start = from + offset;
+        let run = number_run(text, start);
+        let kind = classify_number(&text[run.clone()]).filter(|_| !is_reference(&text[..start]));
+        if let Some(kind) = kind {
+            return Some((run, kind));
+        }
+        from = run.end.max(start + 1);
+    }
+    None
+}
+
+/// The digits and separators starting at `start`, ending on a digit
+fn number_run(text: &str, start: usize) -> Range<usize> {
+    let preceded_by_word = text[..start]
+        .chars()
+        .next_back()
+        .is_some_and(char::is_alphanumeric);
+    if preceded_by_word {
+        return start..start;
+    }
+
+    let mut end = start;
+    let mut last_digit = None;
+    for (i, c) in text[start..].char_indices() {
+        if c.is_ascii_digit() {
+            last_digit = Some(start + i + 1);
+        } else if !NUMBER_CHARS.contains(&c) {
+            break;
+        }
+        end = start + i + c.len_utf8();
+    }
+
+    let Some(mut end_digit) = last_digit else {
+        return start..end;
+    };
+    // "(555) 123-4567)" keeps a closing bracket only if it opened one
+    if text[start..end_digit].contains('(') && text[end_digit..end].starts_with(')') {
+        end_digit += 1;
+    }
+    let followed_by_word = text[end_digit..]
+        .chars()
+        .next()
+        .is_some_and(char::is_alphanumeric);
+    if followed_by_word {
+        return start..start;
+    }
+    start..end_digit
+}
+
+fn classify_number(run: &str) -> Option<PiiKind> {
+    let digits: Vec<u32> = run.chars().filter_map(|c| c.to_digit(10)).collect();
+    if CARD_DIGITS.contains(&digits.len()) && luhn(&digits) {
+        return Some(PiiKind::Card);
+    }
+    if !PHONE_DIGITS.contains(&digits.len()) {
+        return None;
+    }
+
+    let groups: Vec<usize> = run
+        .split(|c: char| !c.is_ascii_digit())
+        .map(str::len)
+        .filter(|len| *len > 0)
+        .collect();
+    // Dates ("2024-03-05", "05.03.2024") and decimals ("3.14159")
+    let date_like = groups.len() == 3
+        && (groups == [4, 2, 2] || (groups[2] == 4 && groups[0] <= 2 && groups[1] <= 2));
+    let split_digit = groups.iter().skip(1).any(|len| *len < 2);
+    let decimal = groups.len() == 2 && run.contains('.') && !run.contains(' ');
+    (!date_like && !split_digit && !decimal).then_some(PiiKind::Phone)
+}
+
+/// Whether the words before a number label it as an order, ticket or
+/// similar reference
+fn is_reference(before: &str) -> bool {
+    let mut words = before
+        .rsplit(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase);
+    let mut label = words.next();
+    // "order number", "ticket no", "case #"
+    if label
+        .as_deref()
+        .is_some_and(|w| matches!(w, "number" | "no" | "num"))
+    {
+        label = words.next();
+    }
+    label.is_some_and(|w| REFERENCE_LABELS.contains(&w.as_str()))
+}
+
+/// Luhn checksum, which every payment card number passes
+fn luhn(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| match (i % 2 == 1, d * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => *d,
+        })
+        .sum();
+    let check_digit = sum % 10;
+    check_digit == 0
+}
+
+fn email(tokens: &[Token], i: usize) -> Option<usize> {
+    let core = &tokens[i].core;
+    let (local, domain) = core.split_once('@')?;
+    let valid = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_alphanumeric() || "._%+-".contains(c))
+    };
+    (valid(local) && valid(domain) && domain.contains('.') && !domain.contains('@')).then_some(1)
+}
+
+/// "221 Baker Street", "1600 Pennsylvania Ave."
+fn address(tokens: &[Token], i: usize) -> Option<usize> {
+    let number = &tokens[i];
+    let is_number = !number.core.is_empty()
+        && number.core.len() <= 6
+        && number.core.chars().all(|c| c.is_ascii_digit())
+        && number.trail.is_empty();
+    if !is_number {
+        return None;
+    }
+
+    for len in 2..=5 {
+        let window = tokens.get(i..i + len)?;
+        let last = &window[len - 1];
+        let joined = window[1..].iter().all(|t| t.lead.is_empty())
+            && window[..len - 1].iter().all(|t| t.trail.is_empty());
+        let capitalized =
+            starts_with_capital(&last.core) || last.core.starts_with(|c: char| c.is_ascii_digit());
+        if !joined || !capitalized {
+            return None;
+        }
+        if len > 2 && STREET_SUFFIXES.contains(&last.lower().as_str()) {
+            return Some(len);
+        }
+    }
+    None
+}
+
+fn name(tokens: &[Token], i: usize, names: &[Vec<String>]) -> Option<usize> {
+    let known = names
+        .iter()
+        .filter(|parts| {
+            tokens.get(i..i + parts.len()).is_some_and(|window| {
+                window
+                    .iter()
+                    .zip(parts.iter())
+                    .all(|(token, part)| token.lower() == *part)
+            })
+        })
+        .map(Vec::len)
+        .max();
+    if known.is_some() {
+        return known;
+    }
+
+    let after_title = i > 0 && HONORIFICS.contains(&tokens[i - 1].lower().as_str());
+    let sentence_start =
+        i == 0 || (tokens[i - 1].ends_sentence() && !after_title) || tokens[i].lead.contains('\n');
+
+    // "Priya" alone, from "Priya Patel"; parts that are ordinary words,
+    // like the "Will" of "Will Smith", need a capital inside a sentence
+    let word = tokens[i].lower();
+    let part_of_known = names
+        .iter()
+        .any(|parts| parts.len() > 1 && parts.contains(&word));
+    let capitalized_name = is_name_word(&tokens[i]) && !sentence_start;
+    if part_of_known && (!common_words::is_common(&word) || capitalized_name) {
+        return Some(1);
+    }
+
+    if !is_name_word(&tokens[i]) || (sentence_start && is_everyday(&tokens[i])) {
+        return None;
+    }
+
+    // "Mary Ann Smith" is one name
+    let mut len = 1;
+    while tokens[i + len - 1].trail.is_empty()
+        && tokens
+            .get(i + len)
+            .is_some_and(|t| t.lead.is_empty() && is_name_word(t))
+    {
+        len += 1;
+    }
+    Some(len)
+}
+
+/// Capitalized like a name: "Priya", "O'Brien", "McDonald", not "NASA",
+/// "I'm" or "Don't"
+fn is_name_word(token: &Token) -> bool {
+    let word = &token.core;
+    let lower = token.lower();
+    let contraction = lower
+        .rsplit_once(['\'', '’'])
+        .is_some_and(|(_, ending)| CONTRACTIONS.contains(&ending));
+    starts_with_capital(word)
+        && word.chars().any(char::is_lowercase)
+        && !contraction
+        && !NOT_NAMES.contains(&lower.as_str())
+        && !HONORIFICS.contains(&lower.as_str())
+        && !word.starts_with('[')
+        && !token.lead.ends_with('[')
+}
+
+/// An ordinary word that is only capitalized because it opens a sentence:
+/// "Please", "Let's", "Ticket", "Unfortunately"
+fn is_everyday(token: &Token) -> bool {
+    let lower = token.lower();
+    let stem = match lower.rsplit_once(['\'', '’']) {
+        Some((stem, "s")) => stem,
+        _ => lower.as_str(),
+    };
+    common_words::is_common(stem) || REFERENCE_LABELS.contains(&stem) || stem.ends_with("ly")
+}
+
+fn starts_with_capital(word: &str) -> bool {
+    word.chars().next().is_some_and(char::is_uppercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_personal_details() {
+        let names = vec!["Priya Patel".to_string()];
+        let cases: &[(&str, &str)] = &[
+            (
+                "Sarah said she'd call back",
+                "[NAME_1] said she'd call back",
+            ),
+            ("Ask Priya about it", "Ask [NAME_1] about it"),
+            ("priya patel is out today", "[NAME_1] is out today"),
+            ("Email me at sam@example.com", "Email me at [EMAIL_1]"),
+            ("Call 555 123 4567 tomorrow", "Call [PHONE_1] tomorrow"),
+            ("My card is 4111 1111 1111 1111", "My card is [CARD_1]"),
+            ("Send it to 221 Baker Street", "Send it to [ADDRESS_1]"),
+            ("Then I met Dr. Patel.", "Then I met Dr. [NAME_1]."),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(redact(input, &names).text, *expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn leaves_ordinary_words_alone() {
+        let cases: &[&str] = &[
+            "Please call back later",
+            "Let's meet on Monday",
+            "Translate this to French",
+            "Post it in Slack and open an issue on GitHub",
+            "Order number 12345678 shipped",
+            "Ticket 4455667 is closed",
+            "I'm sure we'll manage",
+            "Unfortunately it broke",
+            "The build took 3.14159 seconds",
+        ];
+        for input in cases {
+            assert_eq!(redact(input, &[]).text, *input, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn restores_placeholders() {
+        let redacted = redact("Tell Sarah to call 555 123 4567", &[]);
+        assert_eq!(redacted.text, "Tell [NAME_1] to call [PHONE_1]");
+        assert_eq!(
+            redacted
+                .restore("Please tell [NAME_1] to call [PHONE_1].")
+                .as_deref(),
+            Some("Please tell Sarah to call 555 123 4567.")
+        );
+        assert_eq!(redacted.restore("Please tell her to call."), None);
+    }
+}
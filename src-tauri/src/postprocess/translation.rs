@@ -0,0 +1,197 @@
+use super::{prompts, PostprocessError, PostprocessOptions, DEFAULT_TIMEOUT_MS};
+use crate::storage::keychain;
+use crate::transcription::language;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+
+/// LibreTranslate's default address when run on this Mac
+const LIBRETRANSLATE_URL: &str = "http://localhost:5000";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslationBackend {
+    /// The model configured for post-processing, whether or not rewriting
+    /// is enabled
+    #[default]
+    Llm,
+    /// A LibreTranslate server, so text can stay on the device
+    Libretranslate,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranslationOptions {
+    #[serde(default)]
+    pub backend: TranslationBackend,
+    /// LibreTranslate endpoint; defaults to a server on localhost
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// For LibreTranslate; the LLM uses the post-processing timeout
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationOutcome {
+    pub text: String,
+    /// False when translation failed or wasn't needed and `text` is the
+    /// input as is
+    pub applied: bool,
+    pub source_language: String,
+    pub target_language: String,
+    pub backend: TranslationBackend,
+    /// Model used by the LLM backend
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Whether a request went to the model, even if its answer wasn't used
+    #[serde(default)]
+    pub sent: bool,
+}
+
+/// Translate text, falling back to the input
+///
+/// Never fails, like `postprocess::run`: untranslated text is inserted
+/// rather than losing the dictation.
+pub async fn run(
+    text: &str,
+    source_language: &str,
+    target_language: &str,
+    options: &TranslationOptions,
+    llm: &PostprocessOptions,
+) -> TranslationOutcome {
+    match translate(text, source_language, target_language, options, llm).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            log::warn!("Translation skipped, using the transcript: {}", e);
+            let mut outcome = unchanged(text, source_language, target_language, options);
+            if let Some((model, input_tokens, output_tokens)) = e.billed() {
+                outcome.model = model.to_string();
+                outcome.input_tokens = input_tokens;
+                outcome.output_tokens = output_tokens;
+                outcome.sent = true;
+            }
+            outcome
+        }
+    }
+}
+
+/// Translate text from the dictated language into `target_language`
+pub async fn translate(
+    text: &str,
+    source_language: &str,
+    target_language: &str,
+    options: &TranslationOptions,
+    llm: &PostprocessOptions,
+) -> Result<TranslationOutcome, PostprocessError> {
+    let same = language::normalize(source_language) == language::normalize(target_language);
+    if same || text.trim().is_empty() {
+        return Ok(unchanged(text, source_language, target_language, options));
+    }
+
+    let (text, model, input_tokens, output_tokens, sent) = match options.backend {
+        TranslationBackend::Llm => {
+            let system = prompts::translation(source_language, target_language);
+            let completion = super::complete_private(system, text, None, llm).await?;
+            (
+                completion.text,
+                completion.model,
+                completion.input_tokens,
+                completion.output_tokens,
+                true,
+            )
+        }
+        TranslationBackend::Libretranslate => {
+            let translated =
+                libretranslate(text, source_language, target_language, options).await?;
+            (translated, String::new(), 0, 0, false)
+        }
+    };
+
+    Ok(TranslationOutcome {
+        text,
+        applied: true,
+        source_language: source_language.to_string(),
+        target_language: target_language.to_string(),
+        backend: options.backend,
+        model,
+        input_tokens,
+        output_tokens,
+        sent,
+    })
+}
+
+fn unchanged(
+    text: &str,
+    source_language: &str,
+    target_language: &str,
+    options: &TranslationOptions,
+) -> TranslationOutcome {
+    TranslationOutcome {
+        text: text.to_string(),
+        applied: false,
+        source_language: source_language.to_string(),
+        target_language: target_language.to_string(),
+        backend: options.backend,
+        model: String::new(),
+        input_tokens: 0,
+        output_tokens: 0,
+        sent: false,
+    }
+}
+
+/// Translate with LibreTranslate's `/translate` endpoint
+async fn libretranslate(
+    text: &str,
+    source_language: &str,
+    target_language: &str,
+    options: &TranslationOptions,
+) -> Result<String, PostprocessError> {
+    let base_url = options
+        .base_url
+        .as_deref()
+        .unwrap_or(LIBRETRANSLATE_URL)
+        .trim_end_matches('/');
+    // Self-hosted servers usually run without keys
+    let api_key = keychain::get(keychain::SERVICE, "libretranslate_api_key")
+        .map_err(|e| PostprocessError::RequestError(e.to_string()))?;
+    let body = json!({
+        "q": text,
+        "source": language::normalize(source_language),
+        "target": language::normalize(target_language),
+        "format": "text",
+        "api_key": api_key,
+    });
+
+    let timeout_ms = options.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS);
+    let response = reqwest::Client::new()
+        .post(format!("{}/translate", base_url))
+        .timeout(Duration::from_millis(timeout_ms))
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                PostprocessError::Timeout(timeout_ms)
+            } else {
+                e.into()
+            }
+        })?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(PostprocessError::ApiError {
+            status: status.as_u16(),
+            body,
+        });
+    }
+
+    let data: serde_json::Value = response.json().await?;
+    match data["translatedText"].as_str().map(str::trim) {
+        Some(translated) if !translated.is_empty() => Ok(translated.to_string()),
+        _ => Err(PostprocessError::RequestError(
+            "LibreTranslate returned no text".to_string(),
+        )),
+    }
+}
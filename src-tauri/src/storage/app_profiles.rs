@@ -0,0 +1,152 @@
+use super::database::{self, DatabaseError};
+use crate::text::markdown::Formatting;
+use crate::text::style::SpellingVariant;
+use rusqlite::OptionalExtension;
+use serde::{Deserialize, Serialize};
+
+/// Per-application preferences, keyed by bundle ID
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppProfile {
+    pub bundle_id: String,
+    /// Default dictation language for this app
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Leave numbers, dates and addresses as spoken ("twenty five dollars")
+    #[serde(default)]
+    pub keep_spoken_forms: bool,
+    /// Format dictation as code; unset uses the built-in editor and terminal list
+    #[serde(default)]
+    pub code_mode: Option<bool>,
+    /// Markdown or plain-text structure from spoken commands; unset uses the
+    /// global setting
+    #[serde(default)]
+    pub formatting: Option<Formatting>,
+    /// Replace spoken emoji names; unset uses the built-in chat app list
+    #[serde(default)]
+    pub emoji: Option<bool>,
+    /// British or American spelling; unset uses the global setting
+    #[serde(default)]
+    pub spelling_variant: Option<SpellingVariant>,
+    /// Apply the house style rules; unset uses the global setting
+    #[serde(default)]
+    pub house_style: Option<bool>,
+    /// Break long dictations into paragraphs at pauses; unset uses the
+    /// global setting, except in chat apps
+    #[serde(default)]
+    pub paragraph_breaks: Option<bool>,
+    /// Language to translate dictation into ("en", "pt-BR"); unset inserts
+    /// text in the language spoken
+    #[serde(default)]
+    pub translate_to: Option<String>,
+}
+
+/// Load the profile for an app, if the user has configured one
+pub fn get(bundle_id: &str) -> Result<Option<AppProfile>, DatabaseError> {
+    let conn = database::connect()?;
+    conn.query_row(
+        "SELECT bundle_id, language, keep_spoken_forms, code_mode, formatting, emoji,
+                spelling_variant, house_style, paragraph_breaks, translate_to
+         FROM app_profiles WHERE bundle_id = ?1",
+        [bundle_id],
+        |row| {
+            Ok(AppProfile {
+                bundle_id: row.get(0)?,
+                language: row.get(1)?,
+                keep_spoken_forms: row.get(2)?,
+                code_mode: row.get(3)?,
+                formatting: row
+                    .get::<_, Option<String>>(4)?
+                    .map(|f| Formatting::parse(&f)),
+                emoji: row.get(5)?,
+                spelling_variant: row
+                    .get::<_, Option<String>>(6)?
+                    .map(|v| SpellingVariant::parse(&v)),
+                house_style: row.get(7)?,
+                paragraph_breaks: row.get(8)?,
+                translate_to: row.get(9)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
+
+/// List all configured app profiles
+pub fn list() -> Result<Vec<AppProfile>, DatabaseError> {
+    let conn = database::connect()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT bundle_id, language, keep_spoken_forms, code_mode, formatting, emoji,
+                spelling_variant, house_style, paragraph_breaks, translate_to
+             FROM app_profiles ORDER BY bundle_id",
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(AppProfile {
+                bundle_id: row.get(0)?,
+                language: row.get(1)?,
+                keep_spoken_forms: row.get(2)?,
+                code_mode: row.get(3)?,
+                formatting: row
+                    .get::<_, Option<String>>(4)?
+                    .map(|f| Formatting::parse(&f)),
+                emoji: row.get(5)?,
+                spelling_variant: row
+                    .get::<_, Option<String>>(6)?
+                    .map(|v| SpellingVariant::parse(&v)),
+                house_style: row.get(7)?,
+                paragraph_breaks: row.get(8)?,
+                translate_to: row.get(9)?,
+            })
+        })
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
+
+/// Create or replace the profile for an app
+pub fn save(profile: &AppProfile) -> Result<(), DatabaseError> {
+    let conn = database::connect()?;
+    conn.execute(
+        "INSERT INTO app_profiles
+            (bundle_id, language, keep_spoken_forms, code_mode, formatting, emoji,
+             spelling_variant, house_style, paragraph_breaks, translate_to, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, datetime('now'))
+         ON CONFLICT(bundle_id) DO UPDATE SET
+            language = excluded.language,
+            keep_spoken_forms = excluded.keep_spoken_forms,
+            code_mode = excluded.code_mode,
+            formatting = excluded.formatting,
+            emoji = excluded.emoji,
+            spelling_variant = excluded.spelling_variant,
+            house_style = excluded.house_style,
+            paragraph_breaks = excluded.paragraph_breaks,
+            translate_to = excluded.translate_to,
+            updated_at = excluded.updated_at",
+        rusqlite::params![
+            profile.bundle_id,
+            profile.language,
+            profile.keep_spoken_forms,
+            profile.code_mode,
+            profile.formatting.map(|f| f.as_str()),
+            profile.emoji,
+            profile.spelling_variant.map(|v| v.as_str()),
+            profile.house_style,
+            profile.paragraph_breaks,
+            profile.translate_to
+        ],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(())
+}
+
+/// Remove an app's profile so it falls back to the global settings
+pub fn delete(bundle_id: &str) -> Result<(), DatabaseError> {
+    let conn = database::connect()?;
+    conn.execute("DELETE FROM app_profiles WHERE bundle_id = ?1", [bundle_id])
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(())
+}
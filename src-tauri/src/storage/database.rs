@@ -19,16 +19,50 @@ pub fn initialize(path: &str) -> Result<(), DatabaseError> {
         .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
     *db_path = Some(path.to_string());
 
-    // TODO: Open database connection and run migrations
-    // let conn = rusqlite::Connection::open(path)
-    //     .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    let conn =
+        rusqlite::Connection::open(path).map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
 
     // Run schema migrations
-    // create_tables(&conn)?;
+    conn.execute_batch(SCHEMA)
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    add_missing_columns(&conn)?;
 
     Ok(())
 }
 
+/// Bring tables created by older versions up to date with `SCHEMA`
+fn add_missing_columns(conn: &rusqlite::Connection) -> Result<(), DatabaseError> {
+    for (table, column, definition) in ADDED_COLUMNS {
+        let mut stmt = conn
+            .prepare(&format!("PRAGMA table_info({})", table))
+            .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))
+            .map_err(|e| DatabaseError::SqliteError(e.to_string()))?
+            .filter_map(Result::ok)
+            .any(|name| name == *column);
+
+        if !exists {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table, column, definition
+            ))
+            .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Open a connection to the initialized database
+pub fn connect() -> Result<rusqlite::Connection, DatabaseError> {
+    let db_path = DB_PATH
+        .lock()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    let path = db_path.as_ref().ok_or(DatabaseError::NotInitialized)?;
+
+    rusqlite::Connection::open(path).map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
+
 /// Execute a write query
 pub fn execute(query: &str, params: &[String]) -> Result<(), DatabaseError> {
     let db_path = DB_PATH
@@ -70,6 +104,9 @@ CREATE TABLE IF NOT EXISTS history (
     duration_ms INTEGER NOT NULL DEFAULT 0,
     word_count INTEGER NOT NULL DEFAULT 0,
     target_app TEXT,
+    language_confidence REAL,
+    diff TEXT,
+    source_language TEXT,
     created_at TEXT NOT NULL DEFAULT (datetime('now'))
 );
 
@@ -93,7 +130,140 @@ CREATE TABLE IF NOT EXISTS dictionary (
     created_at TEXT NOT NULL DEFAULT (datetime('now'))
 );
 
+CREATE TABLE IF NOT EXISTS dictionary_replacements (
+    id TEXT PRIMARY KEY,
+    history_id TEXT,
+    word TEXT NOT NULL,
+    original TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS filler_words (
+    id TEXT PRIMARY KEY,
+    language TEXT NOT NULL,
+    phrase TEXT NOT NULL,
+    rule TEXT NOT NULL DEFAULT 'delimited',
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (language, phrase)
+);
+
+CREATE TABLE IF NOT EXISTS punctuation_commands (
+    id TEXT PRIMARY KEY,
+    language TEXT NOT NULL,
+    phrase TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    spacing TEXT NOT NULL DEFAULT 'attach_left',
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (language, phrase)
+);
+
+CREATE TABLE IF NOT EXISTS style_rules (
+    id TEXT PRIMARY KEY,
+    pattern TEXT NOT NULL,
+    replacement TEXT NOT NULL,
+    is_regex INTEGER NOT NULL DEFAULT 0,
+    case_sensitive INTEGER NOT NULL DEFAULT 0,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS app_profiles (
+    bundle_id TEXT PRIMARY KEY,
+    language TEXT,
+    keep_spoken_forms INTEGER NOT NULL DEFAULT 0,
+    code_mode INTEGER,
+    formatting TEXT,
+    emoji INTEGER,
+    spelling_variant TEXT,
+    house_style INTEGER,
+    paragraph_breaks INTEGER,
+    translate_to TEXT,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS transcription_queue (
+    id TEXT PRIMARY KEY,
+    audio_path TEXT NOT NULL,
+    target_app TEXT,
+    options TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    history_id TEXT,
+    next_attempt_at TEXT NOT NULL DEFAULT (datetime('now')),
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS transcript_cache (
+    key TEXT PRIMARY KEY,
+    provider TEXT NOT NULL,
+    model TEXT,
+    text TEXT NOT NULL,
+    language TEXT NOT NULL,
+    confidence REAL NOT NULL DEFAULT 1.0,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    size_bytes INTEGER NOT NULL DEFAULT 0,
+    words TEXT,
+    language_probability REAL,
+    hit_count INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    last_used_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS usage_events (
+    id TEXT PRIMARY KEY,
+    history_id TEXT,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    audio_seconds REAL NOT NULL DEFAULT 0,
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    cost_usd REAL NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS usage_daily (
+    day TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    requests INTEGER NOT NULL DEFAULT 0,
+    audio_seconds REAL NOT NULL DEFAULT 0,
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    cost_usd REAL NOT NULL DEFAULT 0,
+    PRIMARY KEY (day, provider, model)
+);
+
 CREATE INDEX IF NOT EXISTS idx_history_created_at ON history(created_at);
 CREATE INDEX IF NOT EXISTS idx_snippets_trigger ON snippets(trigger);
 CREATE INDEX IF NOT EXISTS idx_dictionary_word ON dictionary(word);
+CREATE INDEX IF NOT EXISTS idx_dictionary_replacements_history ON dictionary_replacements(history_id);
+CREATE INDEX IF NOT EXISTS idx_transcription_queue_due ON transcription_queue(status, next_attempt_at);
+CREATE INDEX IF NOT EXISTS idx_transcript_cache_last_used ON transcript_cache(last_used_at);
+CREATE INDEX IF NOT EXISTS idx_usage_events_created_at ON usage_events(created_at);
 "#;
+
+/// Columns added to existing tables after their first release: (table, column, definition)
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("history", "language_confidence", "REAL"),
+    (
+        "app_profiles",
+        "keep_spoken_forms",
+        "INTEGER NOT NULL DEFAULT 0",
+    ),
+    ("app_profiles", "code_mode", "INTEGER"),
+    ("app_profiles", "formatting", "TEXT"),
+    ("app_profiles", "emoji", "INTEGER"),
+    ("app_profiles", "spelling_variant", "TEXT"),
+    ("app_profiles", "house_style", "INTEGER"),
+    ("app_profiles", "paragraph_breaks", "INTEGER"),
+    ("transcript_cache", "words", "TEXT"),
+    ("history", "diff", "TEXT"),
+    ("app_profiles", "translate_to", "TEXT"),
+    ("history", "source_language", "TEXT"),
+    ("transcript_cache", "language_probability", "REAL"),
+];
@@ -0,0 +1,112 @@
+use super::database::{self, DatabaseError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DictionaryWord {
+    pub id: String,
+    pub word: String,
+    pub phonetic_hint: Option<String>,
+    pub category: Option<String>,
+    pub created_at: String,
+}
+
+/// Load every word in the user's custom dictionary, newest first
+pub fn list() -> Result<Vec<DictionaryWord>, DatabaseError> {
+    let conn = database::connect()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, word, phonetic_hint, category, created_at
+             FROM dictionary ORDER BY created_at DESC",
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(DictionaryWord {
+                id: row.get(0)?,
+                word: row.get(1)?,
+                phonetic_hint: row.get(2)?,
+                category: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
+
+/// A transcript word the dictionary pass replaced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DictionaryReplacement {
+    /// The dictionary word written in its place
+    pub word: String,
+    /// The words as transcribed
+    pub original: String,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedReplacement {
+    pub history_id: Option<String>,
+    #[serde(flatten)]
+    pub replacement: DictionaryReplacement,
+    pub created_at: String,
+}
+
+/// Save the replacements made in one dictation
+pub fn record_replacements(
+    history_id: &str,
+    replacements: &[DictionaryReplacement],
+) -> Result<(), DatabaseError> {
+    let mut conn = database::connect()?;
+    let tx = conn
+        .transaction()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    for replacement in replacements {
+        tx.execute(
+            "INSERT INTO dictionary_replacements (id, history_id, word, original, confidence)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                uuid::Uuid::new_v4().to_string(),
+                history_id,
+                replacement.word,
+                replacement.original,
+                replacement.confidence as f64,
+            ],
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    }
+
+    tx.commit()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
+
+/// Most recent auto-fixes, newest first
+pub fn recent_replacements(limit: u32) -> Result<Vec<RecordedReplacement>, DatabaseError> {
+    let conn = database::connect()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT history_id, word, original, confidence, created_at
+             FROM dictionary_replacements ORDER BY created_at DESC LIMIT ?1",
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    let rows = stmt
+        .query_map([limit], |row| {
+            Ok(RecordedReplacement {
+                history_id: row.get(0)?,
+                replacement: DictionaryReplacement {
+                    word: row.get(1)?,
+                    original: row.get(2)?,
+                    confidence: row.get::<_, f64>(3)? as f32,
+                },
+                created_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
@@ -0,0 +1,152 @@
+use super::database::{self, DatabaseError};
+use crate::text::diff::{self, DiffOp, Stage};
+use rusqlite::OptionalExtension;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewHistoryItem {
+    pub text: String,
+    pub original_text: Option<String>,
+    pub was_corrected: bool,
+    pub language: String,
+    pub language_confidence: Option<f32>,
+    pub duration_ms: u64,
+    pub target_app: Option<String>,
+    /// Language spoken when `text` was translated into `language`;
+    /// `original_text` holds the transcript in it
+    #[serde(default)]
+    pub source_language: Option<String>,
+    /// Word-level changes from `original_text` to `text`, by stage
+    #[serde(default)]
+    pub diff: Vec<DiffOp>,
+}
+
+/// What the pipeline changed in a dictation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryDiff {
+    pub history_id: String,
+    /// The transcript as the provider returned it
+    pub original_text: String,
+    pub text: String,
+    pub ops: Vec<DiffOp>,
+}
+
+/// Save a finished dictation and return its history ID
+pub fn insert(item: &NewHistoryItem) -> Result<String, DatabaseError> {
+    let conn = database::connect()?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let word_count = item.text.split_whitespace().count() as i64;
+    let diff = encode_diff(&item.diff)?;
+
+    conn.execute(
+        "INSERT INTO history
+            (id, text, original_text, was_corrected, language, language_confidence,
+             duration_ms, word_count, target_app, diff, source_language)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        rusqlite::params![
+            id,
+            item.text,
+            item.original_text,
+            item.was_corrected,
+            item.language,
+            item.language_confidence.map(|c| c as f64),
+            item.duration_ms as i64,
+            word_count,
+            item.target_app,
+            diff,
+            item.source_language,
+        ],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    Ok(id)
+}
+
+/// Fetch the text of the most recent dictations, newest first
+pub fn recent_texts(limit: usize) -> Result<Vec<String>, DatabaseError> {
+    let conn = database::connect()?;
+    let mut stmt = conn
+        .prepare("SELECT text FROM history ORDER BY created_at DESC LIMIT ?1")
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    let rows = stmt
+        .query_map([limit as i64], |row| row.get(0))
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    rows.collect::<Result<Vec<String>, _>>()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
+
+/// Load the changes recorded for a dictation
+///
+/// `None` if the item doesn't exist. Items saved before diffs were recorded
+/// have no ops.
+pub fn get_diff(id: &str) -> Result<Option<HistoryDiff>, DatabaseError> {
+    let conn = database::connect()?;
+    let row = conn
+        .query_row(
+            "SELECT text, original_text, diff FROM history WHERE id = ?1",
+            [id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    let Some((text, original_text, json)) = row else {
+        return Ok(None);
+    };
+
+    let ops = match json {
+        Some(json) => {
+            serde_json::from_str(&json).map_err(|e| DatabaseError::SqliteError(e.to_string()))?
+        }
+        None => Vec::new(),
+    };
+
+    Ok(Some(HistoryDiff {
+        history_id: id.to_string(),
+        original_text: original_text.unwrap_or_else(|| text.clone()),
+        text,
+        ops,
+    }))
+}
+
+/// Undo every change the given stages made to a dictation and return the
+/// new text
+pub fn revert_stages(id: &str, stages: &[Stage]) -> Result<Option<String>, DatabaseError> {
+    let Some(mut item) = get_diff(id)? else {
+        return Ok(None);
+    };
+    if item.ops.is_empty() {
+        return Ok(Some(item.text));
+    }
+    diff::revert(&mut item.ops, stages);
+    let text = diff::render(&item.ops);
+
+    let conn = database::connect()?;
+    conn.execute(
+        "UPDATE history SET text = ?1, word_count = ?2, diff = ?3 WHERE id = ?4",
+        rusqlite::params![
+            text,
+            text.split_whitespace().count() as i64,
+            encode_diff(&item.ops)?,
+            id,
+        ],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(Some(text))
+}
+
+fn encode_diff(ops: &[DiffOp]) -> Result<Option<String>, DatabaseError> {
+    if ops.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_string(ops)
+        .map(Some)
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
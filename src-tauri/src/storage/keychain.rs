@@ -1,5 +1,8 @@
 use thiserror::Error;
 
+/// Keychain service name used for REDE's own credentials (provider API keys)
+pub const SERVICE: &str = "com.rede.app";
+
 #[derive(Error, Debug)]
 pub enum KeychainError {
     #[error("Keychain error: {0}")]
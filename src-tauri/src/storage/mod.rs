@@ -1,2 +1,7 @@
+pub mod app_profiles;
 pub mod database;
+pub mod dictionary;
+pub mod history;
 pub mod keychain;
+pub mod settings;
+pub mod snippets;
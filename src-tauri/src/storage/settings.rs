@@ -0,0 +1,32 @@
+use super::database::{self, DatabaseError};
+use rusqlite::OptionalExtension;
+
+/// Read a raw value from the settings table
+pub fn get(key: &str) -> Result<Option<String>, DatabaseError> {
+    let conn = database::connect()?;
+    conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+        row.get(0)
+    })
+    .optional()
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
+
+/// Write a raw value to the settings table
+pub fn set(key: &str, value: &str) -> Result<(), DatabaseError> {
+    let conn = database::connect()?;
+    conn.execute(
+        "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, datetime('now'))
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        [key, value],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(())
+}
+
+/// Remove a value from the settings table
+pub fn remove(key: &str) -> Result<(), DatabaseError> {
+    let conn = database::connect()?;
+    conn.execute("DELETE FROM settings WHERE key = ?1", [key])
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(())
+}
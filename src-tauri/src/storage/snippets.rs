@@ -0,0 +1,83 @@
+use super::database::{self, DatabaseError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableKind {
+    Date,
+    Time,
+    Datetime,
+    Clipboard,
+    Random,
+}
+
+/// A `{key}` placeholder in snippet content and what fills it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnippetVariable {
+    pub key: String,
+    #[serde(rename = "type")]
+    pub kind: VariableKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    pub id: String,
+    pub trigger: String,
+    pub content: String,
+    #[serde(default)]
+    pub variables: Vec<SnippetVariable>,
+    pub case_sensitive: bool,
+    pub enabled: bool,
+    pub usage_count: i64,
+}
+
+/// Load the snippets that can currently expand
+pub fn list_enabled() -> Result<Vec<Snippet>, DatabaseError> {
+    let conn = database::connect()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, trigger, content, variables, case_sensitive, enabled, usage_count
+             FROM snippets WHERE enabled = 1",
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            let variables: Option<String> = row.get(3)?;
+            Ok(Snippet {
+                id: row.get(0)?,
+                trigger: row.get(1)?,
+                content: row.get(2)?,
+                variables: variables
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_default(),
+                case_sensitive: row.get(4)?,
+                enabled: row.get(5)?,
+                usage_count: row.get(6)?,
+            })
+        })
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
+
+/// Count one expansion per ID; repeated IDs count once per occurrence
+pub fn record_use(ids: &[String]) -> Result<(), DatabaseError> {
+    let mut conn = database::connect()?;
+    let tx = conn
+        .transaction()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    for id in ids {
+        // Incremented in SQL so concurrent dictations don't lose counts
+        tx.execute(
+            "UPDATE snippets SET usage_count = usage_count + 1 WHERE id = ?1",
+            [id],
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    }
+
+    tx.commit()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
@@ -1,3 +1,7 @@
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,9 +10,29 @@ pub enum NotificationError {
     SendError(String),
 }
 
+static APP: Lazy<Mutex<Option<AppHandle>>> = Lazy::new(|| Mutex::new(None));
+
+/// Keep the app handle notifications are sent through
+pub fn initialize(app: AppHandle) -> Result<(), NotificationError> {
+    let mut handle = APP
+        .lock()
+        .map_err(|e| NotificationError::SendError(e.to_string()))?;
+    *handle = Some(app);
+    Ok(())
+}
+
 /// Send a native macOS notification
 pub fn send(title: &str, body: &str) -> Result<(), NotificationError> {
-    // TODO: Use tauri-plugin-notification
-    let _ = (title, body);
-    Ok(())
+    let app = APP
+        .lock()
+        .map_err(|e| NotificationError::SendError(e.to_string()))?
+        .clone()
+        .ok_or_else(|| NotificationError::SendError("notifications not initialized".to_string()))?;
+
+    app.notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+        .map_err(|e| NotificationError::SendError(e.to_string()))
 }
@@ -1,4 +1,5 @@
 use super::AppInfo;
+use std::process::Command;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -9,10 +10,55 @@ pub enum TrayError {
 
 /// Get information about the currently focused application
 pub fn get_focused_app() -> Result<Option<AppInfo>, TrayError> {
-    // TODO: Use NSWorkspace to get the frontmost application
-    // let workspace = NSWorkspace::sharedWorkspace();
-    // let app = workspace.frontmostApplication();
-    Ok(None)
+    // NSWorkspace's frontmost application, through JavaScript for Automation
+    let output = Command::new("osascript")
+        .args([
+            "-l",
+            "JavaScript",
+            "-e",
+            "ObjC.import('AppKit')",
+            "-e",
+            "const app = $.NSWorkspace.sharedWorkspace.frontmostApplication; \
+             app.isNil() ? '' : [app.bundleIdentifier.js, app.localizedName.js].join('\\n')",
+        ])
+        .output()
+        .map_err(|e| TrayError::SystemError(e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(TrayError::SystemError(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.trim().lines();
+    let bundle_id = lines.next().unwrap_or_default().trim();
+    if bundle_id.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(AppInfo {
+        name: lines.next().unwrap_or(bundle_id).trim().to_string(),
+        bundle_id: bundle_id.to_string(),
+    }))
+}
+
+/// Whether an app is an editor or terminal where dictation is formatted as code
+pub fn is_code_app(bundle_id: &str) -> bool {
+    matches!(
+        bundle_id,
+        "com.microsoft.VSCode"
+            | "com.microsoft.VSCodeInsiders"
+            | "com.todesktop.230313mzl4w4u92" // Cursor
+            | "dev.zed.Zed"
+            | "com.sublimetext.4"
+            | "com.apple.dt.Xcode"
+            | "com.apple.Terminal"
+            | "com.googlecode.iterm2"
+            | "dev.warp.Warp-Stable"
+            | "net.kovidgoyal.kitty"
+            | "com.github.wez.wezterm"
+            | "io.alacritty"
+            | "com.mitchellh.ghostty"
+    ) || bundle_id.starts_with("com.jetbrains.")
 }
 
 /// Get the tone context for a given application
@@ -25,3 +71,12 @@ pub fn get_app_tone(bundle_id: &str) -> &'static str {
         _ => "neutral",
     }
 }
+
+/// Whether an app is for chat, where dictation takes emoji and stays on one
+/// line by default; matches `emoji: true` in the frontend's `APP_CONTEXTS`
+pub fn is_chat_app(bundle_id: &str) -> bool {
+    matches!(
+        bundle_id,
+        "com.tinyspeck.slackmacgap" | "com.apple.MobileSMS"
+    )
+}
@@ -0,0 +1,543 @@
+use super::normalization;
+use super::tokens::{self, Token};
+
+/// Identifier style a casing command asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Casing {
+    Camel,
+    Pascal,
+    Snake,
+    Kebab,
+    Constant,
+}
+
+const CASING_COMMANDS: &[(&str, Casing)] = &[
+    ("camel case", Casing::Camel),
+    ("pascal case", Casing::Pascal),
+    ("snake case", Casing::Snake),
+    ("kebab case", Casing::Kebab),
+    ("constant case", Casing::Constant),
+    ("screaming snake case", Casing::Constant),
+];
+
+impl Casing {
+    fn apply(&self, parts: &[String]) -> String {
+        match self {
+            Casing::Camel => parts
+                .iter()
+                .enumerate()
+                .map(|(i, p)| if i == 0 { p.clone() } else { capitalized(p) })
+                .collect(),
+            Casing::Pascal => parts.iter().map(|p| capitalized(p)).collect(),
+            Casing::Snake => parts.join("_"),
+            Casing::Kebab => parts.join("-"),
+            Casing::Constant => parts.join("_").to_uppercase(),
+        }
+    }
+}
+
+/// How a spoken symbol is spaced in code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    /// Spaced on both sides: `=` `==` `&&`
+    Operator,
+    /// Joined to a name before it, as in a call: `(` `[` `{`
+    Open,
+    /// Joined to what came before: `)` `]` `}`
+    Close,
+    /// Joined left and spaced after: `,` `;` `:`
+    Separator,
+    /// Joined on both sides: `.` `::` `_`
+    Tight,
+    /// Joined to the next word: `!` `@` and `-` for flags
+    Prefix,
+    /// Opens a string, or closes the one that is open
+    Quote,
+    /// Line breaks and tabs
+    Whitespace,
+}
+
+use Role::{Close, Open, Operator, Prefix, Quote, Separator, Tight, Whitespace};
+
+const SYMBOLS: &[(&str, &str, Role)] = &[
+    ("equals", "=", Operator),
+    ("double equals", "==", Operator),
+    ("equals equals", "==", Operator),
+    ("triple equals", "===", Operator),
+    ("not equals", "!=", Operator),
+    ("plus equals", "+=", Operator),
+    ("minus equals", "-=", Operator),
+    ("times equals", "*=", Operator),
+    ("plus", "+", Operator),
+    ("minus", "-", Operator),
+    ("times", "*", Operator),
+    ("star", "*", Operator),
+    ("divided by", "/", Operator),
+    ("modulo", "%", Operator),
+    ("less than", "<", Operator),
+    ("greater than", ">", Operator),
+    ("less than or equal", "<=", Operator),
+    ("greater than or equal", ">=", Operator),
+    ("arrow", "->", Operator),
+    ("fat arrow", "=>", Operator),
+    ("and and", "&&", Operator),
+    ("double ampersand", "&&", Operator),
+    ("or or", "||", Operator),
+    ("double pipe", "||", Operator),
+    ("ampersand", "&", Operator),
+    ("pipe", "|", Operator),
+    ("caret", "^", Operator),
+    ("question mark", "?", Operator),
+    ("open paren", "(", Open),
+    ("open parenthesis", "(", Open),
+    ("open bracket", "[", Open),
+    ("open square bracket", "[", Open),
+    ("open brace", "{", Open),
+    ("open curly", "{", Open),
+    ("open curly brace", "{", Open),
+    ("close paren", ")", Close),
+    ("close parenthesis", ")", Close),
+    ("close bracket", "]", Close),
+    ("close square bracket", "]", Close),
+    ("close brace", "}", Close),
+    ("close curly", "}", Close),
+    ("close curly brace", "}", Close),
+    ("comma", ",", Separator),
+    ("semicolon", ";", Separator),
+    ("colon", ":", Separator),
+    ("double colon", "::", Tight),
+    ("dot", ".", Tight),
+    ("dot dot", "..", Prefix),
+    ("underscore", "_", Tight),
+    ("hyphen", "-", Tight),
+    ("slash", "/", Tight),
+    ("backslash", "\\", Tight),
+    ("bang", "!", Prefix),
+    ("exclamation mark", "!", Prefix),
+    ("at sign", "@", Prefix),
+    ("hash", "#", Prefix),
+    ("pound sign", "#", Prefix),
+    ("dollar sign", "$", Prefix),
+    ("tilde", "~", Prefix),
+    ("dash", "-", Prefix),
+    ("double dash", "--", Prefix),
+    ("quote", "\"", Quote),
+    ("double quote", "\"", Quote),
+    ("single quote", "'", Quote),
+    ("backtick", "`", Quote),
+    ("new line", "\n", Whitespace),
+    ("newline", "\n", Whitespace),
+    ("tab", "\t", Whitespace),
+];
+
+/// Words after which an opening bracket is spaced instead of read as a call
+const KEYWORDS: &[&str] = &[
+    "if", "elif", "for", "while", "switch", "catch", "match", "return", "in", "and", "or", "not",
+];
+
+/// Writes the next word as spoken ("literal equals")
+const LITERAL: &str = "literal";
+
+/// Punctuation the provider adds for pauses, which means nothing in code
+const PROSE_MARKS: &[char] = &[',', '.', ';', ':', '!', '?'];
+
+/// Format dictation as code
+///
+/// Casing commands ("snake case user id") build one identifier from the
+/// words up to the next symbol, command or pause; spoken symbols become
+/// their characters and number words become digits. Commands are English
+/// whatever the dictation language.
+pub fn format(text: &str) -> String {
+    let tokens = tokens::tokenize(text);
+    let mut writer = Writer::default();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = &tokens[i];
+        for _ in token.lead.matches('\n') {
+            writer.symbol("\n", Whitespace);
+        }
+
+        if token.core.is_empty() {
+            // A symbol the provider already wrote out
+            let raw = token.lead.trim_start_matches('\n');
+            if !raw.is_empty() {
+                writer.word(raw, false);
+            }
+            i += 1;
+            continue;
+        }
+
+        if token.lower() == LITERAL && token.trail.is_empty() && i + 1 < tokens.len() {
+            writer.word(&bare(&tokens[i + 1]), false);
+            i += 2;
+            continue;
+        }
+
+        if let Some((len, casing)) = casing_at(&tokens, i) {
+            let (used, parts) = identifier_parts(&tokens, i + len);
+            if !parts.is_empty() {
+                writer.word(&casing.apply(&parts), false);
+            }
+            i += len + used;
+            continue;
+        }
+
+        if let Some((len, symbol, role)) = symbol_at(&tokens, i) {
+            writer.symbol(symbol, role);
+            i += len;
+            continue;
+        }
+
+        if let Some((len, digits)) = normalization::digits_at(&tokens, i) {
+            writer.word(&digits, false);
+            i += len;
+            continue;
+        }
+
+        let mut word = token.clone();
+        let sentence_start = i == 0 || tokens[i - 1].ends_sentence();
+        if sentence_start && is_titlecase(&word.core) {
+            // Capitalized by the provider, not by the speaker
+            word.decapitalize();
+        }
+        writer.word(&bare(&word), KEYWORDS.contains(&token.lower().as_str()));
+        i += 1;
+    }
+
+    writer.out.trim_end_matches(' ').to_string()
+}
+
+/// The casing command starting at `tokens[start]`, as written by the
+/// provider either way ("snake case" or "snakecase")
+fn casing_at(tokens: &[Token], start: usize) -> Option<(usize, Casing)> {
+    CASING_COMMANDS.iter().find_map(|(phrase, casing)| {
+        let parts: Vec<&str> = phrase.split(' ').collect();
+        if tokens[start].lower() == parts.concat() {
+            return Some((1, *casing));
+        }
+        phrase_at(tokens, start, &parts).then_some((parts.len(), *casing))
+    })
+}
+
+/// The longest spoken symbol starting at `tokens[start]`
+fn symbol_at(tokens: &[Token], start: usize) -> Option<(usize, &'static str, Role)> {
+    SYMBOLS
+        .iter()
+        .filter_map(|(phrase, symbol, role)| {
+            let parts: Vec<&str> = phrase.split(' ').collect();
+            phrase_at(tokens, start, &parts).then_some((parts.len(), *symbol, *role))
+        })
+        .max_by_key(|(len, _, _)| *len)
+}
+
+/// Whether `parts` are spoken at `tokens[start]` without a pause between them
+fn phrase_at(tokens: &[Token], start: usize, parts: &[&str]) -> bool {
+    let Some(window) = tokens.get(start..start + parts.len()) else {
+        return false;
+    };
+    window
+        .iter()
+        .zip(parts)
+        .enumerate()
+        .all(|(k, (token, part))| {
+            let joined = (k == 0 || token.lead.is_empty())
+                && (k == parts.len() - 1 || token.trail.is_empty());
+            joined && token.lower() == *part
+        })
+}
+
+/// Lowercase words of the identifier after a casing command, and how many
+/// tokens they took
+fn identifier_parts(tokens: &[Token], start: usize) -> (usize, Vec<String>) {
+    let mut parts = Vec::new();
+    let mut j = start;
+
+    while j < tokens.len() {
+        let token = &tokens[j];
+        let ends_here = token.core.is_empty()
+            || !token.lead.is_empty()
+            || token.lower() == LITERAL
+            || casing_at(tokens, j).is_some()
+            || symbol_at(tokens, j).is_some();
+        if ends_here {
+            break;
+        }
+
+        if let Some((len, digits)) = normalization::digits_at(tokens, j) {
+            parts.push(digits);
+            j += len;
+        } else {
+            parts.extend(split_words(&token.core));
+            j += 1;
+        }
+        // The speaker paused, which ends the identifier
+        if !tokens[j - 1].trail.is_empty() {
+            break;
+        }
+    }
+
+    (j - start, parts)
+}
+
+/// Split a written word into lowercase parts at punctuation and humps
+/// ("userID" and "user-id" are both `["user", "id"]`)
+fn split_words(word: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+
+    for c in word.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower {
+            parts.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_lowercase() || c.is_numeric();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+fn capitalized(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// "Return", but not "I", "ID" or "UserService"
+fn is_titlecase(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars.next().is_some_and(char::is_uppercase)
+        && word.chars().count() > 1
+        && chars.all(char::is_lowercase)
+}
+
+/// The word without the provider's pause punctuation
+fn bare(token: &Token) -> String {
+    format!(
+        "{}{}{}",
+        token.lead.trim_start_matches('\n'),
+        token.core,
+        token.trail.trim_end_matches(PROSE_MARKS)
+    )
+}
+
+/// What was last written, which decides the spacing of the next piece
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Last {
+    Start,
+    Word,
+    Keyword,
+    Operator,
+    Open,
+    Close,
+    Separator,
+    Tight,
+    Prefix,
+    Whitespace,
+}
+
+struct Writer {
+    out: String,
+    last: Last,
+    open_quote: Option<&'static str>,
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self {
+            out: String::new(),
+            last: Last::Start,
+            open_quote: None,
+        }
+    }
+}
+
+impl Writer {
+    fn word(&mut self, word: &str, keyword: bool) {
+        if self.spaced_after() {
+            self.out.push(' ');
+        }
+        self.out.push_str(word);
+        self.last = if keyword { Last::Keyword } else { Last::Word };
+    }
+
+    fn symbol(&mut self, symbol: &'static str, role: Role) {
+        let role = match role {
+            // Unary minus: "equals minus one" is `= -1`
+            Operator if symbol == "-" && !matches!(self.last, Last::Word | Last::Close) => Prefix,
+            Quote => match self.open_quote {
+                Some(open) if open == symbol => {
+                    self.open_quote = None;
+                    Close
+                }
+                // An apostrophe inside a string
+                Some(_) => Tight,
+                None => {
+                    self.open_quote = Some(symbol);
+                    Prefix
+                }
+            },
+            role => role,
+        };
+
+        match role {
+            Operator => {
+                self.trim_spaces();
+                if !matches!(self.last, Last::Start | Last::Open | Last::Whitespace) {
+                    self.out.push(' ');
+                }
+                self.last = Last::Operator;
+            }
+            Open => {
+                // `foo(` and `items[`, but `if (` and `fn main() {`
+                let spaced = if symbol == "{" {
+                    self.spaced_after()
+                } else {
+                    matches!(self.last, Last::Keyword | Last::Operator | Last::Separator)
+                };
+                if spaced {
+                    self.out.push(' ');
+                }
+                self.last = Last::Open;
+            }
+            Close => {
+                self.trim_spaces();
+                self.last = Last::Close;
+            }
+            Separator => {
+                self.trim_spaces();
+                self.last = Last::Separator;
+            }
+            Tight => {
+                self.trim_spaces();
+                self.last = Last::Tight;
+            }
+            Prefix => {
+                if self.spaced_after() {
+                    self.out.push(' ');
+                }
+                self.last = Last::Prefix;
+            }
+            Quote | Whitespace => {
+                self.trim_spaces();
+                self.last = Last::Whitespace;
+            }
+        }
+        self.out.push_str(symbol);
+    }
+
+    /// Whether a word or prefix written now needs a space first
+    fn spaced_after(&self) -> bool {
+        matches!(
+            self.last,
+            Last::Word | Last::Keyword | Last::Operator | Last::Close | Last::Separator
+        )
+    }
+
+    fn trim_spaces(&mut self) {
+        let kept = self.out.trim_end_matches(' ').len();
+        self.out.truncate(kept);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_code_corpus() {
+        let cases: &[(&str, &str)] = &[
+            // Casing commands
+            (
+                "snake case user id equals open paren close paren",
+                "user_id = ()",
+            ),
+            ("camel case get user name", "getUserName"),
+            ("pascal case http client", "HttpClient"),
+            ("kebab case main nav bar", "main-nav-bar"),
+            ("constant case max retries equals five", "MAX_RETRIES = 5"),
+            ("screaming snake case api key", "API_KEY"),
+            ("Camel case user ID.", "userId"),
+            ("camelcase is ready", "isReady"),
+            ("snake case item two", "item_2"),
+            (
+                "camel case first name, camel case last name",
+                "firstName lastName",
+            ),
+            // Symbols and spacing
+            (
+                "const camel case total equals price times quantity semicolon",
+                "const total = price * quantity;",
+            ),
+            (
+                "camel case fetch user open paren camel case user id close paren",
+                "fetchUser(userId)",
+            ),
+            (
+                "if open paren x double equals ten close paren open brace",
+                "if (x == 10) {",
+            ),
+            ("x not equals null", "x != null"),
+            ("a and and bang b", "a && !b"),
+            ("return minus one", "return -1"),
+            ("x equals minus five", "x = -5"),
+            ("i plus equals two", "i += 2"),
+            (
+                "items open bracket zero close bracket dot name",
+                "items[0].name",
+            ),
+            ("std double colon vec double colon new", "std::vec::new"),
+            (
+                "fn main open paren close paren arrow result",
+                "fn main() -> result",
+            ),
+            (
+                "open paren a comma b close paren fat arrow a plus b",
+                "(a, b) => a + b",
+            ),
+            ("at sign decorator", "@decorator"),
+            ("x equals three point five", "x = 3.5"),
+            ("name equals quote hello quote", "name = \"hello\""),
+            (
+                "print open paren single quote hi single quote close paren",
+                "print('hi')",
+            ),
+            // Terminals
+            ("git commit dash m quote fix quote", "git commit -m \"fix\""),
+            ("ls dash la tilde slash projects", "ls -la ~/projects"),
+            ("npm install double dash save dev", "npm install --save dev"),
+            (
+                "cat file dot txt pipe grep error",
+                "cat file.txt | grep error",
+            ),
+            ("cd dot dot slash src", "cd ../src"),
+            // Line breaks, literals and provider punctuation
+            (
+                "open brace new line tab return x new line close brace",
+                "{\n\treturn x\n}",
+            ),
+            ("literal equals sign", "equals sign"),
+            ("Return x.", "return x"),
+            (
+                "Snake case user ID equals open paren, close paren.",
+                "user_id = ()",
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(format(input), *expected, "input: {:?}", input);
+        }
+    }
+}
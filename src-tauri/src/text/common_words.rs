@@ -0,0 +1,341 @@
+/// Frequent English words, for telling ordinary prose from names and
+/// addresses ("look at google dot com" isn't an email)
+const COMMON: &[&str] = &[
+    "a",
+    "about",
+    "above",
+    "across",
+    "add",
+    "after",
+    "again",
+    "against",
+    "ago",
+    "all",
+    "also",
+    "always",
+    "am",
+    "an",
+    "and",
+    "another",
+    "answer",
+    "any",
+    "anything",
+    "are",
+    "area",
+    "around",
+    "as",
+    "ask",
+    "at",
+    "away",
+    "back",
+    "bad",
+    "be",
+    "because",
+    "been",
+    "before",
+    "being",
+    "below",
+    "best",
+    "better",
+    "between",
+    "big",
+    "bit",
+    "book",
+    "both",
+    "box",
+    "but",
+    "buy",
+    "by",
+    "call",
+    "came",
+    "can",
+    "car",
+    "case",
+    "cat",
+    "change",
+    "check",
+    "city",
+    "click",
+    "close",
+    "code",
+    "come",
+    "company",
+    "could",
+    "day",
+    "days",
+    "did",
+    "do",
+    "does",
+    "dog",
+    "done",
+    "door",
+    "down",
+    "each",
+    "early",
+    "end",
+    "even",
+    "ever",
+    "every",
+    "eye",
+    "face",
+    "fact",
+    "far",
+    "feel",
+    "few",
+    "file",
+    "find",
+    "fine",
+    "first",
+    "for",
+    "form",
+    "found",
+    "free",
+    "friend",
+    "from",
+    "full",
+    "game",
+    "gave",
+    "get",
+    "give",
+    "go",
+    "going",
+    "good",
+    "got",
+    "great",
+    "group",
+    "had",
+    "hand",
+    "has",
+    "have",
+    "he",
+    "head",
+    "hear",
+    "help",
+    "her",
+    "here",
+    "him",
+    "his",
+    "home",
+    "hope",
+    "hour",
+    "house",
+    "how",
+    "i",
+    "idea",
+    "if",
+    "in",
+    "info",
+    "into",
+    "is",
+    "it",
+    "its",
+    "job",
+    "just",
+    "keep",
+    "kind",
+    "knew",
+    "know",
+    "last",
+    "late",
+    "later",
+    "leave",
+    "left",
+    "less",
+    "let",
+    "life",
+    "like",
+    "line",
+    "link",
+    "list",
+    "little",
+    "live",
+    "long",
+    "look",
+    "looking",
+    "lot",
+    "love",
+    "made",
+    "mail",
+    "main",
+    "make",
+    "man",
+    "many",
+    "may",
+    "me",
+    "mean",
+    "meet",
+    "might",
+    "mind",
+    "more",
+    "most",
+    "move",
+    "much",
+    "must",
+    "my",
+    "name",
+    "need",
+    "never",
+    "new",
+    "news",
+    "next",
+    "nice",
+    "night",
+    "no",
+    "not",
+    "note",
+    "now",
+    "number",
+    "of",
+    "off",
+    "office",
+    "often",
+    "old",
+    "on",
+    "once",
+    "one",
+    "only",
+    "open",
+    "or",
+    "order",
+    "other",
+    "our",
+    "out",
+    "over",
+    "own",
+    "page",
+    "part",
+    "people",
+    "phone",
+    "place",
+    "plan",
+    "play",
+    "please",
+    "point",
+    "post",
+    "put",
+    "read",
+    "real",
+    "right",
+    "room",
+    "run",
+    "said",
+    "same",
+    "saw",
+    "say",
+    "school",
+    "search",
+    "see",
+    "seen",
+    "send",
+    "sent",
+    "set",
+    "she",
+    "shop",
+    "should",
+    "show",
+    "side",
+    "sign",
+    "site",
+    "small",
+    "so",
+    "some",
+    "something",
+    "soon",
+    "start",
+    "still",
+    "stop",
+    "store",
+    "story",
+    "such",
+    "support",
+    "sure",
+    "take",
+    "talk",
+    "team",
+    "tell",
+    "than",
+    "thank",
+    "thanks",
+    "that",
+    "the",
+    "their",
+    "them",
+    "then",
+    "there",
+    "these",
+    "they",
+    "thing",
+    "things",
+    "think",
+    "this",
+    "those",
+    "time",
+    "to",
+    "today",
+    "told",
+    "too",
+    "took",
+    "top",
+    "try",
+    "turn",
+    "two",
+    "under",
+    "until",
+    "up",
+    "us",
+    "use",
+    "used",
+    "very",
+    "visit",
+    "wait",
+    "want",
+    "was",
+    "watch",
+    "water",
+    "way",
+    "we",
+    "web",
+    "website",
+    "week",
+    "well",
+    "went",
+    "were",
+    "what",
+    "when",
+    "where",
+    "which",
+    "while",
+    "who",
+    "why",
+    "will",
+    "win",
+    "with",
+    "word",
+    "work",
+    "world",
+    "would",
+    "write",
+    "year",
+    "yes",
+    "yet",
+    "you",
+    "your",
+];
+
+/// Function words, which rarely belong to a name or term
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "he", "her", "him",
+    "his", "i", "if", "in", "is", "it", "its", "me", "my", "no", "not", "of", "on", "or", "our",
+    "she", "so", "that", "the", "their", "them", "then", "they", "this", "to", "up", "us", "was",
+    "we", "were", "with", "you", "your",
+];
+
+/// Whether a lowercase word is a function word ("and", "her")
+pub fn is_stopword(word: &str) -> bool {
+    STOPWORDS.contains(&word)
+}
+
+/// Whether a lowercase word is everyday English
+pub fn is_common(word: &str) -> bool {
+    COMMON.contains(&word)
+}
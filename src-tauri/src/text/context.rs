@@ -0,0 +1,216 @@
+use super::common_words;
+use super::markdown;
+use super::tokens;
+use super::ProcessingOptions;
+use crate::transcription::language;
+
+/// Characters before the cursor read to decide spacing and capitalization
+pub const CONTEXT_CHARS: usize = 64;
+
+/// Characters after which the next word attaches without a space
+const OPENERS: &[char] = &['(', '[', '{', '"', '“', '‘', '«', '/', '@', '#', '$', '-'];
+
+/// Characters that close a phrase and may follow a sentence end (`."`, `.)`)
+const CLOSERS: &[char] = &[')', ']', '}', '"', '”', '’', '»', '\''];
+
+/// Characters that attach to the previous word without a space
+const ATTACHES_LEFT: &[char] = &[
+    ',', '.', ';', ':', '!', '?', ')', ']', '}', '”', '’', '»', '…', '%',
+];
+
+/// Fit dictated text to the text already before the cursor
+///
+/// Decides the leading space, whether the first word is capitalized and
+/// whether the provider's closing period belongs. `before` is `None` when the
+/// insertion layer can't read the field; the text is then treated as a new
+/// sentence. Text starting with one of `keep` (dictionary words, snippet
+/// output) keeps its case, and only everyday English words are lowercased
+/// mid-sentence, so names and German nouns stay capitalized.
+pub fn fit(
+    text: &str,
+    before: Option<&str>,
+    language: &str,
+    keep: &[&str],
+    options: &ProcessingOptions,
+) -> String {
+    let text = text.trim();
+    if text.is_empty() {
+        return String::new();
+    }
+
+    let sentence_start = match before {
+        Some(before) => starts_sentence(before),
+        None => true,
+    };
+
+    let mut tokens = tokens::tokenize(text);
+    if options.auto_capitalize && !options.code_mode {
+        if let Some(first) = tokens.iter_mut().find(|t| !t.core.is_empty()) {
+            if sentence_start {
+                first.capitalize();
+            } else if language::normalize(language) == "en"
+                && is_plainly_capitalized(&first.core)
+                && common_words::is_common(&first.lower())
+                && !keep
+                    .iter()
+                    .any(|k| !k.is_empty() && text.starts_with(k.trim()))
+            {
+                first.decapitalize();
+            }
+        }
+    }
+    let mut text = tokens::join(&tokens);
+
+    if options.auto_punctuation && !options.code_mode && !markdown::ends_in_block(&text) {
+        if sentence_start {
+            // A new sentence should end like one
+            if text.ends_with(|c: char| c.is_alphanumeric()) {
+                text.push('.');
+            }
+        } else if text.ends_with('.') && !text.ends_with("..") && !has_inner_sentence_end(&text) {
+            // A fragment dropped into a sentence doesn't close it
+            text.pop();
+        }
+    }
+
+    match before {
+        Some(before) if needs_space(before, &text) => format!(" {}", text),
+        _ => text,
+    }
+}
+
+/// Whether text typed after `before` begins a new sentence
+fn starts_sentence(before: &str) -> bool {
+    if before.trim_end_matches([' ', '\t']).ends_with('\n') {
+        return true;
+    }
+    let trimmed = before.trim_end().trim_end_matches(|c: char| {
+        OPENERS.contains(&c) || CLOSERS.contains(&c) || c.is_whitespace()
+    });
+    trimmed.is_empty() || trimmed.ends_with(['.', '!', '?'])
+}
+
+fn needs_space(before: &str, text: &str) -> bool {
+    let Some(last) = before.chars().last() else {
+        return false;
+    };
+    !last.is_whitespace()
+        && !OPENERS.contains(&last)
+        && !text.starts_with(ATTACHES_LEFT)
+        && !text.starts_with(char::is_whitespace)
+}
+
+/// Capitalized only because it opened the provider's sentence: "The", not
+/// "I", "I'm", "NASA" or "iPhone"
+fn is_plainly_capitalized(word: &str) -> bool {
+    let mut chars = word.chars();
+    let first_upper = chars.next().is_some_and(char::is_uppercase);
+    let rest_lower = chars.all(|c| !c.is_uppercase());
+    let pronoun_i = word == "I" || word.starts_with("I'") || word.starts_with("I’");
+    first_upper && rest_lower && !pronoun_i
+}
+
+/// Whether the text closes a sentence before its final character
+fn has_inner_sentence_end(text: &str) -> bool {
+    let body = &text[..text.len() - 1];
+    body.contains(". ") || body.contains("! ") || body.contains("? ") || body.contains('\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> ProcessingOptions {
+        ProcessingOptions {
+            auto_capitalize: true,
+            auto_punctuation: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fits_text_to_the_cursor() {
+        let cases: &[(&str, Option<&str>, &str, &str)] = &[
+            (
+                "The meeting is at noon.",
+                Some("I think "),
+                "en",
+                "the meeting is at noon",
+            ),
+            (
+                "the meeting is at noon",
+                None,
+                "en",
+                "The meeting is at noon.",
+            ),
+            (
+                "the meeting is at noon",
+                Some("Done. "),
+                "en",
+                "The meeting is at noon.",
+            ),
+            (
+                "the meeting is at noon",
+                Some("Done.\n"),
+                "en",
+                "The meeting is at noon.",
+            ),
+            (
+                "The meeting is at noon.",
+                Some("I think"),
+                "en",
+                " the meeting is at noon",
+            ),
+            ("Fine.", Some("(\""), "en", "Fine."),
+            (
+                "Siobhan will call.",
+                Some("I think "),
+                "en",
+                "Siobhan will call",
+            ),
+            ("Kubernetes.", Some("we use "), "en", "Kubernetes"),
+            ("I'm late.", Some("sorry, "), "en", "I'm late"),
+            ("NASA called.", Some("so "), "en", "NASA called"),
+            (
+                "Das Haus ist groß.",
+                Some("Ich glaube "),
+                "de",
+                "Das Haus ist groß",
+            ),
+            (
+                "It works. Ship it.",
+                Some("and "),
+                "en",
+                "it works. Ship it.",
+            ),
+            (", then lunch.", Some("meeting"), "en", ", then lunch"),
+        ];
+        for (text, before, language, expected) in cases {
+            assert_eq!(
+                fit(text, *before, language, &[], &options()),
+                *expected,
+                "text: {:?}, before: {:?}",
+                text,
+                before
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_dictionary_and_snippet_case() {
+        assert_eq!(
+            fit(
+                "Best regards, Sam",
+                Some("so "),
+                "en",
+                &["Best regards, Sam"],
+                &options()
+            ),
+            "Best regards, Sam"
+        );
+        assert_eq!(
+            fit("Best regards, Sam", Some("so "), "en", &[], &options()),
+            "best regards, Sam"
+        );
+    }
+}
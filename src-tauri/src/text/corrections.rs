@@ -0,0 +1,522 @@
+use super::tokens::{self, Token};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CueKind {
+    /// Replace the span before the cue with the words after it
+    Replace,
+    /// Discard the clause (or sentence) before the cue
+    Scratch,
+}
+
+/// Pauses a cue must be set off by before it's treated as a correction,
+/// so "I actually like it" and "no wait time" are left alone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pause {
+    Before,
+    Both,
+}
+
+struct Cue {
+    phrase: &'static str,
+    kind: CueKind,
+    pause: Pause,
+    /// May revise the previous sentence when spoken at the start of a new one
+    cross_sentence: bool,
+}
+
+const fn cue(phrase: &'static str, kind: CueKind, pause: Pause, cross_sentence: bool) -> Cue {
+    Cue {
+        phrase,
+        kind,
+        pause,
+        cross_sentence,
+    }
+}
+
+use CueKind::{Replace, Scratch};
+
+// Longer phrases first so "I meant to say" wins over "I meant"
+const EN_CUES: &[Cue] = &[
+    cue("let me rephrase that", Scratch, Pause::Before, true),
+    cue("let me rephrase", Scratch, Pause::Before, true),
+    cue("scratch that", Scratch, Pause::Before, true),
+    cue("forget that", Scratch, Pause::Before, true),
+    cue("what I meant was", Replace, Pause::Before, false),
+    cue("I meant to say", Replace, Pause::Before, false),
+    cue("no wait", Replace, Pause::Before, true),
+    cue("wait no", Replace, Pause::Before, true),
+    cue("no sorry", Replace, Pause::Before, true),
+    cue("or rather", Replace, Pause::Before, false),
+    cue("make that", Replace, Pause::Before, false),
+    cue("I meant", Replace, Pause::Before, false),
+    cue("I mean", Replace, Pause::Before, false),
+    cue("correction", Replace, Pause::Before, false),
+    cue("sorry", Replace, Pause::Both, false),
+    cue("actually", Replace, Pause::Both, false),
+];
+
+const ES_CUES: &[Cue] = &[
+    cue("olvida eso", Scratch, Pause::Before, true),
+    cue("no espera", Replace, Pause::Before, true),
+    cue("quiero decir", Replace, Pause::Before, false),
+    cue("mejor dicho", Replace, Pause::Before, false),
+    cue("perdón", Replace, Pause::Both, false),
+];
+
+/// Words after a repair that keep the pause before them ("Tuesday, and")
+const CONJUNCTIONS: &[&str] = &["and", "but", "or", "so", "then", "y", "pero", "o"];
+
+/// Hesitations skipped when finding where a revision starts and ends
+const HESITATIONS: &[&str] = &["um", "uh", "uhm", "er", "erm", "ah", "hm", "eh"];
+
+const WEEKDAYS: &[&str] = &[
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+    "today",
+    "tomorrow",
+    "tonight",
+    "yesterday",
+];
+
+const MONTHS: &[&str] = &[
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+const NUMBER_WORDS: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "fifteen", "twenty", "thirty", "forty", "fifty", "hundred", "noon",
+    "midnight",
+];
+
+/// One revision the speaker made
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelfCorrection {
+    pub cue: String,
+    /// The words that were replaced or discarded
+    pub original: String,
+    /// The words that replaced them (empty when discarded)
+    pub corrected: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorrectionOutcome {
+    pub text: String,
+    pub corrections: Vec<SelfCorrection>,
+}
+
+fn cues_for(language: &str) -> &'static [Cue] {
+    match language {
+        "en" => EN_CUES,
+        "es" => ES_CUES,
+        _ => &[],
+    }
+}
+
+/// Apply spoken self-corrections ("Tuesday, no wait, Wednesday")
+pub fn resolve(text: &str, language: &str) -> CorrectionOutcome {
+    let cues = cues_for(language);
+    let tokens = tokens::tokenize(text);
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut corrections = Vec::new();
+    let mut capitalize_next = false;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        if let Some((cue, len)) = match_cue(cues, &tokens, i, out.last()) {
+            let cue_end = i + len;
+            let applied = match cue.kind {
+                Scratch => scratch(&mut out, cue),
+                Replace => replace(&mut out, cue, &tokens, cue_end),
+            };
+
+            if let Some((correction, resume_at, capitalize)) = applied {
+                corrections.push(correction);
+                capitalize_next = capitalize;
+                i = resume_at.unwrap_or(cue_end);
+                continue;
+            }
+        }
+
+        let mut token = tokens[i].clone();
+        if capitalize_next {
+            token.capitalize();
+            capitalize_next = false;
+        }
+        out.push(token);
+        i += 1;
+    }
+
+    CorrectionOutcome {
+        text: tokens::join(&out),
+        corrections,
+    }
+}
+
+/// Find a cue phrase at `tokens[start]` whose pause requirements are met
+fn match_cue<'a>(
+    cues: &'a [Cue],
+    tokens: &[Token],
+    start: usize,
+    prev: Option<&Token>,
+) -> Option<(&'a Cue, usize)> {
+    let pause_before = match prev {
+        Some(prev) => prev.has_pause() || is_hesitation(Some(prev)),
+        None => true,
+    };
+
+    cues.iter().find_map(|cue| {
+        let words: Vec<&str> = cue.phrase.split_whitespace().collect();
+        let end = start + words.len();
+        if end > tokens.len() {
+            return None;
+        }
+
+        let matches =
+            tokens[start..end]
+                .iter()
+                .zip(&words)
+                .enumerate()
+                .all(|(i, (token, word))| {
+                    let inner_ok = i == words.len() - 1 || !token.has_pause();
+                    inner_ok && token.lower() == word.to_lowercase()
+                });
+        if !matches {
+            return None;
+        }
+
+        let last = &tokens[end - 1];
+        let pause_after = last.has_pause() || end == tokens.len();
+        let pauses_ok = match cue.pause {
+            Pause::Before => pause_before,
+            Pause::Both => pause_before && pause_after,
+        };
+        pauses_ok.then_some((cue, words.len()))
+    })
+}
+
+fn is_hesitation(token: Option<&Token>) -> bool {
+    token.is_some_and(|t| HESITATIONS.contains(&t.lower().as_str()))
+}
+
+/// Start of the span a cue may revise: the current sentence, or the previous
+/// one when a cross-sentence cue opens a new sentence
+fn scope_start(out: &[Token], cue: &Cue) -> usize {
+    let sentence_start = |end: usize| {
+        out[..end]
+            .iter()
+            .rposition(Token::ends_sentence)
+            .map_or(0, |p| p + 1)
+    };
+
+    let current = sentence_start(out.len());
+    if current == out.len() && cue.cross_sentence && current > 0 {
+        return sentence_start(current - 1);
+    }
+    current
+}
+
+/// Drop trailing hesitations so "Tuesday, um, no wait" revises "Tuesday"
+fn trim_hesitations(out: &mut Vec<Token>) {
+    while is_hesitation(out.last()) {
+        out.pop();
+    }
+}
+
+type Applied = (SelfCorrection, Option<usize>, bool);
+
+fn scratch(out: &mut Vec<Token>, cue: &Cue) -> Option<Applied> {
+    let mut trimmed = out.clone();
+    trim_hesitations(&mut trimmed);
+    let start = scope_start(&trimmed, cue);
+    if start == trimmed.len() {
+        return None;
+    }
+
+    let original = tokens::join(&trimmed[start..]);
+    trimmed.truncate(start);
+    *out = trimmed;
+
+    // Whatever follows now opens the sentence
+    let capitalize = match out.last() {
+        Some(prev) => prev.ends_sentence(),
+        None => true,
+    };
+
+    Some((
+        SelfCorrection {
+            cue: cue.phrase.to_string(),
+            original: strip_trailing_pause(&original),
+            corrected: String::new(),
+        },
+        None,
+        capitalize,
+    ))
+}
+
+fn replace(out: &mut Vec<Token>, cue: &Cue, tokens: &[Token], cue_end: usize) -> Option<Applied> {
+    // The repair runs from after the cue to the next pause
+    let repair_start = (cue_end..tokens.len()).find(|&j| !is_hesitation(Some(&tokens[j])))?;
+    let repair_end = (repair_start..tokens.len())
+        .find(|&j| tokens[j].has_pause())
+        .map_or(tokens.len(), |j| j + 1);
+    let repair = &tokens[repair_start..repair_end];
+
+    let mut trimmed = out.clone();
+    trim_hesitations(&mut trimmed);
+    let start = scope_start(&trimmed, cue);
+    if start == trimmed.len() {
+        return None;
+    }
+
+    // Without a word to line the repair up with, "It was, actually, quite
+    // good" is prose rather than a revision
+    let reparandum_start = start + align(&trimmed[start..], repair)?;
+    let reparandum = &trimmed[reparandum_start..];
+    let original = tokens::join(reparandum);
+    let capitalize = reparandum[0].starts_uppercase()
+        && (reparandum_start == 0 || trimmed[reparandum_start - 1].ends_sentence());
+    let lead = reparandum[0].lead.clone();
+
+    trimmed.truncate(reparandum_start);
+    *out = trimmed;
+
+    let mut repair = repair.to_vec();
+    if capitalize {
+        repair[0].capitalize();
+    }
+    // The pause that closed the repair goes unless the sentence would have
+    // had it anyway: "five, make that six, boxes" is "six boxes"
+    let keeps_pause = match tokens.get(repair_end) {
+        Some(next) => CONJUNCTIONS.contains(&next.lower().as_str()),
+        None => true,
+    };
+    if let Some(last) = repair.last_mut() {
+        if !keeps_pause && last.trail.chars().all(|c| matches!(c, ',' | ';')) {
+            last.trail.clear();
+        }
+    }
+    repair[0].lead = lead + &repair[0].lead;
+
+    let corrected = tokens::join(&repair);
+    out.extend(repair);
+
+    Some((
+        SelfCorrection {
+            cue: cue.phrase.to_string(),
+            original: strip_trailing_pause(&original),
+            corrected: strip_trailing_pause(&corrected),
+        },
+        Some(repair_end),
+        false,
+    ))
+}
+
+/// Where in `scope` the revised span starts, relative to `scope`
+///
+/// Tries the last occurrence of the repair's first word ("to John, sorry,
+/// to Mary"), then the last word of the same kind (day, month, number,
+/// name); `None` when neither is in the scope.
+fn align(scope: &[Token], repair: &[Token]) -> Option<usize> {
+    let first = repair[0].lower();
+    if let Some(pos) = scope.iter().rposition(|t| t.lower() == first) {
+        return Some(pos);
+    }
+
+    let class = word_class(&repair[0], false)?;
+    scope
+        .iter()
+        .enumerate()
+        .rposition(|(i, t)| word_class(t, i == 0) == Some(class))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WordClass {
+    Day,
+    Month,
+    Number,
+    Name,
+}
+
+fn word_class(token: &Token, sentence_initial: bool) -> Option<WordClass> {
+    let word = token.lower();
+    if WEEKDAYS.contains(&word.as_str()) {
+        Some(WordClass::Day)
+    } else if MONTHS.contains(&word.as_str()) {
+        Some(WordClass::Month)
+    } else if word.chars().next().is_some_and(|c| c.is_ascii_digit())
+        || NUMBER_WORDS.contains(&word.as_str())
+    {
+        Some(WordClass::Number)
+    } else if token.starts_uppercase() && !sentence_initial && word != "i" {
+        Some(WordClass::Name)
+    } else {
+        None
+    }
+}
+
+fn strip_trailing_pause(text: &str) -> String {
+    text.trim_end_matches([',', ';', ':']).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_spoken_corrections() {
+        let cases: &[(&str, &str)] = &[
+            // Day and date revisions
+            ("Send it Tuesday, no wait, Wednesday", "Send it Wednesday"),
+            (
+                "Let's meet on Friday, sorry, Thursday.",
+                "Let's meet on Thursday.",
+            ),
+            (
+                "The deadline is March 3rd, I mean, April 3rd.",
+                "The deadline is April 3rd.",
+            ),
+            ("See you Tuesday. No wait, Wednesday.", "See you Wednesday."),
+            ("See you tomorrow, no wait, today", "See you today"),
+            // Numbers and times
+            ("Meet at 3, sorry, 4 pm", "Meet at 4 pm"),
+            (
+                "I need two apples, I mean, three apples",
+                "I need three apples",
+            ),
+            ("Order five, make that six, boxes", "Order six boxes"),
+            (
+                "It costs 20 dollars, actually, 25 dollars.",
+                "It costs 25 dollars.",
+            ),
+            // Names and repeated words
+            ("Call John, no wait, Mary", "Call Mary"),
+            ("Send it to John, sorry, to Mary.", "Send it to Mary."),
+            (
+                "I'll take the red one, no wait, the blue one",
+                "I'll take the blue one",
+            ),
+            ("Forward it to Alex, or rather, Sam", "Forward it to Sam"),
+            (
+                "Tell Priya, I meant to say, tell Dana the news",
+                "Tell Dana the news",
+            ),
+            // The repair stops at the next pause; the rest is kept
+            (
+                "Book it for Monday, no wait, Tuesday, and invite the team.",
+                "Book it for Tuesday, and invite the team.",
+            ),
+            // Hesitations around the cue
+            (
+                "Send it Tuesday, um, no wait, Wednesday",
+                "Send it Wednesday",
+            ),
+            (
+                "Send it Tuesday, no wait, uh, Wednesday",
+                "Send it Wednesday",
+            ),
+            // Scratching a clause or sentence
+            (
+                "Send the report to Bob. Scratch that. Send it to Alice.",
+                "Send it to Alice.",
+            ),
+            ("I think we should, scratch that, we must go", "We must go"),
+            (
+                "Hello team. This is a draft, let me rephrase, this is final.",
+                "Hello team. This is final.",
+            ),
+            // Not corrections
+            ("I actually like it", "I actually like it"),
+            ("I'm sorry I'm late", "I'm sorry I'm late"),
+            ("What I mean is simple", "What I mean is simple"),
+            ("Make that cake for me", "Make that cake for me"),
+            ("No wait is too long", "No wait is too long"),
+            ("Scratch that itch", "Scratch that itch"),
+            (
+                "send it Tuesday no wait Wednesday",
+                "send it Tuesday no wait Wednesday",
+            ),
+            (
+                "It was, actually, quite good.",
+                "It was, actually, quite good.",
+            ),
+            (
+                "There's no wait time at the restaurant",
+                "There's no wait time at the restaurant",
+            ),
+            (
+                "I need to scratch that off my list",
+                "I need to scratch that off my list",
+            ),
+            ("He said, I mean, it's fine", "He said, I mean, it's fine"),
+            (
+                "Forward it to Alex or rather Sam",
+                "Forward it to Alex or rather Sam",
+            ),
+            (
+                "Correction fluid is on my desk",
+                "Correction fluid is on my desk",
+            ),
+            ("", ""),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(resolve(input, "en").text, *expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn records_each_revision() {
+        let outcome = resolve("Send it Tuesday, no wait, Wednesday", "en");
+        assert_eq!(
+            outcome.corrections,
+            vec![SelfCorrection {
+                cue: "no wait".to_string(),
+                original: "Tuesday".to_string(),
+                corrected: "Wednesday".to_string(),
+            }]
+        );
+
+        let outcome = resolve("Call Sam at noon, sorry, at one. Scratch that.", "en");
+        assert_eq!(outcome.text, "");
+        assert_eq!(outcome.corrections.len(), 2);
+        assert_eq!(outcome.corrections[1].original, "Call Sam at one.");
+        assert_eq!(outcome.corrections[1].corrected, "");
+    }
+
+    #[test]
+    fn leaves_text_alone_without_cues() {
+        let outcome = resolve("Plain sentence with nothing to fix.", "en");
+        assert_eq!(outcome.text, "Plain sentence with nothing to fix.");
+        assert!(outcome.corrections.is_empty());
+    }
+
+    #[test]
+    fn uses_language_specific_cues() {
+        assert_eq!(
+            resolve("Llego el lunes, no espera, el martes", "es").text,
+            "Llego el martes"
+        );
+        // English cues don't fire for other languages
+        assert_eq!(
+            resolve("Llego el lunes, no wait, el martes", "es").text,
+            "Llego el lunes, no wait, el martes"
+        );
+    }
+}
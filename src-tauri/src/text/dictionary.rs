@@ -0,0 +1,286 @@
+use super::common_words;
+use super::similarity;
+use super::tokens::{self, Token};
+use crate::storage::dictionary::{DictionaryReplacement, DictionaryWord};
+use serde::{Deserialize, Serialize};
+
+/// Confidence a match needs before a word is replaced
+pub const DEFAULT_THRESHOLD: f32 = 0.85;
+
+/// Most transcript words one dictionary entry may have been split into
+/// ("cooper netties" for "Kubernetes")
+const MAX_WINDOW: usize = 3;
+
+/// Share of the confidence that comes from sounding alike; the rest is
+/// spelling
+const PHONETIC_WEIGHT: f32 = 0.7;
+
+/// Phonetic keys this short match too many ordinary words ("cat" and
+/// "Kate" are both "KT"), so they also need a close spelling
+const SHORT_KEY_LEN: usize = 2;
+const SHORT_KEY_MIN_SPELLING: f32 = 0.75;
+
+/// Confidence lost for each function word in a multi-word window, so "and
+/// her son" doesn't become "Anderson"
+const STOPWORD_PENALTY: f32 = 0.1;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DictionaryOutcome {
+    pub text: String,
+    pub replacements: Vec<DictionaryReplacement>,
+}
+
+/// A dictionary word with its spellings and phonetic keys precomputed
+struct Entry<'a> {
+    word: &'a str,
+    spellings: Vec<String>,
+    keys: Vec<String>,
+}
+
+impl<'a> Entry<'a> {
+    fn new(word: &'a DictionaryWord) -> Self {
+        let spellings: Vec<String> = std::iter::once(word.word.as_str())
+            .chain(word.phonetic_hint.as_deref())
+            .map(letters)
+            .filter(|s| !s.is_empty())
+            .collect();
+        let keys = spellings.iter().map(|s| phonetic_key(s)).collect();
+        Self {
+            word: &word.word,
+            spellings,
+            keys,
+        }
+    }
+
+    /// Confidence that `spoken` (letters only) was meant to be this word
+    ///
+    /// Without `hints` only the word's own spelling counts, so a hint of
+    /// "win" for "Nguyen" doesn't claim every "win".
+    fn score(&self, spoken: &str, key: &str, hints: bool) -> f32 {
+        let count = if hints { self.spellings.len() } else { 1 };
+        let phonetic = self.keys[..count]
+            .iter()
+            .map(|k| similarity::ratio(key, k))
+            .fold(0.0, f32::max);
+        let spelling = self.spellings[..count]
+            .iter()
+            .map(|s| similarity::ratio(spoken, s))
+            .fold(0.0, f32::max);
+
+        if key.len() <= SHORT_KEY_LEN && spelling < SHORT_KEY_MIN_SPELLING {
+            return 0.0;
+        }
+        PHONETIC_WEIGHT * phonetic + (1.0 - PHONETIC_WEIGHT) * spelling
+    }
+}
+
+/// Metaphone key with voiced consonants folded into their voiceless pair,
+/// which recognizers often confuse ("cooper" for "Kuber")
+fn phonetic_key(spelling: &str) -> String {
+    similarity::metaphone(spelling)
+        .chars()
+        .map(|c| match c {
+            'B' => 'P',
+            'D' => 'T',
+            'V' => 'F',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Lowercase letters and digits only, so "Cooper-Netties" compares as
+/// "coopernetties"
+fn letters(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Replace transcript words that sound like a dictionary entry
+///
+/// Each position takes the best-scoring entry over windows of one to three
+/// words; words already spelled as an entry are kept as they are.
+pub fn correct(text: &str, words: &[DictionaryWord], threshold: f32) -> DictionaryOutcome {
+    let tokens = tokens::tokenize(text);
+    let entries: Vec<Entry> = words
+        .iter()
+        .map(Entry::new)
+        .filter(|e| !e.spellings.is_empty())
+        .collect();
+
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut replacements = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match best_match(&tokens, i, &entries).filter(|m| m.confidence >= threshold) {
+            Some(found) => {
+                let window = &tokens[i..i + found.len];
+                if found.exact {
+                    out.extend_from_slice(window);
+                } else {
+                    replacements.push(DictionaryReplacement {
+                        word: found.word.to_string(),
+                        original: window
+                            .iter()
+                            .map(|t| t.core.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                        confidence: found.confidence,
+                    });
+                    out.push(Token {
+                        lead: window[0].lead.clone(),
+                        core: found.word.to_string(),
+                        trail: window[found.len - 1].trail.clone(),
+                    });
+                }
+                i += found.len;
+            }
+            None => {
+                out.push(tokens[i].clone());
+                i += 1;
+            }
+        }
+    }
+
+    DictionaryOutcome {
+        text: tokens::join(&out),
+        replacements,
+    }
+}
+
+struct Match<'a> {
+    word: &'a str,
+    len: usize,
+    confidence: f32,
+    /// Already written exactly as the entry
+    exact: bool,
+}
+
+fn best_match<'a>(tokens: &[Token], start: usize, entries: &[Entry<'a>]) -> Option<Match<'a>> {
+    let mut best: Option<Match> = None;
+
+    for len in 1..=MAX_WINDOW {
+        let Some(window) = tokens.get(start..start + len) else {
+            break;
+        };
+        let contiguous = window
+            .iter()
+            .enumerate()
+            .all(|(k, t)| (k == 0 || t.lead.is_empty()) && (k == len - 1 || t.trail.is_empty()));
+        if !contiguous {
+            break;
+        }
+
+        let written = window
+            .iter()
+            .map(|t| t.core.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let spoken = letters(&written);
+        if spoken.is_empty() {
+            continue;
+        }
+        let key = phonetic_key(&spoken);
+        let heard: Vec<String> = window.iter().map(Token::lower).collect();
+        // Ordinary words, and words already in the dictionary, were most
+        // likely said as heard
+        let common = heard.iter().all(|w| common_words::is_common(w));
+        let hints = !common && !entries.iter().any(|e| e.spellings[0] == spoken);
+        let stopwords = heard
+            .iter()
+            .filter(|w| common_words::is_stopword(w))
+            .count();
+        let penalty = if len > 1 {
+            STOPWORD_PENALTY * stopwords as f32
+        } else {
+            0.0
+        };
+
+        for entry in entries {
+            let exact = written == entry.word;
+            let confidence = if exact || spoken == entry.spellings[0] {
+                1.0
+            } else if len > 1 && common {
+                0.0
+            } else {
+                entry.score(&spoken, &key, hints) - penalty
+            };
+            // Longer windows win ties so a split word is rejoined whole
+            let better = match &best {
+                Some(b) => confidence > b.confidence || (confidence == b.confidence && !b.exact),
+                None => true,
+            };
+            if better {
+                best = Some(Match {
+                    word: entry.word,
+                    len,
+                    confidence,
+                    exact,
+                });
+            }
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(word: &str, hint: Option<&str>) -> DictionaryWord {
+        DictionaryWord {
+            id: word.to_string(),
+            word: word.to_string(),
+            phonetic_hint: hint.map(str::to_string),
+            category: None,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn corrects_misheard_dictionary_words() {
+        let words = vec![
+            word("Kubernetes", None),
+            word("Anderson", None),
+            word("Nguyen", Some("win")),
+            word("Siobhan", Some("shivawn")),
+        ];
+        let cases: &[(&str, &str)] = &[
+            ("deploy it to cooper netties", "deploy it to Kubernetes"),
+            ("ask shivawn about it", "ask Siobhan about it"),
+            ("ask Siobhan about it", "ask Siobhan about it"),
+            ("call mister andersen", "call mister Anderson"),
+            // Ordinary words stay as heard
+            ("I saw him and her son", "I saw him and her son"),
+            ("we will win the game", "we will win the game"),
+            ("the cooper is here", "the cooper is here"),
+            ("", ""),
+        ];
+
+        for (input, expected) in cases {
+            let outcome = correct(input, &words, DEFAULT_THRESHOLD);
+            assert_eq!(outcome.text, *expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn records_each_replacement() {
+        let words = vec![word("Kubernetes", None)];
+        let outcome = correct("cooper netties, please", &words, DEFAULT_THRESHOLD);
+        assert_eq!(outcome.text, "Kubernetes, please");
+        assert_eq!(outcome.replacements.len(), 1);
+        assert_eq!(outcome.replacements[0].original, "cooper netties");
+        assert_eq!(outcome.replacements[0].word, "Kubernetes");
+        assert!(outcome.replacements[0].confidence >= DEFAULT_THRESHOLD);
+    }
+
+    #[test]
+    fn prefers_the_word_as_heard_when_it_is_an_entry() {
+        let words = vec![word("Cait", Some("kate")), word("Kate", None)];
+        let outcome = correct("tell kate", &words, DEFAULT_THRESHOLD);
+        assert_eq!(outcome.text, "tell Kate");
+    }
+}
@@ -0,0 +1,451 @@
+use super::tokens;
+use serde::{Deserialize, Serialize};
+
+/// The pipeline stage that made an edit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Paragraphs,
+    SelfCorrection,
+    Fillers,
+    Dictionary,
+    Emoji,
+    Normalization,
+    Punctuation,
+    Code,
+    Formatting,
+    Spelling,
+    HouseStyle,
+    Snippets,
+    /// Spelling mode ("spell alpha bravo")
+    SpelledOut,
+    /// LLM post-processing
+    Rewrite,
+    /// Translation into another language
+    Translation,
+    /// Capitalization and spacing to fit the text before the cursor
+    Context,
+}
+
+/// Text as a stage left it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageText {
+    pub stage: Stage,
+    pub text: String,
+}
+
+/// One run of a word-level diff from the transcript to the final text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum DiffOp {
+    /// Words the pipeline left as transcribed
+    Equal { text: String },
+    /// Words one stage removed, added or replaced
+    Change {
+        stage: Stage,
+        removed: String,
+        inserted: String,
+        /// Undone by the user; the removed words are used instead
+        #[serde(default)]
+        reverted: bool,
+    },
+}
+
+/// Add a stage's output to `stages` if it changed the text
+///
+/// `input` is the text before the first recorded stage.
+pub fn record(stages: &mut Vec<StageText>, input: &str, stage: Stage, text: &str) {
+    let before = stages.last().map_or(input, |s| s.text.as_str());
+    if before != text {
+        stages.push(StageText {
+            stage,
+            text: text.to_string(),
+        });
+    }
+}
+
+/// Where a word in the current text came from
+#[derive(Debug, Clone, Copy)]
+enum Origin {
+    /// The transcript word at this index
+    Original(usize),
+    /// The transcript word at this index, last edited in place by a stage
+    Edited(usize, Stage),
+    Added(Stage),
+}
+
+/// Diff the transcript against the last stage's text, crediting each
+/// change to the stage that made it
+///
+/// Words are followed through every stage in turn, so "um" dropped by
+/// filler removal and a sentence reworded by the LLM come out as separate
+/// changes even though both are in the final text. A word one stage adds
+/// and a later stage removes leaves no trace. Punctuation and case are part
+/// of the word, so "hello" becoming "Hello," is a change; a word edited by
+/// several stages is credited to the last.
+pub fn compute(original: &str, stages: &[StageText]) -> Vec<DiffOp> {
+    let original_words = words(original);
+    let mut current: Vec<(String, Origin)> = original_words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| (word.clone(), Origin::Original(i)))
+        .collect();
+    let mut removed_by: Vec<Option<Stage>> = vec![None; original_words.len()];
+
+    for step in stages {
+        let next = words(&step.text);
+        let old: Vec<&str> = current.iter().map(|(w, _)| w.as_str()).collect();
+        let new: Vec<&str> = next.iter().map(String::as_str).collect();
+        let matched = align(&old, &new);
+
+        let mut kept = vec![false; current.len()];
+        let mut origins: Vec<Origin> = vec![Origin::Added(step.stage); next.len()];
+        let (mut i, mut j) = (0, 0);
+        for (mi, mj) in matched.into_iter().chain([(current.len(), next.len())]) {
+            // As many words in as out between two matches is the same words
+            // edited, such as "so" capitalized to "So"
+            if mi - i == mj - j {
+                for (old, new) in (i..mi).zip(j..mj) {
+                    if let Origin::Original(k) | Origin::Edited(k, _) = current[old].1 {
+                        kept[old] = true;
+                        origins[new] = Origin::Edited(k, step.stage);
+                    }
+                }
+            }
+            if mi < current.len() {
+                kept[mi] = true;
+                origins[mj] = current[mi].1;
+            }
+            (i, j) = (mi + 1, mj + 1);
+        }
+        for (i, (_, origin)) in current.iter().enumerate() {
+            if let (false, Origin::Original(k) | Origin::Edited(k, _)) = (kept[i], origin) {
+                removed_by[*k] = Some(step.stage);
+            }
+        }
+        current = next.into_iter().zip(origins).collect();
+    }
+
+    let mut ops = Vec::new();
+    let mut equal: Vec<String> = Vec::new();
+    let mut pending: Vec<Pending> = Vec::new();
+    let mut next_original = 0;
+    for (word, origin) in current {
+        let k = match origin {
+            Origin::Original(k) | Origin::Edited(k, _) => k,
+            Origin::Added(stage) => {
+                pending_insertion(&mut pending, stage).inserted.push(word);
+                continue;
+            }
+        };
+        for skipped in next_original..k {
+            pending_removal(&mut pending, removed_by[skipped], skipped)
+                .removed
+                .push(original_words[skipped].clone());
+        }
+        next_original = k + 1;
+        if let Origin::Edited(_, stage) = origin {
+            let change = pending_removal(&mut pending, Some(stage), k);
+            change.removed.push(original_words[k].clone());
+            change.inserted.push(word);
+        } else {
+            flush(&mut ops, &mut equal, &mut pending, k);
+            equal.push(word);
+        }
+    }
+    for skipped in next_original..original_words.len() {
+        pending_removal(&mut pending, removed_by[skipped], skipped)
+            .removed
+            .push(original_words[skipped].clone());
+    }
+    flush(&mut ops, &mut equal, &mut pending, original_words.len());
+    if !equal.is_empty() {
+        ops.push(DiffOp::Equal { text: join(&equal) });
+    }
+    ops
+}
+
+/// A change between two unchanged runs, being collected
+struct Pending {
+    stage: Stage,
+    /// First and last transcript words the change removes or edits
+    first: Option<usize>,
+    last: Option<usize>,
+    removed: Vec<String>,
+    inserted: Vec<String>,
+}
+
+impl Pending {
+    fn new(stage: Stage) -> Self {
+        Pending {
+            stage,
+            first: None,
+            last: None,
+            removed: Vec::new(),
+            inserted: Vec::new(),
+        }
+    }
+}
+
+/// The pending change for `stage` taking out transcript word `k`: the
+/// stage's change ending just before it, or a new one
+fn pending_removal(pending: &mut Vec<Pending>, stage: Option<Stage>, k: usize) -> &mut Pending {
+    // Every transcript word missing from the final text was removed by some
+    // stage; `Context` is only a fallback
+    let stage = stage.unwrap_or(Stage::Context);
+    let index = pending
+        .iter()
+        .rposition(|p| p.stage == stage && (p.last.is_none() || p.last.map(|l| l + 1) == Some(k)));
+    let index = match index {
+        Some(index) => index,
+        None => {
+            pending.push(Pending::new(stage));
+            pending.len() - 1
+        }
+    };
+    let change = &mut pending[index];
+    change.first = change.first.or(Some(k));
+    change.last = Some(k);
+    change
+}
+
+/// The pending change for words `stage` added, which go with the words it
+/// took out nearby
+fn pending_insertion(pending: &mut Vec<Pending>, stage: Stage) -> &mut Pending {
+    let index = match pending.iter().rposition(|p| p.stage == stage) {
+        Some(index) => index,
+        None => {
+            pending.push(Pending::new(stage));
+            pending.len() - 1
+        }
+    };
+    &mut pending[index]
+}
+
+/// Emit the unchanged run and the changes after it, in transcript order
+///
+/// `next` is the transcript word after the changes, where pure insertions
+/// sit. Keeping the order means reverting one stage puts its words back
+/// where they were: "um" before "I", not after it.
+fn flush(ops: &mut Vec<DiffOp>, equal: &mut Vec<String>, pending: &mut Vec<Pending>, next: usize) {
+    if pending.is_empty() {
+        return;
+    }
+    if !equal.is_empty() {
+        ops.push(DiffOp::Equal { text: join(equal) });
+        equal.clear();
+    }
+    pending.sort_by_key(|change| change.first.unwrap_or(next));
+    for change in pending.drain(..) {
+        ops.push(DiffOp::Change {
+            stage: change.stage,
+            removed: join(&change.removed),
+            inserted: join(&change.inserted),
+            reverted: false,
+        });
+    }
+}
+
+/// The text a diff produces, with reverted changes undone
+pub fn render(ops: &[DiffOp]) -> String {
+    let pieces = ops.iter().map(|op| match op {
+        DiffOp::Equal { text } => text.as_str(),
+        DiffOp::Change {
+            removed,
+            reverted: true,
+            ..
+        } => removed.as_str(),
+        DiffOp::Change { inserted, .. } => inserted.as_str(),
+    });
+    let pieces: Vec<String> = pieces
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect();
+    join(&pieces)
+}
+
+/// Mark every change made by one of `stages` as reverted
+pub fn revert(ops: &mut [DiffOp], stages: &[Stage]) {
+    for op in ops {
+        if let DiffOp::Change {
+            stage, reverted, ..
+        } = op
+        {
+            if stages.contains(stage) {
+                *reverted = true;
+            }
+        }
+    }
+}
+
+fn words(text: &str) -> Vec<String> {
+    tokens::tokenize(text)
+        .iter()
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_string())
+        .collect()
+}
+
+/// Join words with spaces, or with the line breaks they start with
+fn join(words: &[String]) -> String {
+    let mut out = String::new();
+    for word in words {
+        if !out.is_empty() && !word.starts_with('\n') {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    out
+}
+
+/// Index pairs of a longest common subsequence of `old` and `new`
+fn align(old: &[&str], new: &[&str]) -> Vec<(usize, usize)> {
+    // Shared ends are matched directly, which keeps the table small when a
+    // stage only touches a few words
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let width = b.len() + 1;
+    let mut lengths = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i * width + j] = if a[i] == b[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut pairs: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            pairs.push((prefix + i, prefix + j));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs.extend((0..suffix).map(|k| (old.len() - suffix + k, new.len() - suffix + k)));
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stage outputs in pipeline order, as `record` would keep them
+    fn stages(steps: &[(Stage, &str)]) -> Vec<StageText> {
+        steps
+            .iter()
+            .map(|(stage, text)| StageText {
+                stage: *stage,
+                text: text.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn records_only_stages_that_change_the_text() {
+        let mut recorded = Vec::new();
+        record(&mut recorded, "hello", Stage::Fillers, "hello");
+        record(&mut recorded, "hello", Stage::Punctuation, "Hello.");
+        record(&mut recorded, "hello", Stage::Rewrite, "Hello.");
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].stage, Stage::Punctuation);
+    }
+
+    #[test]
+    fn credits_each_change_to_its_stage() {
+        let steps = stages(&[
+            (Stage::Fillers, "so i went to the store"),
+            (Stage::Punctuation, "So i went to the store."),
+        ]);
+        let ops = compute("um so i went to the store", &steps);
+        assert_eq!(
+            ops,
+            vec![
+                DiffOp::Change {
+                    stage: Stage::Fillers,
+                    removed: "um".to_string(),
+                    inserted: String::new(),
+                    reverted: false,
+                },
+                DiffOp::Change {
+                    stage: Stage::Punctuation,
+                    removed: "so".to_string(),
+                    inserted: "So".to_string(),
+                    reverted: false,
+                },
+                DiffOp::Equal {
+                    text: "i went to the".to_string(),
+                },
+                DiffOp::Change {
+                    stage: Stage::Punctuation,
+                    removed: "store".to_string(),
+                    inserted: "store.".to_string(),
+                    reverted: false,
+                },
+            ]
+        );
+        assert_eq!(render(&ops), "So i went to the store.");
+    }
+
+    #[test]
+    fn reverts_stages_in_place() {
+        let original = "um so i went to the store and uh bought milk";
+        let steps = stages(&[
+            (Stage::Fillers, "so i went to the store and bought milk"),
+            (Stage::Rewrite, "I went to the shop and bought milk"),
+        ]);
+        let cases: &[(&[Stage], &str)] = &[
+            (&[], "I went to the shop and bought milk"),
+            (
+                &[Stage::Fillers],
+                "um I went to the shop and uh bought milk",
+            ),
+            (&[Stage::Rewrite], "so i went to the store and bought milk"),
+            (&[Stage::Fillers, Stage::Rewrite], original),
+        ];
+        for (reverted, expected) in cases {
+            let mut ops = compute(original, &steps);
+            revert(&mut ops, reverted);
+            assert_eq!(render(&ops), *expected, "reverted: {:?}", reverted);
+        }
+    }
+
+    #[test]
+    fn drops_words_added_and_removed_later() {
+        let steps = stages(&[
+            (Stage::Snippets, "see you soon best Sam"),
+            (Stage::Rewrite, "see you soon"),
+        ]);
+        assert_eq!(
+            compute("see you soon", &steps),
+            vec![DiffOp::Equal {
+                text: "see you soon".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn aligns_a_longest_common_subsequence() {
+        let old = ["a", "b", "c", "d", "e"];
+        let new = ["a", "x", "c", "e", "f"];
+        assert_eq!(align(&old, &new), vec![(0, 0), (2, 2), (4, 3)]);
+        assert_eq!(align(&[], &new), vec![]);
+    }
+}
@@ -0,0 +1,387 @@
+use super::tokens::{self, Token};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+/// Common emoji with the ways people say them
+///
+/// Names are the Unicode CLDR English short names with hyphens read as
+/// spaces ("upside-down face" is said "upside down face"). Every other
+/// emoji is found by its CLDR name or shortcode through `emojis`.
+const EMOJI: &[(&str, &str, &[&str])] = &[
+    ("grinning face", "😀", &["grin", "grinning", "big smile"]),
+    ("grinning face with big eyes", "😃", &[]),
+    ("grinning face with smiling eyes", "😄", &["happy face"]),
+    ("beaming face with smiling eyes", "😁", &["beaming face"]),
+    (
+        "grinning squinting face",
+        "😆",
+        &["laughing", "laughing face"],
+    ),
+    (
+        "grinning face with sweat",
+        "😅",
+        &["sweat smile", "nervous laugh"],
+    ),
+    ("rolling on the floor laughing", "🤣", &["rofl"]),
+    (
+        "face with tears of joy",
+        "😂",
+        &[
+            "tears of joy",
+            "crying laughing",
+            "laughing crying",
+            "lol",
+            "joy",
+        ],
+    ),
+    ("slightly smiling face", "🙂", &["smile", "slight smile"]),
+    ("upside down face", "🙃", &["upside down"]),
+    ("melting face", "🫠", &["melting"]),
+    ("winking face", "😉", &["wink", "winky face", "winking"]),
+    (
+        "smiling face with smiling eyes",
+        "😊",
+        &["smiley", "smiley face", "smiling face", "blush"],
+    ),
+    ("smiling face with halo", "😇", &["halo", "angel face"]),
+    ("smiling face with hearts", "🥰", &["in love"]),
+    ("smiling face with heart eyes", "😍", &["heart eyes"]),
+    ("star struck", "🤩", &["starstruck"]),
+    (
+        "face blowing a kiss",
+        "😘",
+        &["kiss", "kissy face", "blowing a kiss"],
+    ),
+    ("smiling face with tear", "🥲", &["happy tears"]),
+    ("face savoring food", "😋", &["yum", "yummy"]),
+    ("face with tongue", "😛", &["tongue out", "tongue"]),
+    ("winking face with tongue", "😜", &[]),
+    ("zany face", "🤪", &["crazy face", "goofy face"]),
+    (
+        "smiling face with open hands",
+        "🤗",
+        &["hug", "hugging face", "hugs"],
+    ),
+    ("face with hand over mouth", "🤭", &["giggle", "oops"]),
+    ("shushing face", "🤫", &["shush", "shh"]),
+    ("thinking face", "🤔", &["thinking", "hmm"]),
+    ("saluting face", "🫡", &["salute"]),
+    ("zipper mouth face", "🤐", &["zipper mouth", "lips sealed"]),
+    (
+        "face with raised eyebrow",
+        "🤨",
+        &["raised eyebrow", "skeptical face"],
+    ),
+    ("neutral face", "😐", &["meh"]),
+    ("expressionless face", "😑", &["expressionless"]),
+    ("face without mouth", "😶", &["speechless"]),
+    ("smirking face", "😏", &["smirk"]),
+    ("unamused face", "😒", &["unamused"]),
+    (
+        "face with rolling eyes",
+        "🙄",
+        &["eye roll", "rolling eyes"],
+    ),
+    ("grimacing face", "😬", &["grimace", "yikes"]),
+    ("relieved face", "😌", &["relieved"]),
+    ("pensive face", "😔", &["pensive"]),
+    ("sleepy face", "😪", &["sleepy"]),
+    ("sleeping face", "😴", &["sleeping", "zzz face"]),
+    ("face with medical mask", "😷", &["mask", "sick face"]),
+    ("face with thermometer", "🤒", &["fever"]),
+    ("nauseated face", "🤢", &["nauseated", "gross"]),
+    ("face vomiting", "🤮", &["vomiting", "puke"]),
+    ("hot face", "🥵", &["sweating"]),
+    ("cold face", "🥶", &["freezing"]),
+    ("face with crossed out eyes", "😵", &["dizzy face"]),
+    ("exploding head", "🤯", &["mind blown"]),
+    ("partying face", "🥳", &["party face"]),
+    (
+        "smiling face with sunglasses",
+        "😎",
+        &["sunglasses", "cool", "cool face"],
+    ),
+    ("nerd face", "🤓", &["nerd"]),
+    ("confused face", "😕", &["confused"]),
+    ("worried face", "😟", &["worried"]),
+    (
+        "slightly frowning face",
+        "🙁",
+        &["sad face", "frown", "frowny face"],
+    ),
+    ("face with open mouth", "😮", &["surprised face", "wow"]),
+    ("astonished face", "😲", &["astonished", "shocked face"]),
+    ("flushed face", "😳", &["flushed", "embarrassed"]),
+    ("pleading face", "🥺", &["puppy eyes", "pleading"]),
+    ("crying face", "😢", &["crying", "sad", "tear"]),
+    ("loudly crying face", "😭", &["sobbing", "sob"]),
+    (
+        "face screaming in fear",
+        "😱",
+        &["scream", "screaming face"],
+    ),
+    ("disappointed face", "😞", &["disappointed"]),
+    ("downcast face with sweat", "😓", &[]),
+    ("weary face", "😩", &["weary"]),
+    ("tired face", "😫", &["tired"]),
+    ("yawning face", "🥱", &["yawn", "bored"]),
+    ("face with steam from nose", "😤", &["huffing", "triumph"]),
+    ("enraged face", "😡", &["pouting face", "furious", "rage"]),
+    ("angry face", "😠", &["angry", "mad face"]),
+    ("face with symbols on mouth", "🤬", &["cursing", "swearing"]),
+    ("smiling face with horns", "😈", &["devil", "smiling devil"]),
+    ("skull", "💀", &["dead", "skull face"]),
+    ("pile of poo", "💩", &["poop", "poo"]),
+    ("clown face", "🤡", &["clown"]),
+    ("ghost", "👻", &[]),
+    ("alien", "👽", &[]),
+    ("robot", "🤖", &["robot face"]),
+    ("see no evil monkey", "🙈", &["see no evil"]),
+    ("hear no evil monkey", "🙉", &["hear no evil"]),
+    ("speak no evil monkey", "🙊", &["speak no evil"]),
+    ("hundred points", "💯", &["hundred", "one hundred", "100"]),
+    ("collision", "💥", &["boom", "explosion"]),
+    ("sweat droplets", "💦", &["sweat drops"]),
+    ("red heart", "❤️", &["heart", "love"]),
+    ("orange heart", "🧡", &[]),
+    ("yellow heart", "💛", &[]),
+    ("green heart", "💚", &[]),
+    ("blue heart", "💙", &[]),
+    ("purple heart", "💜", &[]),
+    ("black heart", "🖤", &[]),
+    ("broken heart", "💔", &["heartbreak"]),
+    ("two hearts", "💕", &["hearts"]),
+    ("waving hand", "👋", &["wave", "waving"]),
+    ("raised hand", "✋", &["high five", "hand"]),
+    ("ok hand", "👌", &["ok", "okay", "okay hand", "perfect"]),
+    ("pinched fingers", "🤌", &["chef's kiss", "chefs kiss"]),
+    ("victory hand", "✌️", &["peace", "peace sign", "victory"]),
+    ("crossed fingers", "🤞", &["fingers crossed"]),
+    ("sign of the horns", "🤘", &["rock on", "horns"]),
+    (
+        "backhand index pointing left",
+        "👈",
+        &["point left", "pointing left"],
+    ),
+    (
+        "backhand index pointing right",
+        "👉",
+        &["point right", "pointing right"],
+    ),
+    (
+        "backhand index pointing up",
+        "👆",
+        &["point up", "pointing up"],
+    ),
+    (
+        "backhand index pointing down",
+        "👇",
+        &["point down", "pointing down"],
+    ),
+    (
+        "thumbs up",
+        "👍",
+        &["thumb up", "thumbs up sign", "like", "plus one"],
+    ),
+    ("thumbs down", "👎", &["thumb down", "dislike", "minus one"]),
+    ("raised fist", "✊", &["fist"]),
+    ("oncoming fist", "👊", &["fist bump", "punch"]),
+    ("clapping hands", "👏", &["clap", "clapping", "applause"]),
+    ("raising hands", "🙌", &["raised hands", "hooray", "praise"]),
+    ("handshake", "🤝", &["deal"]),
+    (
+        "folded hands",
+        "🙏",
+        &["pray", "praying hands", "please", "thank you", "thanks"],
+    ),
+    ("flexed biceps", "💪", &["muscle", "flex", "strong"]),
+    ("brain", "🧠", &[]),
+    ("eyes", "👀", &["eye", "looking"]),
+    ("person shrugging", "🤷", &["shrug", "shrugging"]),
+    ("person facepalming", "🤦", &["facepalm", "face palm"]),
+    ("person raising hand", "🙋", &["raising hand"]),
+    (
+        "party popper",
+        "🎉",
+        &["party", "tada", "celebration", "confetti"],
+    ),
+    ("confetti ball", "🎊", &[]),
+    ("birthday cake", "🎂", &["cake", "birthday"]),
+    ("wrapped gift", "🎁", &["gift", "present"]),
+    ("trophy", "🏆", &["winner"]),
+    ("fire", "🔥", &["lit", "flame"]),
+    ("star", "⭐", &[]),
+    ("sparkles", "✨", &["sparkle"]),
+    ("high voltage", "⚡", &["lightning", "zap"]),
+    ("sun", "☀️", &["sunny"]),
+    ("rainbow", "🌈", &[]),
+    ("hot beverage", "☕", &["coffee", "tea"]),
+    ("pizza", "🍕", &[]),
+    ("taco", "🌮", &[]),
+    ("doughnut", "🍩", &["donut"]),
+    ("beer mug", "🍺", &["beer"]),
+    ("clinking beer mugs", "🍻", &["cheers", "beers"]),
+    ("clinking glasses", "🥂", &["toast", "champagne"]),
+    ("wine glass", "🍷", &["wine"]),
+    ("rocket", "🚀", &["ship it"]),
+    (
+        "check mark button",
+        "✅",
+        &["check mark", "checkmark", "check", "tick", "done"],
+    ),
+    ("cross mark", "❌", &["x", "cross"]),
+    ("red question mark", "❓", &["question mark", "question"]),
+    (
+        "red exclamation mark",
+        "❗",
+        &["exclamation mark", "exclamation"],
+    ),
+    ("warning", "⚠️", &["caution"]),
+    ("police car light", "🚨", &["siren", "alert"]),
+    ("light bulb", "💡", &["idea", "lightbulb"]),
+    ("pushpin", "📌", &["pin"]),
+    ("calendar", "📅", &[]),
+    ("alarm clock", "⏰", &["alarm"]),
+    ("crown", "👑", &[]),
+    ("money bag", "💰", &["money"]),
+    ("bug", "🐛", &[]),
+    ("dog face", "🐶", &["dog", "puppy"]),
+    ("cat face", "🐱", &["cat", "kitty"]),
+    ("unicorn", "🦄", &[]),
+];
+
+/// Word after a name that asks for the emoji ("thumbs up emoji")
+const EMOJI_WORDS: &[&str] = &["emoji", "emojis", "emoticon"];
+
+/// Names that only ever mean the emoji, so they don't need "emoji" after
+/// them
+const STANDALONE: &[&str] = &["smiley face", "winky face", "kissy face"];
+
+/// Longest name looked up, in words ("woman with white cane facing right")
+const MAX_NAME_WORDS: usize = 8;
+
+/// Spoken form of every name, synonym and shortcode, with the emoji
+static NAMES: Lazy<HashMap<String, &'static str>> = Lazy::new(|| {
+    let mut names = HashMap::new();
+    for emoji in emojis::iter() {
+        let spoken = std::iter::once(emoji.name()).chain(emoji.shortcodes());
+        for name in spoken {
+            names.entry(spoken_form(name)).or_insert(emoji.as_str());
+        }
+    }
+    // The curated names win where a shortcode says otherwise
+    for (name, emoji, synonyms) in EMOJI {
+        for name in std::iter::once(name).chain(synonyms.iter()) {
+            names.insert(spoken_form(name), *emoji);
+        }
+    }
+    names
+});
+
+/// Replace spoken emoji names with the emoji
+///
+/// A name needs "emoji" after it ("thumbs up emoji", "sad face emoji"),
+/// except the few that can't mean anything else ("smiley face"). Unknown
+/// names are left as spoken.
+pub fn resolve(text: &str) -> String {
+    let tokens = tokens::tokenize(text);
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match emoji_at(&tokens, i) {
+            Some((len, emoji)) => {
+                out.push(Token {
+                    lead: tokens[i].lead.clone(),
+                    core: emoji.to_string(),
+                    trail: tokens[i + len - 1].trail.clone(),
+                });
+                i += len;
+            }
+            None => {
+                out.push(tokens[i].clone());
+                i += 1;
+            }
+        }
+    }
+
+    tokens::join(&out)
+}
+
+/// The emoji named at `tokens[start]` and how many words the name took,
+/// preferring the longest name
+fn emoji_at(tokens: &[Token], start: usize) -> Option<(usize, &'static str)> {
+    (1..=MAX_NAME_WORDS).rev().find_map(|len| {
+        let window = tokens.get(start..start + len)?;
+        // A name can't run across punctuation or a line break
+        let joined = window[..len - 1].iter().all(|t| t.trail.is_empty())
+            && window[1..].iter().all(|t| t.lead.is_empty());
+        if !joined {
+            return None;
+        }
+
+        let phrase = spoken_form(&tokens::join(window));
+        let emoji = *NAMES.get(&phrase)?;
+
+        let last = &window[len - 1];
+        let asked = last.trail.is_empty()
+            && tokens
+                .get(start + len)
+                .is_some_and(|t| t.lead.is_empty() && EMOJI_WORDS.contains(&t.lower().as_str()));
+        if asked {
+            Some((len + 1, emoji))
+        } else if STANDALONE.contains(&phrase.as_str()) {
+            Some((len, emoji))
+        } else {
+            None
+        }
+    })
+}
+
+/// A name as it's said: lowercase words without punctuation, so "flag:
+/// Japan", "thumbs_up" and "woman’s hat" match "flag japan", "thumbs up"
+/// and "woman's hat"
+fn spoken_form(name: &str) -> String {
+    name.to_lowercase()
+        .replace('’', "'")
+        .replace(['-', '_'], " ")
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\''))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inserts_named_emoji() {
+        let cases: &[(&str, &str)] = &[
+            ("thumbs up emoji", "👍"),
+            ("great job, fire emoji!", "great job, 🔥!"),
+            ("see you soon smiley face", "see you soon 😊"),
+            ("that's a sad face emoji", "that's a 🙁"),
+            ("pineapple emoji", "🍍"),
+            ("flag Japan emoji", "🇯🇵"),
+            ("thumbs-up emoji", "👍"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(resolve(input), *expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn leaves_names_without_emoji_alone() {
+        let cases: &[&str] = &[
+            "don't make a sad face",
+            "my cat face is on the card",
+            "the fire is out",
+            "thumbs up from the team",
+        ];
+        for input in cases {
+            assert_eq!(resolve(input), *input, "input: {:?}", input);
+        }
+    }
+}
@@ -0,0 +1,449 @@
+use super::tokens::{self, Token};
+use crate::storage::database::{self, DatabaseError};
+use serde::{Deserialize, Serialize};
+
+/// When a filler phrase may be dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FillerRule {
+    /// Pure hesitation sounds; always removed
+    Always,
+    /// Only when set off by pauses on both sides ("I was, like, tired"),
+    /// so "I like pizza" keeps its verb
+    Delimited,
+    /// At the start of a sentence, or when delimited
+    SentenceStart,
+}
+
+impl FillerRule {
+    fn as_str(&self) -> &'static str {
+        match self {
+            FillerRule::Always => "always",
+            FillerRule::Delimited => "delimited",
+            FillerRule::SentenceStart => "sentence_start",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "always" => FillerRule::Always,
+            "sentence_start" => FillerRule::SentenceStart,
+            _ => FillerRule::Delimited,
+        }
+    }
+}
+
+use FillerRule::{Always, Delimited, SentenceStart};
+
+const EN_FILLERS: &[(&str, FillerRule)] = &[
+    ("um", Always),
+    ("uh", Always),
+    ("uhm", Always),
+    ("er", Always),
+    ("erm", Always),
+    ("ah", Always),
+    ("hm", Always),
+    ("like", Delimited),
+    ("you know", Delimited),
+    ("I mean", Delimited),
+    ("sort of", Delimited),
+    ("kind of", Delimited),
+    ("basically", SentenceStart),
+    ("actually", SentenceStart),
+    ("literally", Delimited),
+    ("right", Delimited),
+    ("so yeah", Delimited),
+];
+
+const ES_FILLERS: &[(&str, FillerRule)] = &[
+    ("eh", Always),
+    ("em", Always),
+    ("este", Delimited),
+    ("o sea", Delimited),
+    ("pues", SentenceStart),
+    ("bueno", SentenceStart),
+    ("tipo", Delimited),
+    ("en plan", Delimited),
+];
+
+const FR_FILLERS: &[(&str, FillerRule)] = &[
+    ("euh", Always),
+    ("heu", Always),
+    ("bah", Always),
+    ("ben", SentenceStart),
+    ("genre", Delimited),
+    ("en fait", Delimited),
+    ("tu vois", Delimited),
+    ("quoi", Delimited),
+];
+
+const DE_FILLERS: &[(&str, FillerRule)] = &[
+    ("äh", Always),
+    ("ähm", Always),
+    ("öhm", Always),
+    ("halt", Delimited),
+    ("also", Delimited),
+    ("sozusagen", Delimited),
+    ("quasi", Delimited),
+];
+
+/// Words that are often legitimately doubled ("she had had enough",
+/// "very very good") and so aren't treated as stutters
+const DOUBLES_ALLOWED: &[&str] = &[
+    "had", "that", "very", "really", "so", "no", "bye", "yeah", "ha", "now", "well", "many",
+    "much", "far", "long",
+];
+
+/// Words that open a sentence with a comma of their own, kept when a
+/// filler after them is dropped
+const INTRODUCTIONS: &[&str] = &[
+    "okay", "ok", "alright", "well", "so", "yes", "yeah", "no", "oh", "anyway", "sure", "now",
+    "hey", "hi", "hello", "bueno", "vale", "pues", "bon", "alors", "oui", "non", "ja", "nein",
+    "gut",
+];
+
+/// A filler phrase as shown to and edited by the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillerWord {
+    pub language: String,
+    pub phrase: String,
+    pub rule: FillerRule,
+    pub enabled: bool,
+    /// Ships with REDE rather than added by the user
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+/// Built-in filler phrases for a language
+pub fn builtin(language: &str) -> Vec<FillerWord> {
+    let phrases = match language {
+        "en" => EN_FILLERS,
+        "es" => ES_FILLERS,
+        "fr" => FR_FILLERS,
+        "de" => DE_FILLERS,
+        _ => &[],
+    };
+    phrases
+        .iter()
+        .map(|(phrase, rule)| FillerWord {
+            language: language.to_string(),
+            phrase: phrase.to_string(),
+            rule: *rule,
+            enabled: true,
+            builtin: true,
+        })
+        .collect()
+}
+
+/// The effective filler list for a language: built-ins merged with user edits
+pub fn list(language: &str) -> Result<Vec<FillerWord>, DatabaseError> {
+    let mut words = builtin(language);
+
+    let conn = database::connect()?;
+    let mut stmt = conn
+        .prepare("SELECT phrase, rule, enabled FROM filler_words WHERE language = ?1")
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    let overrides = stmt
+        .query_map([language], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, bool>(2)?,
+            ))
+        })
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    for (phrase, rule, enabled) in overrides {
+        let rule = FillerRule::parse(&rule);
+        match words
+            .iter_mut()
+            .find(|w| w.phrase.eq_ignore_ascii_case(&phrase))
+        {
+            Some(word) => {
+                word.rule = rule;
+                word.enabled = enabled;
+            }
+            None => words.push(FillerWord {
+                language: language.to_string(),
+                phrase,
+                rule,
+                enabled,
+                builtin: false,
+            }),
+        }
+    }
+
+    Ok(words)
+}
+
+/// Add or update a filler phrase; disabling a built-in hides it
+pub fn save(word: &FillerWord) -> Result<(), DatabaseError> {
+    let conn = database::connect()?;
+    conn.execute(
+        "INSERT INTO filler_words (id, language, phrase, rule, enabled)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(language, phrase) DO UPDATE SET
+            rule = excluded.rule,
+            enabled = excluded.enabled",
+        rusqlite::params![
+            uuid::Uuid::new_v4().to_string(),
+            word.language,
+            word.phrase.trim().to_lowercase(),
+            word.rule.as_str(),
+            word.enabled,
+        ],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(())
+}
+
+/// Drop a user edit, restoring the built-in behaviour for that phrase
+pub fn delete(language: &str, phrase: &str) -> Result<(), DatabaseError> {
+    let conn = database::connect()?;
+    conn.execute(
+        "DELETE FROM filler_words WHERE language = ?1 AND phrase = ?2",
+        [language, &phrase.trim().to_lowercase()],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(())
+}
+
+/// Compiled filler phrases, longest first so "you know" wins over "you"
+#[derive(Debug, Clone, Default)]
+pub struct FillerList {
+    phrases: Vec<(Vec<String>, FillerRule)>,
+}
+
+impl FillerList {
+    pub fn new(words: &[FillerWord]) -> Self {
+        let mut phrases: Vec<(Vec<String>, FillerRule)> = words
+            .iter()
+            .filter(|w| w.enabled)
+            .filter_map(|w| {
+                let parts: Vec<String> = w
+                    .phrase
+                    .split_whitespace()
+                    .map(|p| squeeze(&p.to_lowercase()))
+                    .collect();
+                (!parts.is_empty()).then_some((parts, w.rule))
+            })
+            .collect();
+        phrases.sort_by_key(|(parts, _)| std::cmp::Reverse(parts.len()));
+        Self { phrases }
+    }
+
+    /// Load the effective list for a language
+    pub fn load(language: &str) -> Result<Self, DatabaseError> {
+        Ok(Self::new(&list(language)?))
+    }
+
+    /// Length and rule of the filler phrase starting at `tokens[start]`, if any
+    fn match_at(&self, tokens: &[Token], start: usize) -> Option<(usize, FillerRule)> {
+        self.phrases.iter().find_map(|(parts, rule)| {
+            let end = start + parts.len();
+            if end > tokens.len() {
+                return None;
+            }
+            let window = &tokens[start..end];
+            let matches = window
+                .iter()
+                .zip(parts)
+                .enumerate()
+                .all(|(i, (token, part))| {
+                    // A phrase can't span punctuation ("you, know")
+                    let inner_ok = (i == 0 || token.lead.is_empty())
+                        && (i == parts.len() - 1 || token.trail.is_empty());
+                    inner_ok && squeeze(&token.lower()) == *part
+                });
+            matches.then_some((parts.len(), *rule))
+        })
+    }
+}
+
+/// Collapse runs of the same letter so "ummm" and "uhhh" match "um" and "uh"
+fn squeeze(word: &str) -> String {
+    let mut out = String::with_capacity(word.len());
+    let mut last = None;
+    for c in word.chars() {
+        if last != Some(c) {
+            out.push(c);
+        }
+        last = Some(c);
+    }
+    out
+}
+
+/// Remove filler phrases and collapse stutters
+pub fn remove(text: &str, fillers: &FillerList) -> String {
+    let tokens = tokens::tokenize(text);
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut capitalize_next = false;
+    let mut carried_lead = String::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        if let Some((len, rule)) = fillers.match_at(&tokens, i) {
+            let sentence_start = tokens[i].lead.contains('\n')
+                || match out.last() {
+                    Some(prev) => prev.ends_sentence(),
+                    None => true,
+                };
+            let pause_before = sentence_start || out.last().is_some_and(Token::has_pause);
+            let last = &tokens[i + len - 1];
+            let pause_after = i + len == tokens.len() || last.has_pause();
+
+            // "..., right?" is a tag question, not a filler
+            let tag_question = last.trail.contains('?');
+
+            let drop = match rule {
+                Always => true,
+                _ if tag_question => false,
+                Delimited => pause_before && pause_after,
+                SentenceStart => sentence_start || (pause_before && pause_after),
+            };
+
+            if drop {
+                let closes_sentence = last.ends_sentence();
+                let at_end = i + len == tokens.len();
+                // "Okay, right, let's go" still needs a comma after "Okay"
+                let after_introduction = match out.len() {
+                    0 => false,
+                    n => {
+                        (n == 1 || out[n - 2].ends_sentence() || out[n - 1].lead.contains('\n'))
+                            && INTRODUCTIONS.contains(&out[n - 1].lower().as_str())
+                    }
+                };
+                if let Some(prev) = out.last_mut() {
+                    if !prev.ends_sentence() {
+                        if closes_sentence {
+                            // "done, you know." -> "done."
+                            let end: String =
+                                last.trail.chars().filter(|c| ".!?".contains(*c)).collect();
+                            prev.trail = trim_pause(&prev.trail) + &end;
+                        } else if at_end || (last.has_pause() && !after_introduction) {
+                            // Drop the comma pair that bracketed the filler
+                            prev.trail = trim_pause(&prev.trail);
+                        }
+                    }
+                }
+                if sentence_start && tokens[i].starts_uppercase() {
+                    capitalize_next = true;
+                }
+                carried_lead.push_str(&tokens[i].lead);
+                i += len;
+                continue;
+            }
+        }
+
+        let mut token = tokens[i].clone();
+        if capitalize_next {
+            token.capitalize();
+            capitalize_next = false;
+        }
+        if !carried_lead.is_empty() {
+            token.lead = std::mem::take(&mut carried_lead) + &token.lead;
+        }
+        out.push(token);
+        i += 1;
+    }
+
+    tokens::join(&collapse_stutters(out))
+}
+
+/// Strip trailing pause punctuation (commas, semicolons, dashes)
+fn trim_pause(trail: &str) -> String {
+    trail
+        .trim_end_matches([',', ';', ':', '—', '-'])
+        .to_string()
+}
+
+/// Collapse repeated words ("I I I think") and cut-off starts ("th- the")
+fn collapse_stutters(tokens: Vec<Token>) -> Vec<Token> {
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+
+    for token in tokens {
+        if let Some(prev) = out.last() {
+            let word = token.lower();
+            let prev_word = prev.lower();
+
+            let cut_off = prev.trail == "-"
+                && !prev_word.is_empty()
+                && word.len() > prev_word.len()
+                && word.starts_with(&prev_word);
+
+            // A single legitimate double is kept; a third repeat is a stutter
+            let allowed_double = DOUBLES_ALLOWED.contains(&word.as_str())
+                && (out.len() < 2 || out[out.len() - 2].lower() != word);
+
+            let repeated = !word.is_empty()
+                && word == prev_word
+                && token.lead.is_empty()
+                && (prev.trail.is_empty() || prev.trail == ",")
+                && !word.chars().all(|c| c.is_ascii_digit())
+                && !allowed_double;
+
+            if cut_off || repeated {
+                let prev = out.pop().unwrap();
+                let mut merged = token;
+                if prev.starts_uppercase() {
+                    merged.capitalize();
+                }
+                merged.lead = prev.lead;
+                out.push(merged);
+                continue;
+            }
+        }
+        out.push(token);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_fillers() {
+        let fillers = FillerList::new(&builtin("en"));
+        let cases: &[(&str, &str)] = &[
+            ("I like pizza", "I like pizza"),
+            ("Um, I think we should go.", "I think we should go."),
+            ("I was, like, really tired", "I was really tired"),
+            ("Okay, right, let's go.", "Okay, let's go."),
+            ("Well, you know, it's late", "Well, it's late"),
+            ("Done. So, like, what now?", "Done. So, what now?"),
+            ("It's, kind of, weird", "It's weird"),
+            ("I'm done, you know.", "I'm done."),
+            ("Okay, right", "Okay"),
+            ("Like, I said no", "I said no"),
+            ("Actually I think it works", "I think it works"),
+            ("it actually works", "it actually works"),
+            ("what kind of car", "what kind of car"),
+            ("I like, um, pizza", "I like pizza"),
+            ("that's great, right?", "that's great, right?"),
+            ("I ummm think so", "I think so"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(remove(input, &fillers), *expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn collapses_stutters() {
+        let fillers = FillerList::default();
+        let cases: &[(&str, &str)] = &[
+            ("I I I think so", "I think so"),
+            ("th- the cat", "the cat"),
+            ("the the the dog", "the dog"),
+            ("She had had enough", "She had had enough"),
+            ("very very good", "very very good"),
+            ("room 4 4 4", "room 4 4 4"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(remove(input, &fillers), *expected, "input: {:?}", input);
+        }
+    }
+}
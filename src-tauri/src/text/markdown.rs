@@ -0,0 +1,369 @@
+use super::tokens::{self, Token};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// How spoken structure ("bullet point", "heading two") is written out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Formatting {
+    /// Structure commands are left as words
+    #[default]
+    Off,
+    Markdown,
+    /// For rich-text fields that would show Markdown syntax literally:
+    /// "•" bullets, headings and bold as plain lines and words
+    Plain,
+}
+
+impl Formatting {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Formatting::Off => "off",
+            Formatting::Markdown => "markdown",
+            Formatting::Plain => "plain",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "markdown" => Formatting::Markdown,
+            "plain" => Formatting::Plain,
+            _ => Formatting::Off,
+        }
+    }
+}
+
+/// A command and the block it starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Bullet,
+    Numbered,
+    Heading(usize),
+    CodeBlock,
+    EndCodeBlock,
+    Bold,
+    EndBold,
+}
+
+const COMMANDS: &[(&str, Command)] = &[
+    ("bullet point", Command::Bullet),
+    ("new bullet", Command::Bullet),
+    ("numbered item", Command::Numbered),
+    ("numbered point", Command::Numbered),
+    ("next number", Command::Numbered),
+    ("heading one", Command::Heading(1)),
+    ("heading 1", Command::Heading(1)),
+    ("heading two", Command::Heading(2)),
+    ("heading 2", Command::Heading(2)),
+    ("heading three", Command::Heading(3)),
+    ("heading 3", Command::Heading(3)),
+    ("end code block", Command::EndCodeBlock),
+    ("close code block", Command::EndCodeBlock),
+    ("code block", Command::CodeBlock),
+    ("start bold", Command::Bold),
+    ("begin bold", Command::Bold),
+    ("end bold", Command::EndBold),
+    ("stop bold", Command::EndBold),
+];
+
+/// Punctuation the provider leaves at the end of a spoken list item
+const ITEM_TRAIL: &[char] = &[',', ';', ':', '.'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Text,
+    Bullet,
+    Numbered,
+    Heading(usize),
+    Code,
+}
+
+struct Block {
+    kind: Kind,
+    tokens: Vec<Token>,
+    /// Runs of bold words, as token indexes
+    bold: Vec<Range<usize>>,
+}
+
+impl Block {
+    fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            tokens: Vec::new(),
+            bold: Vec::new(),
+        }
+    }
+
+    /// End the bold run started at `start`, if any
+    fn close_bold(&mut self, start: &mut Option<usize>) {
+        if let Some(start) = start.take() {
+            if start < self.tokens.len() {
+                self.bold.push(start..self.tokens.len());
+            }
+        }
+    }
+}
+
+/// Turn spoken structure commands into Markdown or plain-text structure
+///
+/// List items and headings run to the end of the sentence, a line break or
+/// the next command, so "bullet point buy milk, eggs. Bullet point call mom"
+/// is two items. "code block" ... "end code block" is kept verbatim and
+/// "start bold" ... "end bold" wraps words inside any block; neither acts
+/// unless its end is said too. List, heading and code commands count only
+/// at the start of a sentence. Commands are English whatever the dictation
+/// language.
+pub fn format(text: &str, style: Formatting) -> String {
+    if style == Formatting::Off {
+        return text.to_string();
+    }
+    render(&blocks(&tokens::tokenize(text)), style)
+}
+
+/// Whether text ends in a list item, heading or code block, which don't take
+/// a closing period
+pub fn ends_in_block(text: &str) -> bool {
+    let last = text.trim_end().rsplit('\n').next().unwrap_or_default();
+    let numbered = last
+        .split_once(". ")
+        .is_some_and(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    last.starts_with("- ")
+        || last.starts_with("• ")
+        || last.starts_with('#')
+        || last == "```"
+        || numbered
+}
+
+fn blocks(tokens: &[Token]) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut current = Block::new(Kind::Text);
+    let mut bold: Option<usize> = None;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let mut token = tokens[i].clone();
+        // Blocks open at the start of a sentence, after a colon ("Shopping
+        // list: bullet point milk") or from inside another block, so "my
+        // favourite bullet point" stays prose
+        let at_boundary = i == 0
+            || tokens[i - 1].ends_sentence()
+            || tokens[i - 1].trail.ends_with(':')
+            || token.lead.contains('\n')
+            || current.kind != Kind::Text;
+        let command = command_at(tokens, i).filter(|(len, command)| match command {
+            Command::Bullet | Command::Numbered | Command::Heading(_) => at_boundary,
+            // "I made a code block yesterday" opens no fence
+            Command::CodeBlock => {
+                at_boundary && closed_after(tokens, i + len, Command::EndCodeBlock)
+            }
+            Command::Bold => closed_after(tokens, i + len, Command::EndBold),
+            Command::EndBold => bold.is_some(),
+            Command::EndCodeBlock => current.kind == Kind::Code,
+        });
+
+        if current.kind == Kind::Code {
+            if let Some((len, Command::EndCodeBlock)) = command {
+                blocks.push(std::mem::replace(&mut current, Block::new(Kind::Text)));
+                i += len;
+            } else {
+                current.tokens.push(token);
+                i += 1;
+            }
+            continue;
+        }
+
+        // A line break ends a list item or heading
+        if token.lead.contains('\n') && current.kind != Kind::Text {
+            current.close_bold(&mut bold);
+            blocks.push(std::mem::replace(&mut current, Block::new(Kind::Text)));
+            token.lead = token.lead.trim_start_matches('\n').to_string();
+        }
+
+        if let Some((len, command)) = command {
+            let next = match command {
+                Command::Bullet => Some(Kind::Bullet),
+                Command::Numbered => Some(Kind::Numbered),
+                Command::Heading(level) => Some(Kind::Heading(level)),
+                Command::CodeBlock => Some(Kind::Code),
+                Command::EndCodeBlock => None,
+                Command::Bold => {
+                    bold.get_or_insert(current.tokens.len());
+                    None
+                }
+                Command::EndBold => {
+                    current.close_bold(&mut bold);
+                    // "end bold, okay" keeps its comma
+                    if let Some(last) = current.tokens.last_mut() {
+                        last.trail.push_str(&tokens[i + len - 1].trail);
+                    }
+                    None
+                }
+            };
+            if let Some(kind) = next {
+                current.close_bold(&mut bold);
+                blocks.push(std::mem::replace(&mut current, Block::new(kind)));
+            }
+            i += len;
+            continue;
+        }
+
+        let ends_sentence = token.ends_sentence();
+        current.tokens.push(token);
+        i += 1;
+        if ends_sentence
+            && matches!(
+                current.kind,
+                Kind::Bullet | Kind::Numbered | Kind::Heading(_)
+            )
+        {
+            current.close_bold(&mut bold);
+            blocks.push(std::mem::replace(&mut current, Block::new(Kind::Text)));
+        }
+    }
+
+    current.close_bold(&mut bold);
+    blocks.push(current);
+    blocks
+        .into_iter()
+        .filter(|b| b.kind == Kind::Code || b.tokens.iter().any(|t| !t.is_empty()))
+        .collect()
+}
+
+fn render(blocks: &[Block], style: Formatting) -> String {
+    let mut out = String::new();
+    let mut number = 0;
+    let mut previous: Option<Kind> = None;
+
+    for block in blocks {
+        let is_item = matches!(block.kind, Kind::Bullet | Kind::Numbered);
+        if let Some(previous) = previous {
+            let same_list = is_item && previous == block.kind;
+            let in_text = previous == Kind::Text && block.kind == Kind::Text;
+            out.push_str(match (same_list, in_text) {
+                (true, _) => "\n",
+                (_, true) => " ",
+                _ => "\n\n",
+            });
+        }
+        number = if block.kind == Kind::Numbered && previous == Some(Kind::Numbered) {
+            number + 1
+        } else {
+            1
+        };
+
+        let body = inline(block, style);
+        let item = body.trim().trim_end_matches(ITEM_TRAIL);
+        match (block.kind, style) {
+            (Kind::Text, _) => out.push_str(body.trim()),
+            (Kind::Bullet, Formatting::Plain) => out.push_str(&format!("• {}", item)),
+            (Kind::Bullet, _) => out.push_str(&format!("- {}", item)),
+            (Kind::Numbered, _) => out.push_str(&format!("{}. {}", number, item)),
+            (Kind::Heading(_), Formatting::Plain) => out.push_str(item),
+            (Kind::Heading(level), _) => out.push_str(&format!("{} {}", "#".repeat(level), item)),
+            (Kind::Code, Formatting::Plain) => out.push_str(body.trim()),
+            (Kind::Code, _) => out.push_str(&format!("```\n{}\n```", body.trim())),
+        }
+        previous = Some(block.kind);
+    }
+    out
+}
+
+/// Join a block's words, wrapping bold runs in `**` for Markdown
+fn inline(block: &Block, style: Formatting) -> String {
+    if style != Formatting::Markdown || block.kind == Kind::Code {
+        return tokens::join(&block.tokens);
+    }
+    let mut tokens = block.tokens.clone();
+    for run in &block.bold {
+        // Inside the word's own punctuation: "**milk**," not "**milk,**"
+        tokens[run.start].lead.push_str("**");
+        tokens[run.end - 1].core.push_str("**");
+    }
+    tokens::join(&tokens)
+}
+
+/// Whether `end` is said anywhere from `tokens[from]` on
+fn closed_after(tokens: &[Token], from: usize, end: Command) -> bool {
+    (from..tokens.len()).any(|j| command_at(tokens, j).is_some_and(|(_, command)| command == end))
+}
+
+/// The command starting at `tokens[start]` and how many words it spans
+fn command_at(tokens: &[Token], start: usize) -> Option<(usize, Command)> {
+    COMMANDS.iter().find_map(|(phrase, command)| {
+        let parts: Vec<&str> = phrase.split(' ').collect();
+        let window = tokens.get(start..start + parts.len())?;
+        let matches = window
+            .iter()
+            .zip(&parts)
+            .enumerate()
+            .all(|(k, (token, part))| {
+                // Only the last word may carry punctuation ("bullet point, milk")
+                token.lower() == *part && (k + 1 == parts.len() || token.trail.is_empty())
+            });
+        matches.then_some((parts.len(), *command))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_spoken_structure() {
+        let cases: &[(&str, &str)] = &[
+            (
+                "bullet point buy milk, eggs. Bullet point call mom",
+                "- buy milk, eggs\n- call mom",
+            ),
+            (
+                "Shopping list: bullet point milk bullet point eggs",
+                "Shopping list:\n\n- milk\n- eggs",
+            ),
+            (
+                "numbered item wake up. Numbered item code",
+                "1. wake up\n2. code",
+            ),
+            (
+                "heading two Plans. We start Monday.",
+                "## Plans\n\nWe start Monday.",
+            ),
+            (
+                "Run this. Code block npm install end code block",
+                "Run this.\n\n```\nnpm install\n```",
+            ),
+            ("buy start bold milk end bold today", "buy **milk** today"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                format(input, Formatting::Markdown),
+                *expected,
+                "input: {:?}",
+                input
+            );
+        }
+        assert_eq!(
+            format("bullet point milk bullet point eggs", Formatting::Plain),
+            "• milk\n• eggs"
+        );
+    }
+
+    #[test]
+    fn leaves_commands_inside_sentences_alone() {
+        let cases: &[&str] = &[
+            "I made a code block yesterday.",
+            "My favourite bullet point is this one.",
+            "Code block formatting is broken.",
+            "We should start bold moves.",
+            "That was the end bold statement.",
+            "See heading two for details.",
+        ];
+        for input in cases {
+            assert_eq!(
+                format(input, Formatting::Markdown),
+                *input,
+                "input: {:?}",
+                input
+            );
+        }
+    }
+}
@@ -0,0 +1,223 @@
+pub mod code;
+pub mod common_words;
+pub mod context;
+pub mod corrections;
+pub mod dictionary;
+pub mod diff;
+pub mod emoji;
+pub mod fillers;
+pub mod markdown;
+pub mod normalization;
+pub mod paragraphs;
+pub mod punctuation;
+pub mod similarity;
+pub mod snippets;
+pub mod spelling;
+pub mod style;
+pub mod tokens;
+
+use crate::storage;
+use diff::Stage;
+use serde::{Deserialize, Serialize};
+
+/// Which local post-processing stages run on a transcript
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProcessingOptions {
+    #[serde(default)]
+    pub smart_correction: bool,
+    #[serde(default)]
+    pub remove_fillers: bool,
+    #[serde(default)]
+    pub auto_punctuation: bool,
+    #[serde(default)]
+    pub auto_capitalize: bool,
+    /// Turn spoken commands ("comma", "new line") into symbols
+    #[serde(default)]
+    pub spoken_punctuation: bool,
+    /// Write numbers, dates, money and addresses in written form
+    #[serde(default)]
+    pub normalize_spoken_forms: bool,
+    /// Locale tag for written forms ("en-GB"), which are only read from
+    /// English; defaults from the language
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Replace spoken snippet triggers with their content
+    #[serde(default)]
+    pub expand_snippets: bool,
+    /// Let snippet triggers match despite small recognition errors
+    #[serde(default)]
+    pub fuzzy_snippets: bool,
+    /// Format for code: casing commands, spoken symbols and no automatic
+    /// capitalization or punctuation
+    #[serde(default)]
+    pub code_mode: bool,
+    /// Replace spoken emoji names ("thumbs up emoji"); set per app from its
+    /// profile
+    #[serde(default)]
+    pub emoji: bool,
+    /// Start paragraphs and sentences at long pauses; needs the audio, so
+    /// only applies to dictations
+    #[serde(default)]
+    pub paragraph_breaks: bool,
+    /// Pause that starts a paragraph; defaults to
+    /// `paragraphs::DEFAULT_PARAGRAPH_PAUSE_MS`
+    #[serde(default)]
+    pub paragraph_pause_ms: Option<u64>,
+    /// Turn list, heading, bold and code block commands into structure
+    #[serde(default)]
+    pub formatting: markdown::Formatting,
+    /// Rewrite words in British or American spelling
+    #[serde(default)]
+    pub spelling_variant: style::SpellingVariant,
+    /// Apply the user's house style rules
+    #[serde(default)]
+    pub house_style: bool,
+    /// Replace words that sound like custom dictionary entries
+    #[serde(default)]
+    pub dictionary_correction: bool,
+    /// Confidence needed for a dictionary replacement; defaults to
+    /// `dictionary::DEFAULT_THRESHOLD`
+    #[serde(default)]
+    pub dictionary_threshold: Option<f32>,
+}
+
+/// A transcript after local processing
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProcessedText {
+    pub text: String,
+    /// Revisions the speaker made out loud ("no wait", "scratch that")
+    pub corrections: Vec<corrections::SelfCorrection>,
+    /// Snippets expanded into the text
+    pub snippets: Vec<snippets::SnippetMatch>,
+    /// Words auto-fixed from the custom dictionary
+    pub replacements: Vec<storage::dictionary::DictionaryReplacement>,
+    /// Text after each stage that changed it, for the history diff
+    #[serde(default)]
+    pub stages: Vec<diff::StageText>,
+}
+
+/// Run the enabled local stages over a transcript
+pub fn process(text: &str, language: &str, options: &ProcessingOptions) -> ProcessedText {
+    let input = text;
+    let mut text = text.to_string();
+    let mut stages = Vec::new();
+    let mut self_corrections = Vec::new();
+
+    // Before filler removal, which would otherwise eat cues like "I mean"
+    if options.smart_correction {
+        let outcome = corrections::resolve(&text, language);
+        text = outcome.text;
+        self_corrections = outcome.corrections;
+        diff::record(&mut stages, input, Stage::SelfCorrection, &text);
+    }
+
+    if options.remove_fillers {
+        let list = fillers::FillerList::load(language).unwrap_or_else(|e| {
+            log::warn!("Failed to load filler words, using built-ins: {}", e);
+            fillers::FillerList::new(&fillers::builtin(language))
+        });
+        text = fillers::remove(&text, &list);
+        diff::record(&mut stages, input, Stage::Fillers, &text);
+    }
+
+    let mut replacements = Vec::new();
+    if options.dictionary_correction {
+        match storage::dictionary::list() {
+            Ok(words) => {
+                let threshold = options
+                    .dictionary_threshold
+                    .unwrap_or(dictionary::DEFAULT_THRESHOLD);
+                let outcome = dictionary::correct(&text, &words, threshold);
+                text = outcome.text;
+                replacements = outcome.replacements;
+                diff::record(&mut stages, input, Stage::Dictionary, &text);
+            }
+            Err(e) => log::warn!("Failed to load dictionary, skipping correction: {}", e),
+        }
+    }
+
+    // Before normalization, which would write "hundred points" as a number
+    if options.emoji && !options.code_mode {
+        text = emoji::resolve(&text);
+        diff::record(&mut stages, input, Stage::Emoji, &text);
+    }
+
+    if options.normalize_spoken_forms && !options.code_mode {
+        let locale = match &options.locale {
+            Some(tag) => normalization::Locale::parse(tag),
+            None => normalization::Locale::new(language, None),
+        };
+        text = normalization::normalize(&text, language, &locale);
+        diff::record(&mut stages, input, Stage::Normalization, &text);
+    }
+
+    if options.spoken_punctuation && !options.code_mode {
+        let commands = punctuation::CommandList::load(language).unwrap_or_else(|e| {
+            log::warn!(
+                "Failed to load punctuation commands, using built-ins: {}",
+                e
+            );
+            punctuation::CommandList::new(language, &punctuation::builtin(language))
+        });
+        text = punctuation::apply(&text, &commands);
+        diff::record(&mut stages, input, Stage::Punctuation, &text);
+    }
+
+    // In place of normalization and spoken punctuation, whose prose rules
+    // don't fit code
+    if options.code_mode {
+        text = code::format(&text);
+        diff::record(&mut stages, input, Stage::Code, &text);
+    }
+
+    if options.formatting != markdown::Formatting::Off && !options.code_mode {
+        text = markdown::format(&text, options.formatting);
+        diff::record(&mut stages, input, Stage::Formatting, &text);
+    }
+
+    // After punctuation and formatting so rules can match commas and list
+    // items, and before snippets so their content is left as written
+    if options.spelling_variant != style::SpellingVariant::Off && !options.code_mode {
+        text = style::convert(&text, options.spelling_variant);
+        diff::record(&mut stages, input, Stage::Spelling, &text);
+    }
+
+    if options.house_style && !options.code_mode {
+        match style::RuleSet::load() {
+            Ok(rules) => {
+                text = style::apply(&text, &rules);
+                diff::record(&mut stages, input, Stage::HouseStyle, &text);
+            }
+            Err(e) => log::warn!("Failed to load style rules, skipping house style: {}", e),
+        }
+    }
+
+    // Last, so snippet content is inserted verbatim
+    let mut expanded = Vec::new();
+    if options.expand_snippets {
+        match storage::snippets::list_enabled() {
+            Ok(list) => {
+                let expansion = snippets::expand(&text, &list, options.fuzzy_snippets);
+                text = expansion.text;
+                expanded = expansion.matches;
+                diff::record(&mut stages, input, Stage::Snippets, &text);
+            }
+            Err(e) => log::warn!("Failed to load snippets, skipping expansion: {}", e),
+        }
+
+        let used: Vec<String> = expanded.iter().map(|m| m.snippet_id.clone()).collect();
+        if !used.is_empty() {
+            if let Err(e) = storage::snippets::record_use(&used) {
+                log::warn!("Failed to record snippet usage: {}", e);
+            }
+        }
+    }
+
+    ProcessedText {
+        text,
+        corrections: self_corrections,
+        snippets: expanded,
+        replacements,
+        stages,
+    }
+}
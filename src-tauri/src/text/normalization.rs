@@ -0,0 +1,1024 @@
+use super::common_words;
+use super::tokens::{self, Token};
+
+/// Written conventions for a locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    /// "3 March" rather than "March 3"
+    pub day_first: bool,
+    pub thousands_separator: char,
+    pub decimal_separator: char,
+    /// "25 €" rather than "€25"
+    pub currency_after: bool,
+    /// "25 %" rather than "25%"
+    pub space_before_percent: bool,
+    /// "PM" rather than "pm"
+    pub uppercase_meridiem: bool,
+    /// "two pounds" is money rather than weight
+    pub pounds_sterling: bool,
+}
+
+impl Locale {
+    /// Conventions for a language and optional BCP 47 region ("en", "GB")
+    pub fn new(language: &str, region: Option<&str>) -> Self {
+        let region = region.map(str::to_uppercase);
+        match (language, region.as_deref()) {
+            ("en", Some(region @ ("GB" | "IE" | "AU" | "NZ" | "ZA" | "IN"))) => Self {
+                day_first: true,
+                uppercase_meridiem: false,
+                pounds_sterling: region == "GB",
+                ..Self::US
+            },
+            ("en", _) => Self::US,
+            ("de" | "es", _) => Self {
+                day_first: true,
+                thousands_separator: '.',
+                decimal_separator: ',',
+                currency_after: true,
+                space_before_percent: true,
+                uppercase_meridiem: false,
+                pounds_sterling: false,
+            },
+            ("fr", _) => Self {
+                day_first: true,
+                thousands_separator: ' ',
+                decimal_separator: ',',
+                currency_after: true,
+                space_before_percent: true,
+                uppercase_meridiem: false,
+                pounds_sterling: false,
+            },
+            _ => Self::US,
+        }
+    }
+
+    /// Parse a locale tag such as "en-GB" or "en_US"
+    pub fn parse(tag: &str) -> Self {
+        let mut parts = tag.split(['-', '_']);
+        let language = parts.next().unwrap_or_default().to_lowercase();
+        Self::new(&language, parts.next())
+    }
+
+    const US: Self = Self {
+        day_first: false,
+        thousands_separator: ',',
+        decimal_separator: '.',
+        currency_after: false,
+        space_before_percent: false,
+        uppercase_meridiem: true,
+        pounds_sterling: false,
+    };
+}
+
+const UNITS: &[&str] = &[
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: &[&str] = &[
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const ORDINAL_UNITS: &[&str] = &[
+    "zeroth",
+    "first",
+    "second",
+    "third",
+    "fourth",
+    "fifth",
+    "sixth",
+    "seventh",
+    "eighth",
+    "ninth",
+    "tenth",
+    "eleventh",
+    "twelfth",
+    "thirteenth",
+    "fourteenth",
+    "fifteenth",
+    "sixteenth",
+    "seventeenth",
+    "eighteenth",
+    "nineteenth",
+];
+
+const ORDINAL_TENS: &[&str] = &[
+    "",
+    "",
+    "twentieth",
+    "thirtieth",
+    "fortieth",
+    "fiftieth",
+    "sixtieth",
+    "seventieth",
+    "eightieth",
+    "ninetieth",
+];
+
+const SCALES: &[(&str, u64)] = &[
+    ("thousand", 1_000),
+    ("million", 1_000_000),
+    ("billion", 1_000_000_000),
+];
+
+const MONTHS: &[&str] = &[
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Spoken currency names and their symbols
+const CURRENCIES: &[(&str, &str)] = &[
+    ("dollar", "$"),
+    ("dollars", "$"),
+    ("buck", "$"),
+    ("bucks", "$"),
+    ("euro", "€"),
+    ("euros", "€"),
+    ("pound", "£"),
+    ("pounds", "£"),
+    ("yen", "¥"),
+];
+
+const CENTS: &[&str] = &["cent", "cents", "pence", "p"];
+
+/// Words that make "pounds" money outside the UK ("it costs two pounds")
+const MONEY_CUES: &[&str] = &[
+    "cost", "costs", "costing", "paid", "pay", "pays", "paying", "price", "priced", "spent",
+    "spend", "owe", "owes", "charge", "charged", "earn", "earned", "fee", "budget", "worth",
+];
+
+/// Words that make "pounds" a weight even in the UK
+const WEIGHT_CUES: &[&str] = &[
+    "weigh", "weighs", "weighed", "weighing", "weight", "lose", "lost", "gain", "gained", "heavy",
+];
+
+/// How far back a money or weight cue counts, in words
+const MONEY_CUE_WINDOW: usize = 4;
+
+/// Words before "one point" that make it a figure of speech, not 1.x
+/// ("at one point two people left")
+const ONE_POINT_IDIOMS: &[&str] = &["at", "from", "to"];
+
+/// Words that introduce a clock time said without am/pm ("at three thirty")
+const TIME_PREPOSITIONS: &[&str] = &[
+    "at", "by", "until", "till", "from", "around", "before", "after",
+];
+
+/// Top-level domains recognised when joining spoken addresses
+const TLDS: &[&str] = &[
+    "com", "org", "net", "io", "co", "edu", "gov", "ai", "app", "dev", "me", "us", "uk", "de",
+    "fr", "es", "info", "biz", "tv",
+];
+
+/// Spoken separators inside emails and domains
+const ADDRESS_JOINERS: &[(&str, &str)] = &[
+    ("dot", "."),
+    ("dash", "-"),
+    ("hyphen", "-"),
+    ("underscore", "_"),
+];
+
+/// Words that never begin an email or domain, so "meet me at example dot
+/// com" keeps its "me at"
+const NOT_ADDRESS_START: &[&str] = &[
+    "a", "an", "the", "and", "or", "of", "to", "in", "on", "at", "for", "with", "me", "us", "you",
+    "him", "her", "them", "it", "we", "i", "is", "are", "was",
+];
+
+/// Words that say an address is coming, so "support at example dot com"
+/// is an email even though "support" is an everyday word
+const EMAIL_CUES: &[&str] = &["email", "e-mail", "emails", "mail", "address", "contact"];
+
+/// How far back an email cue counts, in words
+const EMAIL_CUE_WINDOW: usize = 6;
+
+/// Words after which a month name is a date ("on march third")
+const DATE_PREPOSITIONS: &[&str] = &[
+    "on", "in", "by", "until", "till", "from", "since", "before", "after", "of", "during",
+];
+
+/// Shortest run of spoken digits read as a phone number
+const PHONE_MIN_DIGITS: usize = 7;
+
+/// Convert spoken numbers, dates, times, money and addresses to written form
+///
+/// "twenty five dollars on march third at three thirty pm" becomes "$25 on
+/// March 3 at 3:30 PM". Only English is read: text in other languages
+/// passes through unchanged, since their providers already return digits.
+/// `locale` then only picks the written conventions for English, so German
+/// or French separators apply to English dictated with a "de" or "fr" tag.
+pub fn normalize(text: &str, language: &str, locale: &Locale) -> String {
+    if language != "en" {
+        return text.to_string();
+    }
+
+    let tokens = tokens::tokenize(text);
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let prev = out.last().map(Token::lower);
+        let matched = email(&tokens, i)
+            .or_else(|| url(&tokens, i))
+            .or_else(|| date(&tokens, i, locale))
+            .or_else(|| time(&tokens, i, prev.as_deref(), locale))
+            .or_else(|| phone(&tokens, i, locale))
+            .or_else(|| money(&tokens, i, locale))
+            .or_else(|| number(&tokens, i, locale));
+
+        match matched {
+            Some((len, written)) => {
+                out.push(Token {
+                    lead: tokens[i].lead.clone(),
+                    core: written,
+                    trail: tokens[i + len - 1].trail.clone(),
+                });
+                i += len;
+            }
+            None => {
+                out.push(tokens[i].clone());
+                i += 1;
+            }
+        }
+    }
+
+    tokens::join(&out)
+}
+
+/// Whether `tokens[start..end]` can be read as one phrase: no punctuation
+/// between the words
+fn contiguous(tokens: &[Token], start: usize, end: usize) -> bool {
+    end <= tokens.len()
+        && tokens[start..end].iter().enumerate().all(|(k, t)| {
+            (k == 0 || t.lead.is_empty()) && (start + k == end - 1 || t.trail.is_empty())
+        })
+}
+
+fn word_at(tokens: &[Token], i: usize) -> Option<String> {
+    tokens.get(i).map(Token::lower)
+}
+
+/// A number read from spoken words
+#[derive(Debug, Clone, PartialEq)]
+struct Spoken {
+    value: u64,
+    /// Digits after "point"
+    decimals: Option<String>,
+    ordinal: bool,
+    /// Number of tokens consumed
+    len: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Last {
+    None,
+    Unit,
+    Teen,
+    Tens,
+    Hundred,
+    Scale,
+}
+
+/// Read a cardinal or ordinal number starting at `tokens[start]`
+fn spoken_number(tokens: &[Token], start: usize) -> Option<Spoken> {
+    let mut total = 0u64;
+    let mut current = 0u64;
+    let mut last = Last::None;
+    let mut ordinal = false;
+    let mut j = start;
+
+    while j < tokens.len() && !ordinal {
+        if j > start && (!tokens[j].lead.is_empty() || !tokens[j - 1].trail.is_empty()) {
+            break;
+        }
+        let word = tokens[j].lower();
+
+        if let Some(v) = index_of(UNITS, &word) {
+            if !fits_unit(last, v) {
+                break;
+            }
+            current += v;
+            last = if v < 10 { Last::Unit } else { Last::Teen };
+        } else if let Some(v) = index_of(ORDINAL_UNITS, &word) {
+            if !fits_unit(last, v) {
+                break;
+            }
+            current += v;
+            ordinal = true;
+        } else if let Some(v) = index_of(TENS, &word).filter(|v| *v >= 2) {
+            if !matches!(last, Last::None | Last::Hundred | Last::Scale) {
+                break;
+            }
+            current += v * 10;
+            last = Last::Tens;
+        } else if let Some(v) = index_of(ORDINAL_TENS, &word).filter(|v| *v >= 2) {
+            if !matches!(last, Last::None | Last::Hundred | Last::Scale) {
+                break;
+            }
+            current += v * 10;
+            ordinal = true;
+        } else if word == "hundred" || word == "hundredth" {
+            if current == 0 || current >= 100 {
+                break;
+            }
+            current *= 100;
+            last = Last::Hundred;
+            ordinal = word == "hundredth";
+        } else if let Some((_, scale)) = SCALES.iter().find(|(name, _)| *name == word) {
+            if current == 0 || last == Last::Scale {
+                break;
+            }
+            total += current * scale;
+            current = 0;
+            last = Last::Scale;
+        } else if word == "and"
+            && matches!(last, Last::Hundred | Last::Scale)
+            && contiguous(tokens, j, j + 2)
+            && word_at(tokens, j + 1).is_some_and(|next| {
+                index_of(UNITS, &next).is_some() || index_of(TENS, &next).is_some()
+            })
+        {
+            // "one hundred and five"
+        } else {
+            break;
+        }
+        j += 1;
+    }
+
+    if last == Last::None && !ordinal {
+        return None;
+    }
+
+    let mut spoken = Spoken {
+        value: total + current,
+        decimals: None,
+        ordinal,
+        len: j - start,
+    };
+
+    let idiom = spoken.value == 1
+        && spoken.len == 1
+        && start > 0
+        && tokens[start - 1].trail.is_empty()
+        && ONE_POINT_IDIOMS.contains(&tokens[start - 1].lower().as_str());
+
+    // "three point five"
+    if !ordinal
+        && !idiom
+        && word_at(tokens, j).as_deref() == Some("point")
+        && tokens[j - 1].trail.is_empty()
+    {
+        let mut digits = String::new();
+        let mut k = j + 1;
+        while k < tokens.len() && tokens[k - 1].trail.is_empty() {
+            match digit(&tokens[k].lower()) {
+                Some(d) => digits.push(d),
+                None => break,
+            }
+            k += 1;
+        }
+        if !digits.is_empty() {
+            spoken.decimals = Some(digits);
+            spoken.len = k - start;
+        }
+    }
+
+    Some(spoken)
+}
+
+/// Whether a unit or teen can follow what has been read so far
+fn fits_unit(last: Last, value: u64) -> bool {
+    match last {
+        Last::None | Last::Hundred | Last::Scale => true,
+        // "twenty five", but not "twenty fifteen"
+        Last::Tens => value < 10,
+        Last::Unit | Last::Teen => false,
+    }
+}
+
+fn index_of(words: &[&str], word: &str) -> Option<u64> {
+    words.iter().position(|w| *w == word).map(|p| p as u64)
+}
+
+/// A single spoken digit, including "oh" for zero
+fn digit(word: &str) -> Option<char> {
+    match word {
+        "oh" | "o" => Some('0'),
+        _ => index_of(UNITS, word)
+            .filter(|v| *v < 10)
+            .and_then(|v| char::from_digit(v as u32, 10)),
+    }
+}
+
+/// Digits with locale grouping; four-digit numbers stay ungrouped
+fn group_digits(value: u64, locale: &Locale) -> String {
+    let digits = value.to_string();
+    if digits.len() <= 4 {
+        return digits;
+    }
+    digits
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|group| String::from_utf8_lossy(group).into_owned())
+        .collect::<Vec<_>>()
+        .join(&locale.thousands_separator.to_string())
+}
+
+fn format_number(spoken: &Spoken, locale: &Locale) -> String {
+    let mut out = group_digits(spoken.value, locale);
+    if let Some(decimals) = &spoken.decimals {
+        out.push(locale.decimal_separator);
+        out.push_str(decimals);
+    }
+    out
+}
+
+fn ordinal_suffix(value: u64) -> &'static str {
+    match (value % 10, value % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+/// A spoken cardinal as bare digits ("1024", "3.5"), for code
+pub fn digits_at(tokens: &[Token], start: usize) -> Option<(usize, String)> {
+    let spoken = spoken_number(tokens, start).filter(|s| !s.ordinal)?;
+    let mut out = spoken.value.to_string();
+    if let Some(decimals) = &spoken.decimals {
+        out.push('.');
+        out.push_str(decimals);
+    }
+    Some((spoken.len, out))
+}
+
+/// Plain numbers; one to nine stay spelled out as in running prose
+fn number(tokens: &[Token], i: usize, locale: &Locale) -> Option<(usize, String)> {
+    let spoken = spoken_number(tokens, i)?;
+    // "nineteen eighty four" is one year rather than 19 and 84
+    if let Some((len, year)) = year_at(tokens, i).filter(|(len, _)| *len > spoken.len) {
+        return Some((len, year.to_string()));
+    }
+    if spoken.value < 10 && spoken.decimals.is_none() {
+        return None;
+    }
+
+    let written = if spoken.ordinal {
+        format!("{}{}", spoken.value, ordinal_suffix(spoken.value))
+    } else {
+        format_number(&spoken, locale)
+    };
+    Some((spoken.len, written))
+}
+
+/// Amounts of money and percentages
+fn money(tokens: &[Token], i: usize, locale: &Locale) -> Option<(usize, String)> {
+    let spoken = spoken_number(tokens, i).filter(|s| !s.ordinal)?;
+    let mut j = i + spoken.len;
+    let amount = format_number(&spoken, locale);
+
+    // "three point five million dollars"
+    let scale = word_at(tokens, j)
+        .filter(|w| SCALES.iter().any(|(name, _)| name == w))
+        .filter(|_| contiguous(tokens, j - 1, j + 1));
+    if scale.is_some() {
+        j += 1;
+    }
+    let amount = match &scale {
+        Some(scale) => format!("{} {}", amount, scale),
+        None => amount,
+    };
+
+    if !contiguous(tokens, j - 1, j + 1) {
+        return None;
+    }
+    let unit = word_at(tokens, j)?;
+
+    if unit == "percent" || (unit == "per" && word_at(tokens, j + 1).as_deref() == Some("cent")) {
+        let len = if unit == "per" { 2 } else { 1 };
+        let space = if locale.space_before_percent { " " } else { "" };
+        return Some((j + len - i, format!("{}{}%", amount, space)));
+    }
+
+    let (_, symbol) = CURRENCIES.iter().find(|(name, _)| *name == unit)?;
+    if unit.starts_with("pound") && !pounds_are_money(tokens, i, j, locale) {
+        return None;
+    }
+    j += 1;
+
+    // "twenty five dollars and fifty cents"
+    let mut cents = None;
+    if scale.is_none()
+        && spoken.decimals.is_none()
+        && word_at(tokens, j).as_deref() == Some("and")
+        && contiguous(tokens, j - 1, j + 1)
+    {
+        if let Some(c) = spoken_number(tokens, j + 1).filter(|c| c.value < 100 && !c.ordinal) {
+            let end = j + 1 + c.len;
+            if word_at(tokens, end).is_some_and(|w| CENTS.contains(&w.as_str()))
+                && contiguous(tokens, j, end + 1)
+            {
+                cents = Some(c.value);
+                j = end + 1;
+            }
+        }
+    }
+
+    let amount = match cents {
+        Some(c) => format!("{}{}{:02}", amount, locale.decimal_separator, c),
+        None => amount,
+    };
+    let written = if locale.currency_after {
+        format!("{} {}", amount, symbol)
+    } else {
+        format!("{}{}", symbol, amount)
+    };
+    Some((j - i, written))
+}
+
+/// Whether "pounds" at `tokens[unit]`, after an amount at `tokens[start]`,
+/// is money: "two pounds of beef" and "weighs two hundred pounds" aren't
+fn pounds_are_money(tokens: &[Token], start: usize, unit: usize, locale: &Locale) -> bool {
+    if word_at(tokens, unit + 1).as_deref() == Some("of") {
+        return false;
+    }
+    let cue = |cues: &[&str]| {
+        tokens[start.saturating_sub(MONEY_CUE_WINDOW)..start]
+            .iter()
+            .any(|t| cues.contains(&t.lower().as_str()))
+    };
+    if cue(WEIGHT_CUES) {
+        return false;
+    }
+    let pence = (unit + 1..unit + 5).any(|k| word_at(tokens, k).as_deref() == Some("pence"));
+    locale.pounds_sterling || pence || cue(MONEY_CUES)
+}
+
+/// "March third twenty twenty four", "the third of March"
+fn date(tokens: &[Token], i: usize, locale: &Locale) -> Option<(usize, String)> {
+    let month_at = |k: usize| {
+        let token = tokens.get(k)?;
+        let month = MONTHS
+            .iter()
+            .find(|m| m.eq_ignore_ascii_case(&token.core))?;
+        // "may" is only a month when the provider capitalised it
+        (*month != "May" || token.starts_uppercase()).then_some(*month)
+    };
+    let day_at = |k: usize| {
+        spoken_number(tokens, k).filter(|d| (1..=31).contains(&d.value) && d.decimals.is_none())
+    };
+
+    // "the third of March"
+    let the = usize::from(word_at(tokens, i).as_deref() == Some("the"));
+    if let Some(day) = day_at(i + the).filter(|d| d.ordinal) {
+        let of = i + the + day.len;
+        if word_at(tokens, of).as_deref() == Some("of") {
+            if let Some(month) = month_at(of + 1) {
+                if contiguous(tokens, i, of + 2) {
+                    let mut end = of + 2;
+                    let year = year_at(tokens, end);
+                    if let Some((len, _)) = year {
+                        end += len;
+                    }
+                    let written = format_date(month, Some(day.value), year.map(|y| y.1), locale);
+                    return Some((end - i, written));
+                }
+            }
+        }
+    }
+
+    let month = month_at(i)?;
+    let mut end = i + 1;
+
+    // A month name can be a verb ("march twenty soldiers", "May I"), so it
+    // needs a preposition or a mid-sentence capital, or else an ordinal day
+    // or a year after it
+    let prev = i.checked_sub(1).map(|k| &tokens[k]);
+    let after_preposition =
+        prev.is_some_and(|p| p.trail.is_empty() && DATE_PREPOSITIONS.contains(&p.lower().as_str()));
+    let capitalized = tokens[i].starts_uppercase() && prev.is_some_and(|p| !p.ends_sentence());
+    let firm = after_preposition || capitalized;
+    if month == "May" && !firm {
+        return None;
+    }
+
+    // "March twenty twenty four" is a month and year
+    if let Some((len, year)) = year_at(tokens, end).filter(|_| contiguous(tokens, i, end + 1)) {
+        return Some((end + len - i, format_date(month, None, Some(year), locale)));
+    }
+
+    let day = day_at(end).filter(|d| contiguous(tokens, i, end + d.len))?;
+    end += day.len;
+    let year = year_at(tokens, end)
+        .filter(|_| tokens[end - 1].trail.is_empty() || tokens[end - 1].trail == ",");
+    if let Some((len, _)) = year {
+        end += len;
+    }
+    if !(firm || day.ordinal || year.is_some()) {
+        return None;
+    }
+
+    Some((
+        end - i,
+        format_date(month, Some(day.value), year.map(|y| y.1), locale),
+    ))
+}
+
+/// A year read as two pairs ("nineteen eighty four", "twenty oh five") or
+/// as a cardinal ("two thousand five")
+///
+/// Pairs start at eleven through twenty so "ten twenty dollar bills" stays
+/// two numbers.
+fn year_at(tokens: &[Token], start: usize) -> Option<(usize, u64)> {
+    let pair = |k: usize| -> Option<(usize, u64)> {
+        let spoken = spoken_number(tokens, k)?;
+        ((10..100).contains(&spoken.value) && !spoken.ordinal && spoken.decimals.is_none())
+            .then_some((spoken.len, spoken.value))
+    };
+
+    if let Some((first_len, century)) = pair(start).filter(|(_, c)| (11..=20).contains(c)) {
+        let next = start + first_len;
+        if contiguous(tokens, start, next + 1) {
+            let second = match word_at(tokens, next).as_deref() {
+                Some("hundred") => Some((1, 0)),
+                Some("oh") => word_at(tokens, next + 1)
+                    .and_then(|w| digit(&w))
+                    .filter(|_| contiguous(tokens, next, next + 2))
+                    .map(|d| (2, u64::from(d.to_digit(10).unwrap_or(0)))),
+                _ => pair(next),
+            };
+            if let Some((second_len, rest)) = second {
+                return Some((first_len + second_len, century * 100 + rest));
+            }
+        }
+    }
+
+    spoken_number(tokens, start)
+        .filter(|s| (1000..3000).contains(&s.value) && !s.ordinal && s.decimals.is_none())
+        .map(|s| (s.len, s.value))
+}
+
+fn format_date(month: &str, day: Option<u64>, year: Option<u64>, locale: &Locale) -> String {
+    match (day, year, locale.day_first) {
+        (Some(day), Some(year), true) => format!("{} {} {}", day, month, year),
+        (Some(day), Some(year), false) => format!("{} {}, {}", month, day, year),
+        (Some(day), None, true) => format!("{} {}", day, month),
+        (Some(day), None, false) => format!("{} {}", month, day),
+        (None, Some(year), _) => format!("{} {}", month, year),
+        (None, None, _) => month.to_string(),
+    }
+}
+
+/// "three thirty pm", "at three thirty", "seven o'clock"
+fn time(
+    tokens: &[Token],
+    i: usize,
+    prev: Option<&str>,
+    locale: &Locale,
+) -> Option<(usize, String)> {
+    let hour = spoken_number(tokens, i)
+        .filter(|h| h.len == 1 && (1..=12).contains(&h.value) && !h.ordinal)?;
+    let mut end = i + 1;
+
+    if word_at(tokens, end).as_deref() == Some("o'clock") && contiguous(tokens, i, end + 1) {
+        return Some((2, format!("{} o'clock", hour.value)));
+    }
+
+    // Minutes: "oh five", "fifteen", "forty five"
+    let mut minutes = None;
+    if contiguous(tokens, i, end + 1) {
+        if word_at(tokens, end).as_deref() == Some("oh") {
+            if let Some(d) = word_at(tokens, end + 1).and_then(|w| digit(&w)) {
+                if contiguous(tokens, end, end + 2) {
+                    minutes = d.to_digit(10).map(u64::from);
+                    end += 2;
+                }
+            }
+        } else if let Some(m) = spoken_number(tokens, end)
+            .filter(|m| (10..60).contains(&m.value) && !m.ordinal && m.decimals.is_none())
+        {
+            minutes = Some(m.value);
+            end += m.len;
+        }
+    }
+
+    let meridiem = meridiem_at(tokens, end).filter(|_| contiguous(tokens, end - 1, end + 1));
+    let clock = match minutes {
+        Some(m) => format!("{}:{:02}", hour.value, m),
+        None => hour.value.to_string(),
+    };
+
+    match meridiem {
+        Some((len, pm)) => {
+            let suffix = match (pm, locale.uppercase_meridiem) {
+                (true, true) => "PM",
+                (true, false) => "pm",
+                (false, true) => "AM",
+                (false, false) => "am",
+            };
+            Some((end + len - i, format!("{} {}", clock, suffix)))
+        }
+        None if minutes.is_some() && prev.is_some_and(|p| TIME_PREPOSITIONS.contains(&p)) => {
+            Some((end - i, clock))
+        }
+        None => None,
+    }
+}
+
+/// "pm", "p.m." or "p m"; returns its length and whether it's afternoon
+fn meridiem_at(tokens: &[Token], k: usize) -> Option<(usize, bool)> {
+    let word = word_at(tokens, k)?;
+    match word.as_str() {
+        "am" | "a.m" => Some((1, false)),
+        "pm" | "p.m" => Some((1, true)),
+        "a" | "p"
+            if word_at(tokens, k + 1).as_deref() == Some("m") && contiguous(tokens, k, k + 2) =>
+        {
+            Some((2, word == "p"))
+        }
+        _ => None,
+    }
+}
+
+/// Seven or more digits read one by one; "double five" and "triple oh"
+/// repeat the next digit
+fn phone(tokens: &[Token], i: usize, locale: &Locale) -> Option<(usize, String)> {
+    let mut digits = String::new();
+    let mut j = i;
+
+    while j < tokens.len() && contiguous(tokens, i, j + 1) {
+        let word = tokens[j].lower();
+        let repeat = match word.as_str() {
+            "double" => 2,
+            "triple" => 3,
+            _ => 1,
+        };
+        let k = if repeat > 1 { j + 1 } else { j };
+        if repeat > 1 && !contiguous(tokens, i, k + 1) {
+            break;
+        }
+        let Some(d) = word_at(tokens, k).and_then(|w| digit(&w)) else {
+            break;
+        };
+        // "o" alone is too ambiguous to start a number
+        if digits.is_empty() && tokens[k].lower() == "o" {
+            return None;
+        }
+        for _ in 0..repeat {
+            digits.push(d);
+        }
+        j = k + 1;
+    }
+
+    if digits.len() < PHONE_MIN_DIGITS {
+        return None;
+    }
+
+    let written = match (digits.len(), locale.day_first) {
+        (10, false) => format!("{}-{}-{}", &digits[..3], &digits[3..6], &digits[6..]),
+        (7, false) => format!("{}-{}", &digits[..3], &digits[3..]),
+        _ => digits,
+    };
+    Some((j - i, written))
+}
+
+/// A run of labels joined by spoken separators ("my dash site dot co dot
+/// uk"), ending in a known top-level domain; returns its length and text
+fn domain(tokens: &[Token], start: usize) -> Option<(usize, String)> {
+    let label = |k: usize| {
+        tokens
+            .get(k)
+            .filter(|t| !t.core.is_empty() && t.core.chars().all(char::is_alphanumeric))
+            .map(Token::lower)
+    };
+
+    let mut written = address_label(tokens, start)?;
+    let mut j = start + 1;
+    let mut best = None;
+
+    while let Some(joiner) = word_at(tokens, j)
+        .and_then(|w| ADDRESS_JOINERS.iter().find(|(name, _)| *name == w))
+        .map(|(_, symbol)| *symbol)
+    {
+        let Some(next) = label(j + 1).filter(|_| contiguous(tokens, start, j + 2)) else {
+            break;
+        };
+        written.push_str(joiner);
+        written.push_str(&next);
+        j += 2;
+        if joiner == "." && TLDS.contains(&next.as_str()) {
+            best = Some((j - start, written.clone()));
+        }
+    }
+
+    best
+}
+
+/// The first label of an address, unless it's a function word
+fn address_label(tokens: &[Token], k: usize) -> Option<String> {
+    let token = tokens.get(k)?;
+    let word = token.lower();
+    (!word.is_empty()
+        && word.chars().all(char::is_alphanumeric)
+        && !NOT_ADDRESS_START.contains(&word.as_str()))
+    .then_some(word)
+}
+
+/// "john dot smith at example dot com"
+///
+/// One everyday word before "at" ("look at google dot com") is only an
+/// address after "email" or "address"; otherwise just the domain is written.
+fn email(tokens: &[Token], i: usize) -> Option<(usize, String)> {
+    let mut local = address_label(tokens, i)?;
+    let mut j = i + 1;
+    let plain_word = common_words::is_common(&local) && !email_cue_before(tokens, i);
+
+    loop {
+        let word = word_at(tokens, j)?;
+        if word == "at" {
+            break;
+        }
+        let (_, symbol) = ADDRESS_JOINERS.iter().find(|(name, _)| *name == word)?;
+        let next = word_at(tokens, j + 1)?;
+        local.push_str(symbol);
+        local.push_str(&next);
+        j += 2;
+    }
+
+    if plain_word && j == i + 1 {
+        return None;
+    }
+    let (len, domain) = domain(tokens, j + 1)?;
+    let end = j + 1 + len;
+    contiguous(tokens, i, end).then(|| (end - i, format!("{}@{}", local, domain)))
+}
+
+/// Whether "email" or "address" was said shortly before, in the same sentence
+fn email_cue_before(tokens: &[Token], i: usize) -> bool {
+    tokens[..i]
+        .iter()
+        .rev()
+        .take(EMAIL_CUE_WINDOW)
+        .take_while(|t| !t.ends_sentence())
+        .any(|t| EMAIL_CUES.contains(&t.lower().as_str()))
+}
+
+/// "w w w dot example dot com slash pricing"
+fn url(tokens: &[Token], i: usize) -> Option<(usize, String)> {
+    let www = (0..3).all(|k| word_at(tokens, i + k).as_deref() == Some("w"))
+        && word_at(tokens, i + 3).as_deref() == Some("dot");
+    let start = if www { i + 4 } else { i };
+
+    let (len, host) = domain(tokens, start)?;
+    let mut end = start + len;
+    let mut written = if www { format!("www.{}", host) } else { host };
+
+    while word_at(tokens, end).as_deref() == Some("slash") && contiguous(tokens, i, end + 2) {
+        let Some(segment) = word_at(tokens, end + 1) else {
+            break;
+        };
+        written.push('/');
+        written.push_str(&segment);
+        end += 2;
+    }
+
+    contiguous(tokens, i, end).then(|| (end - i, written))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_spoken_forms() {
+        let us = Locale::new("en", None);
+        let cases: &[(&str, &str)] = &[
+            // Numbers
+            ("I have twenty five apples", "I have 25 apples"),
+            ("five people came", "five people came"),
+            ("one hundred and five", "105"),
+            ("three point five", "3.5"),
+            (
+                "at one point two people left",
+                "at one point two people left",
+            ),
+            ("add one point five cups", "add 1.5 cups"),
+            ("two million", "2,000,000"),
+            ("the twenty first floor", "the 21st floor"),
+            ("ten twenty dollar bills", "10 $20 bills"),
+            // Years
+            ("born in nineteen eighty four", "born in 1984"),
+            ("since twenty twenty five", "since 2025"),
+            ("back in twenty oh five", "back in 2005"),
+            ("two thousand five", "2005"),
+            // Money and percentages
+            ("twenty five dollars", "$25"),
+            ("three dollars and fifty cents", "$3.50"),
+            ("three point five million dollars", "$3.5 million"),
+            ("ten percent", "10%"),
+            ("buy two pounds of beef", "buy two pounds of beef"),
+            ("he weighs two hundred pounds", "he weighs 200 pounds"),
+            ("it costs five pounds", "it costs £5"),
+            ("five pounds and fifty pence", "£5.50"),
+            // Dates
+            ("on march third", "on March 3"),
+            ("on March twenty", "on March 20"),
+            ("the third of March", "March 3"),
+            ("due March third twenty twenty four", "due March 3, 2024"),
+            ("by May fifth", "by May 5"),
+            ("by may fifth", "by may fifth"),
+            ("march twenty soldiers", "march 20 soldiers"),
+            ("May I have ten", "May I have 10"),
+            ("they may twenty times", "they may 20 times"),
+            // Times
+            ("at three thirty pm", "at 3:30 PM"),
+            ("at three thirty", "at 3:30"),
+            ("seven o'clock", "7 o'clock"),
+            // Phone numbers
+            ("five five five one two three four", "555-1234"),
+            (
+                "call five five five double one two three four five six",
+                "call 555-112-3456",
+            ),
+            // Emails and web addresses
+            (
+                "john dot smith at example dot com",
+                "john.smith@example.com",
+            ),
+            (
+                "write to jane at example dot com",
+                "write to jane@example.com",
+            ),
+            (
+                "my email is support at example dot com",
+                "my email is support@example.com",
+            ),
+            ("look at google dot com", "look at google.com"),
+            ("the site at example dot com", "the site at example.com"),
+            ("meet me at example dot com", "meet me at example.com"),
+            (
+                "w w w dot example dot com slash pricing",
+                "www.example.com/pricing",
+            ),
+            ("", ""),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(normalize(input, "en", &us), *expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn follows_the_locale() {
+        let gb = Locale::parse("en-GB");
+        assert_eq!(normalize("on march third", "en", &gb), "on 3 March");
+        assert_eq!(normalize("twenty pounds", "en", &gb), "£20");
+        assert_eq!(
+            normalize("twenty pounds", "en", &Locale::parse("en-US")),
+            "20 pounds"
+        );
+        assert_eq!(
+            normalize("she weighs twenty pounds", "en", &gb),
+            "she weighs 20 pounds"
+        );
+        let de = Locale::parse("de");
+        assert_eq!(normalize("twenty five euros", "en", &de), "25 €");
+        assert_eq!(
+            normalize("twelve thousand five hundred", "en", &de),
+            "12.500"
+        );
+        // Other languages come back from the provider as digits already
+        assert_eq!(normalize("veinte", "es", &de), "veinte");
+    }
+}
@@ -0,0 +1,222 @@
+use super::tokens;
+use crate::audio::processing::SpeechTiming;
+use crate::transcription::WordTiming;
+
+/// Pause that starts a new paragraph unless the user sets their own
+pub const DEFAULT_PARAGRAPH_PAUSE_MS: u64 = 2000;
+
+/// Pause that ends a sentence the provider left unpunctuated
+const SENTENCE_PAUSE_MS: u64 = 800;
+
+/// Where in a dictation the speaker paused
+pub enum Timing<'a> {
+    /// The provider's word timestamps
+    Words(&'a [WordTiming]),
+    /// Silences found in the audio, for providers without word timing
+    Speech(&'a SpeechTiming),
+}
+
+/// Shortest pause that can add a break, for silence detection
+pub fn min_pause_ms(paragraph_ms: u64) -> u64 {
+    SENTENCE_PAUSE_MS.min(paragraph_ms)
+}
+
+/// Start a paragraph at each pause of `paragraph_ms` or longer, and end the
+/// sentence at shorter pauses the provider didn't punctuate
+///
+/// A paragraph break replaces a comma or semicolon before it with a period.
+/// Sentence breaks only go where the word has no punctuation at all, so a
+/// pause after "however," stays a comma. Silences are placed in the text by
+/// speaking time, assuming an even pace, so word timestamps are preferred.
+pub fn insert_breaks(text: &str, timing: &Timing, paragraph_ms: u64) -> String {
+    let mut tokens = tokens::tokenize(text);
+    let words: Vec<usize> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| !t.core.is_empty())
+        .map(|(i, _)| i)
+        .collect();
+    if words.len() < 2 {
+        return text.to_string();
+    }
+
+    let sentence_ms = min_pause_ms(paragraph_ms);
+    for (after, ms) in gaps(timing, words.len()) {
+        if after + 1 >= words.len() || ms < sentence_ms {
+            continue;
+        }
+        let (word, next) = (words[after], words[after + 1]);
+        if tokens[next].lead.contains('\n') {
+            continue;
+        }
+
+        let paragraph = ms >= paragraph_ms;
+        let token = &mut tokens[word];
+        if !token.ends_sentence() {
+            let pause_only = token.trail.chars().all(|c| matches!(c, ',' | ';' | ':'));
+            if !pause_only || (!paragraph && !token.trail.is_empty()) {
+                continue;
+            }
+            token.trail = ".".to_string();
+            tokens[next].capitalize();
+        }
+        if paragraph {
+            tokens[next].lead.insert_str(0, "\n\n");
+        }
+    }
+    tokens::join(&tokens)
+}
+
+/// Pauses as (index of the word before it, length) in transcript words
+fn gaps(timing: &Timing, word_count: usize) -> Vec<(usize, u64)> {
+    match timing {
+        Timing::Words(timed) => timed
+            .windows(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                let ms = pair[1].start_ms.saturating_sub(pair[0].end_ms);
+                (scale(i, timed.len(), word_count), ms)
+            })
+            .collect(),
+        Timing::Speech(speech) => {
+            let speaking_ms = speech.speaking_ms();
+            if speaking_ms == 0 {
+                return Vec::new();
+            }
+            let mut silent_before = 0;
+            speech
+                .pauses
+                .iter()
+                .filter_map(|pause| {
+                    let spoken = pause.start_ms - speech.start_ms - silent_before;
+                    silent_before += pause.duration_ms();
+                    let words_before =
+                        (spoken as f64 / speaking_ms as f64 * word_count as f64).round() as usize;
+                    words_before
+                        .checked_sub(1)
+                        .map(|after| (after, pause.duration_ms()))
+                })
+                .collect()
+        }
+    }
+}
+
+/// Map timed word `i` onto the transcript's words, which can number
+/// differently when the provider splits numbers or hyphenated words its own
+/// way
+fn scale(i: usize, from: usize, to: usize) -> usize {
+    if from == to {
+        return i;
+    }
+    let end = ((i + 1) as f64 * to as f64 / from as f64).round() as usize;
+    end.saturating_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::processing::Pause;
+
+    /// Word timings with the given silence, in ms, after each word
+    fn timed(words: &str, gaps: &[u64]) -> Vec<WordTiming> {
+        let mut at = 0;
+        words
+            .split_whitespace()
+            .enumerate()
+            .map(|(i, word)| {
+                let timing = WordTiming {
+                    word: word.to_string(),
+                    start_ms: at,
+                    end_ms: at + 300,
+                };
+                at += 300 + gaps.get(i).copied().unwrap_or(100);
+                timing
+            })
+            .collect()
+    }
+
+    #[test]
+    fn breaks_at_word_pauses() {
+        let cases: &[(&str, &[u64], &str)] = &[
+            (
+                "hello there how are you",
+                &[100, 2500],
+                "hello there.\n\nHow are you",
+            ),
+            (
+                "hello there how are you",
+                &[100, 1000],
+                "hello there. How are you",
+            ),
+            (
+                "hello there how are you",
+                &[100, 500],
+                "hello there how are you",
+            ),
+            (
+                "well, however, we left",
+                &[100, 1000],
+                "well, however, we left",
+            ),
+            (
+                "first item, second item",
+                &[100, 2500],
+                "first item.\n\nSecond item",
+            ),
+            ("Done. Next one", &[2500], "Done.\n\nNext one"),
+            ("Done.\nNext one", &[2500], "Done.\nNext one"),
+            ("hello", &[2500], "hello"),
+        ];
+        for (text, gaps, expected) in cases {
+            let words = timed(text, gaps);
+            assert_eq!(
+                insert_breaks(text, &Timing::Words(&words), DEFAULT_PARAGRAPH_PAUSE_MS),
+                *expected,
+                "input: {:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn maps_timed_words_onto_the_transcript() {
+        // The provider timed "twenty" and "five" separately
+        let words = timed(
+            "it costs twenty five dollars now",
+            &[100, 100, 100, 100, 2500],
+        );
+        assert_eq!(
+            insert_breaks("it costs 25 dollars now", &Timing::Words(&words), 2000),
+            "it costs 25 dollars.\n\nNow"
+        );
+        assert_eq!(scale(4, 6, 5), 3);
+        assert_eq!(scale(0, 6, 5), 0);
+        assert_eq!(scale(2, 3, 3), 2);
+    }
+
+    #[test]
+    fn breaks_at_silences() {
+        let speech = SpeechTiming {
+            start_ms: 500,
+            end_ms: 5500,
+            pauses: vec![Pause {
+                start_ms: 1500,
+                end_ms: 4000,
+            }],
+        };
+        assert_eq!(
+            insert_breaks("one two three four", &Timing::Speech(&speech), 2000),
+            "one two.\n\nThree four"
+        );
+        assert_eq!(
+            insert_breaks("one two three four", &Timing::Speech(&speech), 3000),
+            "one two. Three four"
+        );
+
+        let silent = SpeechTiming::default();
+        assert_eq!(
+            insert_breaks("one two", &Timing::Speech(&silent), 2000),
+            "one two"
+        );
+    }
+}
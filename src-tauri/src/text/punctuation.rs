@@ -0,0 +1,538 @@
+use super::tokens::{self, Token};
+use crate::storage::database::{self, DatabaseError};
+use serde::{Deserialize, Serialize};
+
+/// How an inserted symbol is spaced against its neighbours
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Spacing {
+    /// Joined to the previous word: `,` `.` `)`
+    AttachLeft,
+    /// Joined to the next word: `(` and opening quotes
+    AttachRight,
+    /// Joined on both sides: `-` `/`
+    Tight,
+    /// Spaced on both sides: `—` `&`
+    Spaced,
+    /// A line or paragraph break; the next word starts a sentence
+    Break,
+}
+
+impl Spacing {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Spacing::AttachLeft => "attach_left",
+            Spacing::AttachRight => "attach_right",
+            Spacing::Tight => "tight",
+            Spacing::Spaced => "spaced",
+            Spacing::Break => "break",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "attach_right" => Spacing::AttachRight,
+            "tight" => Spacing::Tight,
+            "spaced" => Spacing::Spaced,
+            "break" => Spacing::Break,
+            _ => Spacing::AttachLeft,
+        }
+    }
+}
+
+use Spacing::{AttachLeft, AttachRight, Break, Spaced, Tight};
+
+const EN_COMMANDS: &[(&str, &str, Spacing)] = &[
+    ("comma", ",", AttachLeft),
+    ("period", ".", AttachLeft),
+    ("full stop", ".", AttachLeft),
+    ("question mark", "?", AttachLeft),
+    ("exclamation point", "!", AttachLeft),
+    ("exclamation mark", "!", AttachLeft),
+    ("colon", ":", AttachLeft),
+    ("semicolon", ";", AttachLeft),
+    ("ellipsis", "...", AttachLeft),
+    ("dot dot dot", "...", AttachLeft),
+    ("dash", "—", Spaced),
+    ("hyphen", "-", Tight),
+    ("slash", "/", Tight),
+    ("ampersand", "&", Spaced),
+    ("open quote", "\"", AttachRight),
+    ("begin quote", "\"", AttachRight),
+    ("close quote", "\"", AttachLeft),
+    ("end quote", "\"", AttachLeft),
+    ("unquote", "\"", AttachLeft),
+    ("open paren", "(", AttachRight),
+    ("open parenthesis", "(", AttachRight),
+    ("close paren", ")", AttachLeft),
+    ("close parenthesis", ")", AttachLeft),
+    ("new line", "\n", Break),
+    ("newline", "\n", Break),
+    ("new paragraph", "\n\n", Break),
+];
+
+const ES_COMMANDS: &[(&str, &str, Spacing)] = &[
+    ("coma", ",", AttachLeft),
+    ("punto y coma", ";", AttachLeft),
+    ("punto", ".", AttachLeft),
+    ("dos puntos", ":", AttachLeft),
+    ("signo de interrogación", "?", AttachLeft),
+    ("signo de exclamación", "!", AttachLeft),
+    ("abrir comillas", "\"", AttachRight),
+    ("cerrar comillas", "\"", AttachLeft),
+    ("abrir paréntesis", "(", AttachRight),
+    ("cerrar paréntesis", ")", AttachLeft),
+    ("nueva línea", "\n", Break),
+    ("nuevo párrafo", "\n\n", Break),
+];
+
+const FR_COMMANDS: &[(&str, &str, Spacing)] = &[
+    ("virgule", ",", AttachLeft),
+    ("point virgule", ";", AttachLeft),
+    ("point d'interrogation", "?", AttachLeft),
+    ("point d'exclamation", "!", AttachLeft),
+    ("deux points", ":", AttachLeft),
+    ("point", ".", AttachLeft),
+    ("ouvrez les guillemets", "«", AttachRight),
+    ("fermez les guillemets", "»", AttachLeft),
+    ("ouvrez la parenthèse", "(", AttachRight),
+    ("fermez la parenthèse", ")", AttachLeft),
+    ("à la ligne", "\n", Break),
+    ("nouveau paragraphe", "\n\n", Break),
+];
+
+const DE_COMMANDS: &[(&str, &str, Spacing)] = &[
+    ("komma", ",", AttachLeft),
+    ("punkt", ".", AttachLeft),
+    ("fragezeichen", "?", AttachLeft),
+    ("ausrufezeichen", "!", AttachLeft),
+    ("doppelpunkt", ":", AttachLeft),
+    ("semikolon", ";", AttachLeft),
+    ("anführungszeichen auf", "„", AttachRight),
+    ("anführungszeichen zu", "“", AttachLeft),
+    ("klammer auf", "(", AttachRight),
+    ("klammer zu", ")", AttachLeft),
+    ("neue zeile", "\n", Break),
+    ("neuer absatz", "\n\n", Break),
+];
+
+/// Commands that are also everyday words ("the trial period", "a colon
+/// exam"), which need a pause before them or to end the sentence
+const AMBIGUOUS: &[&str] = &["period", "colon", "dash", "punto", "point", "punkt"];
+
+/// Phrases that introduce a command word as a literal ("the word comma")
+fn literal_prefixes(language: &str) -> &'static [&'static str] {
+    match language {
+        "en" => &["the word", "the words", "the phrase"],
+        "es" => &["la palabra"],
+        "fr" => &["le mot"],
+        "de" => &["das wort"],
+        _ => &[],
+    }
+}
+
+const OPEN_QUOTES: &[char] = &['"', '“', '«', '„'];
+const CLOSE_QUOTES: &[char] = &['"', '”', '»', '“'];
+
+/// A spoken punctuation command as shown to and edited by the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PunctuationCommand {
+    pub language: String,
+    pub phrase: String,
+    pub symbol: String,
+    pub spacing: Spacing,
+    pub enabled: bool,
+    /// Ships with REDE rather than added by the user
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+/// Built-in punctuation commands for a language
+pub fn builtin(language: &str) -> Vec<PunctuationCommand> {
+    let commands = match language {
+        "en" => EN_COMMANDS,
+        "es" => ES_COMMANDS,
+        "fr" => FR_COMMANDS,
+        "de" => DE_COMMANDS,
+        _ => &[],
+    };
+    commands
+        .iter()
+        .map(|(phrase, symbol, spacing)| PunctuationCommand {
+            language: language.to_string(),
+            phrase: phrase.to_string(),
+            symbol: symbol.to_string(),
+            spacing: *spacing,
+            enabled: true,
+            builtin: true,
+        })
+        .collect()
+}
+
+/// The effective command vocabulary for a language: built-ins merged with
+/// user edits
+pub fn list(language: &str) -> Result<Vec<PunctuationCommand>, DatabaseError> {
+    let mut commands = builtin(language);
+
+    let conn = database::connect()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT phrase, symbol, spacing, enabled FROM punctuation_commands
+             WHERE language = ?1",
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    let overrides = stmt
+        .query_map([language], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, bool>(3)?,
+            ))
+        })
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    for (phrase, symbol, spacing, enabled) in overrides {
+        let spacing = Spacing::parse(&spacing);
+        match commands
+            .iter_mut()
+            .find(|c| c.phrase.eq_ignore_ascii_case(&phrase))
+        {
+            Some(command) => {
+                command.symbol = symbol;
+                command.spacing = spacing;
+                command.enabled = enabled;
+            }
+            None => commands.push(PunctuationCommand {
+                language: language.to_string(),
+                phrase,
+                symbol,
+                spacing,
+                enabled,
+                builtin: false,
+            }),
+        }
+    }
+
+    Ok(commands)
+}
+
+/// Add or update a command; disabling a built-in stops it firing
+pub fn save(command: &PunctuationCommand) -> Result<(), DatabaseError> {
+    let conn = database::connect()?;
+    conn.execute(
+        "INSERT INTO punctuation_commands (id, language, phrase, symbol, spacing, enabled)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(language, phrase) DO UPDATE SET
+            symbol = excluded.symbol,
+            spacing = excluded.spacing,
+            enabled = excluded.enabled",
+        rusqlite::params![
+            uuid::Uuid::new_v4().to_string(),
+            command.language,
+            command.phrase.trim().to_lowercase(),
+            command.symbol,
+            command.spacing.as_str(),
+            command.enabled,
+        ],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(())
+}
+
+/// Drop a user edit, restoring the built-in behaviour for that phrase
+pub fn delete(language: &str, phrase: &str) -> Result<(), DatabaseError> {
+    let conn = database::connect()?;
+    conn.execute(
+        "DELETE FROM punctuation_commands WHERE language = ?1 AND phrase = ?2",
+        [language, &phrase.trim().to_lowercase()],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(())
+}
+
+/// Compiled commands for one language, longest first so "question mark"
+/// wins over a user command for "question"
+#[derive(Debug, Clone, Default)]
+pub struct CommandList {
+    commands: Vec<(Vec<String>, String, Spacing)>,
+    literal_prefixes: Vec<Vec<String>>,
+}
+
+impl CommandList {
+    pub fn new(language: &str, commands: &[PunctuationCommand]) -> Self {
+        let mut compiled: Vec<(Vec<String>, String, Spacing)> = commands
+            .iter()
+            .filter(|c| c.enabled)
+            .filter_map(|c| {
+                let parts: Vec<String> =
+                    c.phrase.split_whitespace().map(str::to_lowercase).collect();
+                (!parts.is_empty()).then(|| (parts, c.symbol.clone(), c.spacing))
+            })
+            .collect();
+        compiled.sort_by_key(|(parts, _, _)| std::cmp::Reverse(parts.len()));
+
+        Self {
+            commands: compiled,
+            literal_prefixes: literal_prefixes(language)
+                .iter()
+                .map(|p| p.split_whitespace().map(str::to_string).collect())
+                .collect(),
+        }
+    }
+
+    /// Load the effective vocabulary for a language
+    pub fn load(language: &str) -> Result<Self, DatabaseError> {
+        Ok(Self::new(language, &list(language)?))
+    }
+
+    /// Length, symbol and spacing of the command starting at `tokens[start]`
+    fn match_at(&self, tokens: &[Token], start: usize) -> Option<(usize, &str, Spacing)> {
+        self.commands.iter().find_map(|(parts, symbol, spacing)| {
+            let end = start + parts.len();
+            if end > tokens.len() {
+                return None;
+            }
+            if parts.len() == 1 && AMBIGUOUS.contains(&parts[0].as_str()) {
+                let pause_before =
+                    start > 0 && tokens[start - 1].has_pause() || tokens[start].lead.contains('\n');
+                let ends_sentence = end == tokens.len() || tokens[start].ends_sentence();
+                if !pause_before && !ends_sentence {
+                    return None;
+                }
+            }
+            let matches =
+                tokens[start..end]
+                    .iter()
+                    .zip(parts)
+                    .enumerate()
+                    .all(|(i, (token, part))| {
+                        // "question, mark" is two words, not a command
+                        let inner_ok = i == parts.len() - 1 || token.trail.is_empty();
+                        inner_ok && token.lower() == *part
+                    });
+            matches.then_some((parts.len(), symbol.as_str(), *spacing))
+        })
+    }
+
+    /// Whether the words before `start` mark what follows as literal
+    fn is_literal(&self, tokens: &[Token], start: usize) -> bool {
+        self.literal_prefixes.iter().any(|prefix| {
+            start >= prefix.len()
+                && tokens[start - prefix.len()..start]
+                    .iter()
+                    .zip(prefix)
+                    .all(|(token, word)| token.trail.is_empty() && token.lower() == *word)
+        })
+    }
+}
+
+/// Builds the output text, tracking how the next piece should be spaced
+#[derive(Default)]
+struct Writer {
+    out: String,
+    space_before_next: bool,
+    capitalize_next: bool,
+}
+
+impl Writer {
+    fn word(&mut self, token: &Token) {
+        let mut token = token.clone();
+        if self.capitalize_next && !token.core.is_empty() {
+            token.capitalize();
+            self.capitalize_next = false;
+        }
+        if token.lead.starts_with('\n') {
+            self.trim_spaces();
+        } else {
+            self.separate();
+        }
+        self.out.push_str(&token.to_string());
+        self.space_before_next = true;
+    }
+
+    fn symbol(&mut self, symbol: &str, spacing: Spacing) {
+        match spacing {
+            AttachLeft => {
+                self.trim_spaces();
+                // The provider often punctuates around a spoken command
+                // ("Hello, comma") so replace its mark rather than doubling up
+                if symbol.chars().all(|c| ",;:.!?…".contains(c)) {
+                    let kept = self
+                        .out
+                        .trim_end_matches([',', ';', ':', '.', '!', '?'])
+                        .len();
+                    self.out.truncate(kept);
+                }
+                self.out.push_str(symbol);
+                self.space_before_next = true;
+                if matches!(symbol, "." | "!" | "?") {
+                    self.capitalize_next = true;
+                }
+            }
+            AttachRight => {
+                self.separate();
+                self.out.push_str(symbol);
+                self.space_before_next = false;
+            }
+            Tight => {
+                self.trim_spaces();
+                self.out.push_str(symbol);
+                self.space_before_next = false;
+            }
+            Spaced => {
+                self.trim_spaces();
+                // "well, dash maybe": the dash takes the pause's place
+                if self.out.ends_with(',') {
+                    self.out.pop();
+                }
+                if !self.out.is_empty() && !self.out.ends_with('\n') {
+                    self.out.push(' ');
+                }
+                self.out.push_str(symbol);
+                self.space_before_next = true;
+            }
+            Break => {
+                self.trim_spaces();
+                self.out.push_str(symbol);
+                self.space_before_next = false;
+                self.capitalize_next = true;
+            }
+        }
+    }
+
+    fn separate(&mut self) {
+        if self.space_before_next && !self.out.is_empty() {
+            self.out.push(' ');
+        }
+    }
+
+    fn trim_spaces(&mut self) {
+        let kept = self.out.trim_end_matches(' ').len();
+        self.out.truncate(kept);
+    }
+}
+
+/// Replace spoken punctuation commands with their symbols
+///
+/// Words inside quotes, whether the provider transcribed them or they were
+/// spoken ("open quote" ... "end quote"), and commands introduced as
+/// literals ("the word comma"), are left as spoken. Commands that are also
+/// ordinary words only count after a pause or at the end of a sentence.
+pub fn apply(text: &str, commands: &CommandList) -> String {
+    let tokens = tokens::tokenize(text);
+    let mut writer = Writer::default();
+    let mut in_quote = false;
+    let mut in_spoken_quote = false;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = &tokens[i];
+
+        if in_spoken_quote {
+            match commands.match_at(&tokens, i) {
+                Some((len, symbol, spacing)) if is_close_quote(symbol, spacing) => {
+                    writer.symbol(symbol, AttachLeft);
+                    in_spoken_quote = false;
+                    i += len;
+                }
+                _ => {
+                    writer.word(token);
+                    i += 1;
+                }
+            }
+            continue;
+        }
+
+        if !in_quote && token.lead.contains(OPEN_QUOTES) {
+            in_quote = true;
+        }
+        if in_quote {
+            writer.word(token);
+            if token.trail.contains(CLOSE_QUOTES) {
+                in_quote = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        if let Some((len, symbol, spacing)) = commands.match_at(&tokens, i) {
+            if !commands.is_literal(&tokens, i) {
+                writer.symbol(symbol, spacing);
+                // An open quote that's never closed is just a symbol
+                in_spoken_quote = spacing == AttachRight
+                    && symbol.chars().all(|c| OPEN_QUOTES.contains(&c))
+                    && closed_after(&tokens, i + len, commands);
+                i += len;
+                continue;
+            }
+        }
+
+        writer.word(token);
+        i += 1;
+    }
+
+    writer.out.trim_end_matches(' ').to_string()
+}
+
+fn is_close_quote(symbol: &str, spacing: Spacing) -> bool {
+    spacing == AttachLeft && symbol.chars().all(|c| CLOSE_QUOTES.contains(&c))
+}
+
+/// Whether a close-quote command follows `tokens[start]`
+fn closed_after(tokens: &[Token], start: usize, commands: &CommandList) -> bool {
+    (start..tokens.len()).any(|k| {
+        commands
+            .match_at(tokens, k)
+            .is_some_and(|(_, symbol, spacing)| is_close_quote(symbol, spacing))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_spoken_punctuation() {
+        let commands = CommandList::new("en", &builtin("en"));
+        let cases: &[(&str, &str)] = &[
+            (
+                "hello comma how are you question mark",
+                "hello, how are you?",
+            ),
+            ("That's all period", "That's all."),
+            ("That's all, period. See you", "That's all. See you"),
+            (
+                "Agenda, colon budget and hiring",
+                "Agenda: budget and hiring",
+            ),
+            ("well, dash maybe not", "well — maybe not"),
+            ("first line new line second line", "first line\nSecond line"),
+            ("rock ampersand roll", "rock & roll"),
+            (
+                "He said open quote stop comma now end quote and left",
+                "He said \"stop comma now\" and left",
+            ),
+            (
+                "open quote hello comma world period and more comma here",
+                "\"hello, world period and more, here",
+            ),
+            (
+                "She wrote \"dash, colon\" on the board",
+                "She wrote \"dash, colon\" on the board",
+            ),
+            ("Type the word comma here", "Type the word comma here"),
+            (
+                "The trial period ends Friday",
+                "The trial period ends Friday",
+            ),
+            ("I have a colon exam today", "I have a colon exam today"),
+            ("We had a dash of salt", "We had a dash of salt"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(apply(input, &commands), *expected, "input: {:?}", input);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+/// Levenshtein distance between two strings, counted in characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Phonetic key for a word using the original Metaphone rules
+///
+/// Words that sound alike share a key ("Shivon" and "shivawn" are both
+/// "XFN"). Non-ASCII letters are dropped.
+pub fn metaphone(word: &str) -> String {
+    let letters: Vec<char> = word
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let at = |i: usize| letters.get(i).copied().unwrap_or('\0');
+    let is_vowel = |c: char| "AEIOU".contains(c) && c != '\0';
+    let mut key = String::new();
+
+    let mut i = match (at(0), at(1)) {
+        ('A', 'E') | ('G', 'N') | ('K', 'N') | ('P', 'N') | ('W', 'R') => 1,
+        _ => 0,
+    };
+    if at(0) == 'X' {
+        key.push('S');
+        i = 1;
+    } else if at(0) == 'W' && at(1) == 'H' {
+        key.push('W');
+        i = 2;
+    }
+
+    while i < letters.len() {
+        let c = letters[i];
+        let prev = if i > 0 { at(i - 1) } else { '\0' };
+        let next = at(i + 1);
+
+        if c == prev && c != 'C' {
+            i += 1;
+            continue;
+        }
+
+        match c {
+            'A' | 'E' | 'I' | 'O' | 'U' => {
+                if i == 0 {
+                    key.push(c);
+                }
+            }
+            'B' => {
+                if !(prev == 'M' && i + 1 == letters.len()) {
+                    key.push('B');
+                }
+            }
+            'C' => {
+                if next == 'I' && at(i + 2) == 'A' {
+                    key.push('X');
+                } else if next == 'H' {
+                    key.push(if prev == 'S' { 'K' } else { 'X' });
+                    i += 1;
+                } else if "IEY".contains(next) && next != '\0' {
+                    if prev != 'S' {
+                        key.push('S');
+                    }
+                } else {
+                    key.push('K');
+                }
+            }
+            'D' => {
+                if next == 'G' && "EIY".contains(at(i + 2)) && at(i + 2) != '\0' {
+                    key.push('J');
+                    i += 1;
+                } else {
+                    key.push('T');
+                }
+            }
+            'G' => {
+                let silent_gh = next == 'H' && !is_vowel(at(i + 2));
+                let silent_gn = next == 'N' && (i + 2 == letters.len() || at(i + 2) == 'E');
+                if silent_gh || silent_gn {
+                    // Silent, as in "night" and "sign"
+                } else if "IEY".contains(next) && next != '\0' {
+                    key.push('J');
+                } else {
+                    key.push('K');
+                }
+            }
+            'H' => {
+                if is_vowel(next) && !"CSPTG".contains(prev) {
+                    key.push('H');
+                }
+            }
+            'K' => {
+                if prev != 'C' {
+                    key.push('K');
+                }
+            }
+            'P' => {
+                if next == 'H' {
+                    key.push('F');
+                    i += 1;
+                } else {
+                    key.push('P');
+                }
+            }
+            'Q' => key.push('K'),
+            'S' => {
+                if next == 'H' {
+                    key.push('X');
+                    i += 1;
+                } else if next == 'I' && "OA".contains(at(i + 2)) && at(i + 2) != '\0' {
+                    key.push('X');
+                } else {
+                    key.push('S');
+                }
+            }
+            'T' => {
+                if next == 'I' && "OA".contains(at(i + 2)) && at(i + 2) != '\0' {
+                    key.push('X');
+                } else if next == 'H' {
+                    key.push('0');
+                    i += 1;
+                } else if !(next == 'C' && at(i + 2) == 'H') {
+                    key.push('T');
+                }
+            }
+            'V' => key.push('F'),
+            'W' | 'Y' => {
+                if is_vowel(next) {
+                    key.push(c);
+                }
+            }
+            'X' => key.push_str("KS"),
+            'Z' => key.push('S'),
+            _ => key.push(c),
+        }
+        i += 1;
+    }
+
+    key
+}
+
+/// Similarity in 0..=1 from edit distance, relative to the longer string
+pub fn ratio(a: &str, b: &str) -> f32 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(a, b) as f32 / longest as f32
+}
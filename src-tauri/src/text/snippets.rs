@@ -0,0 +1,285 @@
+use super::common_words;
+use super::similarity;
+use super::tokens::{self, Token};
+use crate::accessibility::clipboard;
+use crate::storage::snippets::{Snippet, VariableKind};
+use serde::{Deserialize, Serialize};
+
+/// Shortest trigger, in characters, that may match approximately
+const FUZZY_MIN_CHARS: usize = 5;
+
+/// Characters of trigger per allowed edit when matching approximately
+const FUZZY_CHARS_PER_EDIT: usize = 5;
+
+/// A trigger found in a transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnippetMatch {
+    pub snippet_id: String,
+    pub trigger: String,
+    /// The words as transcribed
+    pub spoken: String,
+    /// Matched approximately rather than exactly
+    pub fuzzy: bool,
+    /// The rendered content written in its place
+    #[serde(default)]
+    pub expansion: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnippetExpansion {
+    pub text: String,
+    pub matches: Vec<SnippetMatch>,
+}
+
+/// Replace spoken snippet triggers with their rendered content
+///
+/// With `fuzzy`, a trigger also matches words within a small edit distance
+/// ("adress" for "address") when no exact trigger does.
+pub fn expand(text: &str, snippets: &[Snippet], fuzzy: bool) -> SnippetExpansion {
+    let tokens = tokens::tokenize(text);
+    let mut triggers: Vec<(&Snippet, Vec<&str>)> = snippets
+        .iter()
+        .filter(|s| s.enabled)
+        .map(|s| (s, s.trigger.split_whitespace().collect::<Vec<_>>()))
+        .filter(|(_, words)| !words.is_empty())
+        .collect();
+    triggers.sort_by_key(|(_, words)| std::cmp::Reverse(words.len()));
+
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut matches = Vec::new();
+    let mut clipboard_text = None;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let exact = triggers
+            .iter()
+            .find(|(snippet, words)| matches_exactly(&tokens, i, words, snippet.case_sensitive));
+        let found = match exact {
+            Some((snippet, words)) => Some((*snippet, words.len(), false)),
+            None if fuzzy => matches_fuzzily(&tokens, i, &triggers),
+            None => None,
+        };
+
+        match found {
+            Some((snippet, len, is_fuzzy)) => {
+                let window = &tokens[i..i + len];
+                let expansion = render(snippet, &mut clipboard_text);
+                matches.push(SnippetMatch {
+                    snippet_id: snippet.id.clone(),
+                    trigger: snippet.trigger.clone(),
+                    spoken: tokens::join(window),
+                    fuzzy: is_fuzzy,
+                    expansion: expansion.clone(),
+                });
+                out.push(Token {
+                    lead: window[0].lead.clone(),
+                    core: expansion,
+                    trail: window[len - 1].trail.clone(),
+                });
+                i += len;
+            }
+            None => {
+                out.push(tokens[i].clone());
+                i += 1;
+            }
+        }
+    }
+
+    SnippetExpansion {
+        text: tokens::join(&out),
+        matches,
+    }
+}
+
+/// The trigger's words at `tokens[start]`, with no punctuation between them
+fn window(tokens: &[Token], start: usize, len: usize) -> Option<&[Token]> {
+    let window = tokens.get(start..start + len)?;
+    let contiguous = window
+        .iter()
+        .enumerate()
+        .all(|(k, t)| (k == 0 || t.lead.is_empty()) && (k == len - 1 || t.trail.is_empty()));
+    contiguous.then_some(window)
+}
+
+fn matches_exactly(tokens: &[Token], start: usize, words: &[&str], case_sensitive: bool) -> bool {
+    window(tokens, start, words.len()).is_some_and(|window| {
+        window.iter().zip(words).all(|(token, word)| {
+            if case_sensitive {
+                token.core == *word
+            } else {
+                token.lower() == word.to_lowercase()
+            }
+        })
+    })
+}
+
+/// The closest case-insensitive trigger within its edit budget
+///
+/// Words are compared one at a time: short ones must be heard exactly and
+/// longer ones may be off by an edit per `FUZZY_CHARS_PER_EDIT` characters,
+/// unless what was heard is an everyday word in its own right ("my dress"
+/// isn't a misheard "my address").
+fn matches_fuzzily<'a>(
+    tokens: &[Token],
+    start: usize,
+    triggers: &[(&'a Snippet, Vec<&str>)],
+) -> Option<(&'a Snippet, usize, bool)> {
+    triggers
+        .iter()
+        .filter(|(snippet, _)| !snippet.case_sensitive)
+        .filter_map(|(snippet, words)| {
+            if snippet.trigger.trim().chars().count() < FUZZY_MIN_CHARS {
+                return None;
+            }
+            let window = window(tokens, start, words.len())?;
+            let mut distance = 0;
+            for (token, word) in window.iter().zip(words) {
+                let heard = token.lower();
+                let word = word.to_lowercase();
+                if heard == word {
+                    continue;
+                }
+                let length = word.chars().count();
+                let edits = similarity::edit_distance(&heard, &word);
+                if length < FUZZY_MIN_CHARS
+                    || edits > length / FUZZY_CHARS_PER_EDIT
+                    || common_words::is_common(&heard)
+                {
+                    return None;
+                }
+                distance += edits;
+            }
+            Some((distance, *snippet, words.len()))
+        })
+        .min_by_key(|(distance, _, _)| *distance)
+        .map(|(_, snippet, len)| (snippet, len, true))
+}
+
+/// Fill `{key}` placeholders in a snippet's content
+///
+/// The clipboard is read at most once per expansion pass and shared between
+/// snippets; unknown keys are left as written.
+fn render(snippet: &Snippet, clipboard_text: &mut Option<String>) -> String {
+    let mut content = snippet.content.clone();
+
+    for variable in &snippet.variables {
+        let placeholder = format!("{{{}}}", variable.key);
+        if !content.contains(&placeholder) {
+            continue;
+        }
+        let value = match variable.kind {
+            VariableKind::Date => chrono::Local::now().format("%Y-%m-%d").to_string(),
+            VariableKind::Time => chrono::Local::now().format("%H:%M").to_string(),
+            VariableKind::Datetime => chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+            VariableKind::Random => format!("{:06}", uuid::Uuid::new_v4().as_u128() % 1_000_000),
+            VariableKind::Clipboard => clipboard_text
+                .get_or_insert_with(|| {
+                    clipboard::read().unwrap_or_else(|e| {
+                        log::warn!("Snippet clipboard variable left empty: {}", e);
+                        String::new()
+                    })
+                })
+                .clone(),
+        };
+        content = content.replace(&placeholder, &value);
+    }
+
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::snippets::SnippetVariable;
+
+    fn snippet(trigger: &str, content: &str, case_sensitive: bool) -> Snippet {
+        Snippet {
+            id: trigger.to_string(),
+            trigger: trigger.to_string(),
+            content: content.to_string(),
+            variables: Vec::new(),
+            case_sensitive,
+            enabled: true,
+            usage_count: 0,
+        }
+    }
+
+    fn snippets() -> Vec<Snippet> {
+        vec![
+            snippet("my address", "221B Baker Street", false),
+            snippet("sign off", "Best regards, Sam", false),
+            snippet("signature", "Sam Carter, Acme", false),
+            snippet("ACME", "Acme Corporation", true),
+        ]
+    }
+
+    #[test]
+    fn expands_exact_triggers() {
+        let cases: &[(&str, &str)] = &[
+            (
+                "send it to my address please",
+                "send it to 221B Baker Street please",
+            ),
+            ("My Address.", "221B Baker Street."),
+            ("thanks, sign off", "thanks, Best regards, Sam"),
+            ("ACME called", "Acme Corporation called"),
+            ("acme called", "acme called"),
+            ("my, address", "my, address"),
+            ("sign offer", "sign offer"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                expand(input, &snippets(), false).text,
+                *expected,
+                "input: {:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn expands_misheard_triggers() {
+        let cases: &[(&str, &str)] = &[
+            ("send it to my adress", "send it to 221B Baker Street"),
+            ("add my signiture", "add my Sam Carter, Acme"),
+            ("my dress is red", "my dress is red"),
+            ("my actress is here", "my actress is here"),
+            ("sign of the times", "sign of the times"),
+            ("ACMA called", "ACMA called"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                expand(input, &snippets(), true).text,
+                *expected,
+                "input: {:?}",
+                input
+            );
+        }
+        assert_eq!(expand("my adress", &snippets(), false).text, "my adress");
+
+        let expansion = expand("send it to my adress", &snippets(), true);
+        assert_eq!(expansion.matches.len(), 1);
+        assert!(expansion.matches[0].fuzzy);
+        assert_eq!(expansion.matches[0].spoken, "my adress");
+        assert_eq!(expansion.matches[0].expansion, "221B Baker Street");
+    }
+
+    #[test]
+    fn renders_variables() {
+        let mut today = snippet("stamp", "Seen {date} ({ref}, {missing})", false);
+        today.variables = vec![
+            SnippetVariable {
+                key: "date".to_string(),
+                kind: VariableKind::Date,
+            },
+            SnippetVariable {
+                key: "ref".to_string(),
+                kind: VariableKind::Random,
+            },
+        ];
+        let text = expand("stamp", &[today], false).text;
+        let pattern =
+            regex::Regex::new(r"^Seen \d{4}-\d{2}-\d{2} \(\d{6}, \{missing\}\)$").unwrap();
+        assert!(pattern.is_match(&text), "rendered: {:?}", text);
+    }
+}
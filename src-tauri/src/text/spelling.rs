@@ -0,0 +1,344 @@
+use super::tokens;
+
+/// Phrases that switch a single dictation into spelling ("spell alpha seven")
+const PREFIXES: &[&str] = &["spell out", "spell"];
+
+/// NATO alphabet, with the spellings speech recognizers tend to produce
+const NATO: &[(&str, char)] = &[
+    ("alpha", 'a'),
+    ("alfa", 'a'),
+    ("bravo", 'b'),
+    ("charlie", 'c'),
+    ("delta", 'd'),
+    ("echo", 'e'),
+    ("foxtrot", 'f'),
+    ("golf", 'g'),
+    ("hotel", 'h'),
+    ("india", 'i'),
+    ("juliet", 'j'),
+    ("juliett", 'j'),
+    ("kilo", 'k'),
+    ("lima", 'l'),
+    ("mike", 'm'),
+    ("november", 'n'),
+    ("oscar", 'o'),
+    ("papa", 'p'),
+    ("quebec", 'q'),
+    ("romeo", 'r'),
+    ("sierra", 's'),
+    ("tango", 't'),
+    ("uniform", 'u'),
+    ("victor", 'v'),
+    ("whiskey", 'w'),
+    ("whisky", 'w'),
+    ("x-ray", 'x'),
+    ("xray", 'x'),
+    ("yankee", 'y'),
+    ("zulu", 'z'),
+];
+
+/// Letter names as transcribed when a letter is said on its own
+const LETTER_NAMES: &[(&str, char)] = &[
+    ("bee", 'b'),
+    ("cee", 'c'),
+    ("dee", 'd'),
+    ("gee", 'g'),
+    ("aitch", 'h'),
+    ("jay", 'j'),
+    ("kay", 'k'),
+    ("el", 'l'),
+    ("em", 'm'),
+    ("en", 'n'),
+    ("pee", 'p'),
+    ("cue", 'q'),
+    ("ar", 'r'),
+    ("ess", 's'),
+    ("tee", 't'),
+    ("vee", 'v'),
+    ("double-u", 'w'),
+    ("ex", 'x'),
+    ("wye", 'y'),
+    ("zed", 'z'),
+    ("zee", 'z'),
+];
+
+const DIGITS: &[(&str, char)] = &[
+    ("zero", '0'),
+    ("one", '1'),
+    ("two", '2'),
+    ("three", '3'),
+    ("four", '4'),
+    ("five", '5'),
+    ("six", '6'),
+    ("seven", '7'),
+    ("eight", '8'),
+    ("nine", '9'),
+];
+
+/// Spoken symbols; two-word names are matched first
+const SYMBOLS: &[(&str, &str)] = &[
+    ("at sign", "@"),
+    ("forward slash", "/"),
+    ("back slash", "\\"),
+    ("number sign", "#"),
+    ("dollar sign", "$"),
+    ("percent sign", "%"),
+    ("full stop", "."),
+    ("dash", "-"),
+    ("hyphen", "-"),
+    ("minus", "-"),
+    ("underscore", "_"),
+    ("dot", "."),
+    ("period", "."),
+    ("point", "."),
+    ("at", "@"),
+    ("slash", "/"),
+    ("backslash", "\\"),
+    ("colon", ":"),
+    ("hash", "#"),
+    ("pound", "#"),
+    ("plus", "+"),
+    ("equals", "="),
+    ("ampersand", "&"),
+    ("asterisk", "*"),
+    ("star", "*"),
+    ("dollar", "$"),
+    ("percent", "%"),
+    ("tilde", "~"),
+    ("comma", ","),
+    ("space", " "),
+];
+
+/// Words that set the case of the next letter
+const UPPER: &[&str] = &["capital", "cap", "uppercase", "upper"];
+const LOWER: &[&str] = &["small", "lowercase", "lower"];
+
+/// Words that repeat the next letter, digit or symbol
+const REPEATS: &[(&str, usize)] = &[("double", 2), ("triple", 3)];
+
+/// The text after a spelling prefix ("spell alpha bravo"), if it has one
+///
+/// Only when every word after the prefix can be spelled, so "spell check
+/// this page" stays a sentence.
+pub fn strip_prefix(text: &str) -> Option<&str> {
+    let trimmed = text.trim_start();
+    let lower = trimmed.to_lowercase();
+    PREFIXES.iter().find_map(|prefix| {
+        let rest = lower.strip_prefix(prefix)?;
+        // Whole words only: "spelling" isn't "spell" + "ing"
+        if !rest.starts_with(|c: char| c.is_whitespace() || c == ',' || c == ':') {
+            return None;
+        }
+        let rest = &trimmed[prefix.len()..];
+        let rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',' || c == ':');
+        let spellable = words(rest).iter().all(|w| is_spelling_word(w));
+        (spellable && !rest.trim().is_empty()).then_some(rest)
+    })
+}
+
+/// One spoken unit of a spelled sequence
+enum Item {
+    Letter(char),
+    Literal(String),
+}
+
+/// Turn spelled-out input into the characters it names
+///
+/// "alpha bravo seven dash x-ray" becomes "AB7-X". Accepts NATO words,
+/// plain letters, digit words, spoken symbols, "capital"/"small" before a
+/// letter and "double"/"triple" before any item. Letters come out in
+/// capitals unless a case word is used anywhere, in which case unmarked
+/// letters are lowercase ("capital juliet oscar" is "Jo"). Words that
+/// aren't recognized are kept as written; punctuation the provider added
+/// between letters is dropped.
+pub fn spell(text: &str) -> String {
+    let words = words(text);
+    let case_words = words.iter().any(|w| {
+        let w = w.to_lowercase();
+        UPPER.contains(&w.as_str()) || LOWER.contains(&w.as_str())
+    });
+
+    let mut out = String::new();
+    let mut case: Option<bool> = None;
+    let mut repeat = 1;
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i].to_lowercase();
+        if UPPER.contains(&word.as_str()) {
+            case = Some(true);
+            i += 1;
+            continue;
+        }
+        if LOWER.contains(&word.as_str()) {
+            case = Some(false);
+            i += 1;
+            continue;
+        }
+        if let Some((_, times)) = REPEATS.iter().find(|(w, _)| *w == word) {
+            repeat = *times;
+            i += 1;
+            continue;
+        }
+
+        let pair = words
+            .get(i + 1)
+            .map(|next| format!("{} {}", word, next.to_lowercase()));
+        let (len, item) = match pair.as_deref().and_then(symbol) {
+            Some(symbol) => (2, Item::Literal(symbol.to_string())),
+            None => (1, item(&words[i], &word)),
+        };
+
+        let written = match item {
+            Item::Letter(letter) if case.unwrap_or(!case_words) => letter.to_uppercase().collect(),
+            Item::Letter(letter) => letter.to_lowercase().collect(),
+            Item::Literal(literal) => literal,
+        };
+        out.push_str(&written.repeat(repeat));
+        case = None;
+        repeat = 1;
+        i += len;
+    }
+    out
+}
+
+/// Spoken units, with hyphenated input split apart
+fn words(text: &str) -> Vec<String> {
+    tokens::tokenize(text)
+        .iter()
+        .flat_map(|token| {
+            if token.core.is_empty() {
+                // A symbol the provider already wrote out
+                vec![token.lead.trim().to_string()]
+            } else {
+                split_hyphens(&token.core)
+            }
+        })
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Whether a word means something in spelling, or is a character or code
+/// the provider already wrote out ("7", "AB7")
+fn is_spelling_word(word: &str) -> bool {
+    let lower = word.to_lowercase();
+    let lower = lower.as_str();
+    word.chars().count() == 1
+        || word
+            .chars()
+            .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+        || NATO
+            .iter()
+            .chain(LETTER_NAMES)
+            .chain(DIGITS)
+            .any(|(name, _)| *name == lower)
+        || SYMBOLS
+            .iter()
+            .any(|(name, _)| name.split(' ').any(|part| part == lower))
+        || UPPER.contains(&lower)
+        || LOWER.contains(&lower)
+        || REPEATS.iter().any(|(name, _)| *name == lower)
+}
+
+fn item(raw: &str, word: &str) -> Item {
+    let table_letter = NATO
+        .iter()
+        .chain(LETTER_NAMES)
+        .find(|(name, _)| *name == word)
+        .map(|(_, letter)| *letter);
+    if let Some(letter) = table_letter {
+        return Item::Letter(letter);
+    }
+    if let Some((_, digit)) = DIGITS.iter().find(|(name, _)| *name == word) {
+        return Item::Literal(digit.to_string());
+    }
+    if let Some(symbol) = symbol(word) {
+        return Item::Literal(symbol.to_string());
+    }
+
+    let mut chars = raw.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_alphabetic() => Item::Letter(c),
+        _ => Item::Literal(raw.to_string()),
+    }
+}
+
+fn symbol(phrase: &str) -> Option<&'static str> {
+    SYMBOLS
+        .iter()
+        .find(|(name, _)| *name == phrase)
+        .map(|(_, symbol)| *symbol)
+}
+
+/// Split "7-X-ray" into "7", "-", "X-ray", keeping hyphenated names whole
+fn split_hyphens(word: &str) -> Vec<String> {
+    let parts: Vec<&str> = word.split('-').collect();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < parts.len() {
+        if i > 0 {
+            out.push("-".to_string());
+        }
+        let joined = parts
+            .get(i + 1)
+            .map(|next| format!("{}-{}", parts[i], next))
+            .filter(|joined| is_hyphenated_name(joined));
+        match joined {
+            Some(joined) => {
+                out.push(joined);
+                i += 2;
+            }
+            None => {
+                out.push(parts[i].to_string());
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn is_hyphenated_name(word: &str) -> bool {
+    let word = word.to_lowercase();
+    NATO.iter()
+        .chain(LETTER_NAMES)
+        .any(|(name, _)| name.contains('-') && *name == word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spells_words_out() {
+        let cases: &[(&str, &str)] = &[
+            ("alpha bravo seven dash x-ray", "AB7-X"),
+            ("Alpha, bravo, seven.", "AB7"),
+            ("capital juliet oscar", "Jo"),
+            ("small alpha capital bravo", "aB"),
+            ("double echo four", "EE4"),
+            ("triple zero", "000"),
+            ("bee cee dee", "BCD"),
+            ("double-u aitch wye", "WHY"),
+            ("sam at sign example dot com", "sam@example.com"),
+            ("one two three", "123"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(spell(input), *expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn strips_spelling_prefixes() {
+        let cases: &[(&str, Option<&str>)] = &[
+            ("spell alpha bravo seven", Some("alpha bravo seven")),
+            ("Spell out, capital juliet", Some("capital juliet")),
+            ("spell: AB7", Some("AB7")),
+            ("spell check this page", None),
+            ("spelling bee", None),
+            ("spell", None),
+            ("alpha bravo", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(strip_prefix(input), *expected, "input: {:?}", input);
+        }
+    }
+}
@@ -0,0 +1,603 @@
+use crate::storage::database::{self, DatabaseError};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum StyleError {
+    #[error("Invalid style rule pattern: {0}")]
+    InvalidPattern(String),
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+}
+
+/// Which English spelling dictation is written in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpellingVariant {
+    /// Spelling is left as the recognizer wrote it
+    #[default]
+    Off,
+    /// en-GB: "colour", "organise", "centre", "travelled"
+    British,
+    /// en-US: "color", "organize", "center", "traveled"
+    American,
+}
+
+impl SpellingVariant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpellingVariant::Off => "off",
+            SpellingVariant::British => "british",
+            SpellingVariant::American => "american",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "british" => SpellingVariant::British,
+            "american" => SpellingVariant::American,
+            _ => SpellingVariant::Off,
+        }
+    }
+}
+
+/// Stems and the American/British endings they take ("col" + "or"/"our")
+type StemRule = (
+    &'static [&'static str],
+    &'static [(&'static str, &'static str)],
+);
+
+/// Only endings that differ are listed, so "humorous" and "analyses" (the
+/// plural of "analysis") are left alone.
+const STEM_RULES: &[StemRule] = &[
+    (
+        &[
+            "ard", "arm", "behavi", "cand", "clam", "col", "demean", "endeav", "fav", "flav",
+            "harb", "hon", "hum", "lab", "neighb", "od", "parl", "ranc", "rig", "rum", "sav",
+            "splend", "tum", "val", "vap", "vig",
+        ],
+        &[
+            ("or", "our"),
+            ("ors", "ours"),
+            ("ored", "oured"),
+            ("oring", "ouring"),
+            ("orful", "ourful"),
+            ("orless", "ourless"),
+            ("orable", "ourable"),
+            ("orably", "ourably"),
+            ("orite", "ourite"),
+            ("orites", "ourites"),
+        ],
+    ),
+    (
+        &[
+            "apolog",
+            "author",
+            "capital",
+            "categor",
+            "central",
+            "character",
+            "civil",
+            "colon",
+            "commercial",
+            "critic",
+            "custom",
+            "democrat",
+            "digit",
+            "emphas",
+            "energ",
+            "equal",
+            "familiar",
+            "fantas",
+            "fertil",
+            "final",
+            "formal",
+            "general",
+            "global",
+            "harmon",
+            "hospital",
+            "human",
+            "hypothes",
+            "ideal",
+            "immun",
+            "individual",
+            "industrial",
+            "initial",
+            "internal",
+            "jeopard",
+            "legal",
+            "liberal",
+            "local",
+            "magnet",
+            "marginal",
+            "material",
+            "maxim",
+            "mechan",
+            "memor",
+            "mesmer",
+            "minim",
+            "mobil",
+            "modern",
+            "monopol",
+            "natural",
+            "neutral",
+            "normal",
+            "optim",
+            "organ",
+            "patron",
+            "penal",
+            "personal",
+            "polar",
+            "popular",
+            "priorit",
+            "privat",
+            "public",
+            "random",
+            "rational",
+            "real",
+            "recogn",
+            "regular",
+            "revolution",
+            "ritual",
+            "romantic",
+            "sanit",
+            "scrutin",
+            "sensit",
+            "serial",
+            "social",
+            "special",
+            "stabil",
+            "standard",
+            "steril",
+            "subsid",
+            "summar",
+            "symbol",
+            "sympath",
+            "synchron",
+            "tantal",
+            "terror",
+            "theor",
+            "trivial",
+            "urban",
+            "util",
+            "vandal",
+            "visual",
+            "vital",
+            "vocal",
+        ],
+        &[
+            ("ize", "ise"),
+            ("izes", "ises"),
+            ("ized", "ised"),
+            ("izing", "ising"),
+            ("ization", "isation"),
+            ("izations", "isations"),
+            ("izer", "iser"),
+            ("izers", "isers"),
+            ("izable", "isable"),
+        ],
+    ),
+    (
+        &["anal", "catal", "paral"],
+        &[
+            ("yze", "yse"),
+            ("yzed", "ysed"),
+            ("yzing", "ysing"),
+            ("yzer", "yser"),
+            ("yzers", "ysers"),
+        ],
+    ),
+    (
+        &[
+            "calib", "cent", "fib", "lit", "lust", "meag", "sab", "somb", "spect", "theat",
+        ],
+        &[("er", "re"), ("ers", "res")],
+    ),
+    (
+        &[
+            "cancel", "channel", "counsel", "dial", "duel", "equal", "fuel", "funnel", "grovel",
+            "jewel", "label", "level", "libel", "marvel", "model", "panel", "pedal", "pencil",
+            "quarrel", "rival", "shovel", "signal", "snorkel", "spiral", "stencil", "total",
+            "travel", "tunnel", "yodel",
+        ],
+        &[
+            ("ed", "led"),
+            ("ing", "ling"),
+            ("er", "ler"),
+            ("ers", "lers"),
+        ],
+    ),
+];
+
+/// American and British spellings that don't follow a stem rule
+///
+/// Words whose spelling depends on meaning ("program", "check", "tire",
+/// "meter", "license") are left out.
+const WORD_PAIRS: &[(&str, &str)] = &[
+    ("acknowledgment", "acknowledgement"),
+    ("acknowledgments", "acknowledgements"),
+    ("aging", "ageing"),
+    ("aluminum", "aluminium"),
+    ("anesthesia", "anaesthesia"),
+    ("anesthetic", "anaesthetic"),
+    ("armory", "armoury"),
+    ("artifact", "artefact"),
+    ("artifacts", "artefacts"),
+    ("behavioral", "behavioural"),
+    ("catalog", "catalogue"),
+    ("cataloged", "catalogued"),
+    ("catalogs", "catalogues"),
+    ("centered", "centred"),
+    ("centering", "centring"),
+    ("counselor", "counsellor"),
+    ("counselors", "counsellors"),
+    ("cozy", "cosy"),
+    ("defense", "defence"),
+    ("defenses", "defences"),
+    ("enrollment", "enrolment"),
+    ("enrollments", "enrolments"),
+    ("fetus", "foetus"),
+    ("fulfill", "fulfil"),
+    ("fulfillment", "fulfilment"),
+    ("fulfills", "fulfils"),
+    ("gray", "grey"),
+    ("grayish", "greyish"),
+    ("grays", "greys"),
+    ("installment", "instalment"),
+    ("installments", "instalments"),
+    ("jewelry", "jewellery"),
+    ("licorice", "liquorice"),
+    ("maneuver", "manoeuvre"),
+    ("maneuvered", "manoeuvred"),
+    ("maneuvers", "manoeuvres"),
+    ("marvelous", "marvellous"),
+    ("mold", "mould"),
+    ("molds", "moulds"),
+    ("moldy", "mouldy"),
+    ("mustache", "moustache"),
+    ("neighborhood", "neighbourhood"),
+    ("neighborhoods", "neighbourhoods"),
+    ("offense", "offence"),
+    ("offenses", "offences"),
+    ("omelet", "omelette"),
+    ("pajamas", "pyjamas"),
+    ("pediatric", "paediatric"),
+    ("pediatrician", "paediatrician"),
+    ("plow", "plough"),
+    ("plows", "ploughs"),
+    ("pretense", "pretence"),
+    ("savory", "savoury"),
+    ("skeptic", "sceptic"),
+    ("skeptical", "sceptical"),
+    ("skepticism", "scepticism"),
+    ("skillful", "skilful"),
+    ("sulfur", "sulphur"),
+    ("willful", "wilful"),
+    ("woolen", "woollen"),
+    ("yogurt", "yoghurt"),
+];
+
+/// Every American/British pair, stem rules expanded
+fn spelling_pairs() -> impl Iterator<Item = (String, String)> {
+    let expanded = STEM_RULES.iter().flat_map(|(stems, endings)| {
+        stems.iter().flat_map(move |stem| {
+            endings
+                .iter()
+                .map(move |(us, gb)| (format!("{}{}", stem, us), format!("{}{}", stem, gb)))
+        })
+    });
+    let words = WORD_PAIRS
+        .iter()
+        .map(|(us, gb)| (us.to_string(), gb.to_string()));
+    expanded.chain(words)
+}
+
+static TO_BRITISH: Lazy<HashMap<String, String>> = Lazy::new(|| spelling_pairs().collect());
+
+static TO_AMERICAN: Lazy<HashMap<String, String>> =
+    Lazy::new(|| spelling_pairs().map(|(us, gb)| (gb, us)).collect());
+
+/// A word, with an apostrophe inside it for possessives and contractions
+static WORD: Lazy<Regex> = Lazy::new(|| Regex::new(r"[\p{L}]+(?:['’][\p{L}]+)?").unwrap());
+
+/// Rewrite words in British or American spelling, keeping their case
+///
+/// "Colors" becomes "Colours" and "ORGANIZE" becomes "ORGANISE";
+/// possessives ("neighbor's") are converted too.
+pub fn convert(text: &str, variant: SpellingVariant) -> String {
+    let table = match variant {
+        SpellingVariant::Off => return text.to_string(),
+        SpellingVariant::British => &*TO_BRITISH,
+        SpellingVariant::American => &*TO_AMERICAN,
+    };
+    WORD.replace_all(text, |caps: &Captures| {
+        let word = &caps[0];
+        let (stem, possessive) = match word.rfind(['\'', '’']) {
+            Some(i) if word[i..].chars().count() == 2 && word.ends_with(['s', 'S']) => {
+                word.split_at(i)
+            }
+            _ => (word, ""),
+        };
+        match table.get(&stem.to_lowercase()) {
+            Some(converted) => match_case(stem, converted) + possessive,
+            None => word.to_string(),
+        }
+    })
+    .into_owned()
+}
+
+/// Write `replacement` in the case of the text it replaces
+fn match_case(found: &str, replacement: &str) -> String {
+    let letters: Vec<char> = found.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()) {
+        return replacement.to_uppercase();
+    }
+    let mut chars = replacement.chars();
+    match (found.chars().next(), chars.next()) {
+        (Some(f), Some(r)) if f.is_uppercase() && r.is_lowercase() => {
+            r.to_uppercase().chain(chars).collect()
+        }
+        _ => replacement.to_string(),
+    }
+}
+
+/// A house style rule from the user's list
+///
+/// Plain rules replace a whole word or phrase ("e-mail" → "email"), taking
+/// the case of what they replace unless `case_sensitive` is set. Regex rules
+/// use `$1`-style groups in the replacement, so the Oxford comma can be
+/// dropped with `(, [^,.]+), (and|or) ` → `$1 $2 `.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StyleRule {
+    /// Empty for a new rule; assigned on save
+    #[serde(default)]
+    pub id: String,
+    pub pattern: String,
+    pub replacement: String,
+    #[serde(default)]
+    pub is_regex: bool,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    pub enabled: bool,
+}
+
+/// The user's style rules, in the order they apply
+pub fn list() -> Result<Vec<StyleRule>, DatabaseError> {
+    let conn = database::connect()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, pattern, replacement, is_regex, case_sensitive, enabled
+             FROM style_rules ORDER BY created_at, rowid",
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(StyleRule {
+                id: row.get(0)?,
+                pattern: row.get(1)?,
+                replacement: row.get(2)?,
+                is_regex: row.get(3)?,
+                case_sensitive: row.get(4)?,
+                enabled: row.get(5)?,
+            })
+        })
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
+
+/// Add or update a rule, rejecting patterns that don't compile
+pub fn save(rule: &StyleRule) -> Result<StyleRule, StyleError> {
+    compile(rule)?;
+    let mut saved = rule.clone();
+    if saved.id.is_empty() {
+        saved.id = uuid::Uuid::new_v4().to_string();
+    }
+
+    let conn = database::connect()?;
+    conn.execute(
+        "INSERT INTO style_rules (id, pattern, replacement, is_regex, case_sensitive, enabled)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(id) DO UPDATE SET
+            pattern = excluded.pattern,
+            replacement = excluded.replacement,
+            is_regex = excluded.is_regex,
+            case_sensitive = excluded.case_sensitive,
+            enabled = excluded.enabled",
+        rusqlite::params![
+            saved.id,
+            saved.pattern,
+            saved.replacement,
+            saved.is_regex,
+            saved.case_sensitive,
+            saved.enabled,
+        ],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(saved)
+}
+
+pub fn delete(id: &str) -> Result<(), DatabaseError> {
+    let conn = database::connect()?;
+    conn.execute("DELETE FROM style_rules WHERE id = ?1", [id])
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(())
+}
+
+/// Build the regex a rule matches with
+fn compile(rule: &StyleRule) -> Result<Regex, StyleError> {
+    let pattern = rule.pattern.trim();
+    if pattern.is_empty() {
+        return Err(StyleError::InvalidPattern("empty pattern".to_string()));
+    }
+
+    let mut source = String::new();
+    if !rule.case_sensitive {
+        source.push_str("(?i)");
+    }
+    if rule.is_regex {
+        // Untrimmed: a regex may match on its surrounding spaces
+        source.push_str(&rule.pattern);
+    } else {
+        // Whole words only, with any run of spaces between them; `\b` is
+        // only added next to a letter or digit so ", and" still matches
+        let word_edge = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+        if word_edge(pattern.chars().next()) {
+            source.push_str(r"\b");
+        }
+        let words: Vec<String> = pattern.split_whitespace().map(regex::escape).collect();
+        source.push_str(&words.join(r"\s+"));
+        if word_edge(pattern.chars().last()) {
+            source.push_str(r"\b");
+        }
+    }
+
+    Regex::new(&source).map_err(|e| StyleError::InvalidPattern(e.to_string()))
+}
+
+/// Compiled enabled rules
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<(Regex, StyleRule)>,
+}
+
+impl RuleSet {
+    /// Rules that fail to compile are skipped with a warning
+    pub fn new(rules: &[StyleRule]) -> Self {
+        let rules = rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| match compile(rule) {
+                Ok(regex) => Some((regex, rule.clone())),
+                Err(e) => {
+                    log::warn!("Skipping style rule {:?}: {}", rule.pattern, e);
+                    None
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn load() -> Result<Self, DatabaseError> {
+        Ok(Self::new(&list()?))
+    }
+}
+
+/// Apply each rule in turn, so later rules see earlier replacements
+pub fn apply(text: &str, rules: &RuleSet) -> String {
+    let mut text = text.to_string();
+    for (regex, rule) in &rules.rules {
+        let replaced = if rule.is_regex {
+            regex.replace_all(&text, rule.replacement.as_str())
+        } else if rule.case_sensitive {
+            regex.replace_all(&text, regex::NoExpand(&rule.replacement))
+        } else {
+            regex.replace_all(&text, |caps: &Captures| {
+                match_case(&caps[0], &rule.replacement)
+            })
+        };
+        text = replaced.into_owned();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, replacement: &str, is_regex: bool, case_sensitive: bool) -> StyleRule {
+        StyleRule {
+            id: String::new(),
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            is_regex,
+            case_sensitive,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn converts_to_british() {
+        let cases: &[(&str, &str)] = &[
+            ("The colors are my favorite", "The colours are my favourite"),
+            ("Colors", "Colours"),
+            ("ORGANIZE the theater", "ORGANISE the theatre"),
+            ("We traveled and canceled", "We travelled and cancelled"),
+            ("She analyzed the catalog", "She analysed the catalogue"),
+            ("my neighbor's yard", "my neighbour's yard"),
+            ("the neighbor’s dog", "the neighbour’s dog"),
+            ("humorous analyses", "humorous analyses"),
+            ("a recognizable gray", "a recognisable grey"),
+            ("don't colorize", "don't colorize"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                convert(input, SpellingVariant::British),
+                *expected,
+                "input: {:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn converts_to_american() {
+        let cases: &[(&str, &str)] = &[
+            ("The colours are my favourite", "The colors are my favorite"),
+            ("ORGANISE the centre", "ORGANIZE the center"),
+            ("We travelled to the theatre", "We traveled to the theater"),
+            ("my neighbour's defence", "my neighbor's defense"),
+            ("Yoghurt", "Yogurt"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                convert(input, SpellingVariant::American),
+                *expected,
+                "input: {:?}",
+                input
+            );
+        }
+        assert_eq!(convert("colour", SpellingVariant::Off), "colour");
+    }
+
+    #[test]
+    fn applies_house_style() {
+        let rules = RuleSet::new(&[
+            rule("e-mail", "email", false, false),
+            rule("web site", "website", false, false),
+            rule("JS", "JavaScript", false, true),
+            rule(", etc", " and so on", false, false),
+            rule(r"(, [^,.]+), (and|or) ", "$1 $2 ", true, false),
+            rule("(unclosed", "x", true, false),
+        ]);
+        let cases: &[(&str, &str)] = &[
+            ("Send an E-mail today", "Send an Email today"),
+            ("E-MAIL me", "EMAIL me"),
+            ("The web  site is down", "The website is down"),
+            ("e-mailing", "e-mailing"),
+            ("JS and js", "JavaScript and js"),
+            ("apples, pears, etc.", "apples, pears and so on."),
+            ("red, white, and blue flags", "red, white and blue flags"),
+            ("tea or coffee", "tea or coffee"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(apply(input, &rules), *expected, "input: {:?}", input);
+        }
+
+        let mut disabled = rule("e-mail", "email", false, false);
+        disabled.enabled = false;
+        assert_eq!(apply("e-mail", &RuleSet::new(&[disabled])), "e-mail");
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(compile(&rule("  ", "x", false, false)).is_err());
+        assert!(compile(&rule("(unclosed", "x", true, false)).is_err());
+        assert!(compile(&rule("(unclosed", "x", false, false)).is_ok());
+    }
+}
@@ -0,0 +1,138 @@
+/// A whitespace-delimited word split into its punctuation and word parts
+///
+/// `"(well,"` parses as lead `"("`, core `"well"`, trail `","`. Text stages
+/// match on `core` and move punctuation around when they drop words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub lead: String,
+    pub core: String,
+    pub trail: String,
+}
+
+impl Token {
+    pub fn parse(raw: &str) -> Self {
+        let is_word = |c: char| c.is_alphanumeric();
+        let start = raw.find(is_word);
+        let end = raw
+            .rfind(is_word)
+            .map(|i| i + raw[i..].chars().next().map_or(1, char::len_utf8));
+
+        match (start, end) {
+            (Some(start), Some(end)) => Self {
+                lead: raw[..start].to_string(),
+                core: raw[start..end].to_string(),
+                trail: raw[end..].to_string(),
+            },
+            _ => Self {
+                lead: raw.to_string(),
+                core: String::new(),
+                trail: String::new(),
+            },
+        }
+    }
+
+    /// Lowercased word, for case-insensitive matching
+    pub fn lower(&self) -> String {
+        self.core.to_lowercase()
+    }
+
+    /// Whether the token closes a sentence (`.`, `!`, `?`)
+    pub fn ends_sentence(&self) -> bool {
+        self.trail.contains(['.', '!', '?'])
+    }
+
+    /// Whether the token is followed by any spoken-pause punctuation
+    pub fn has_pause(&self) -> bool {
+        self.trail.contains([',', ';', ':', '.', '!', '?', '—'])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lead.is_empty() && self.core.is_empty() && self.trail.is_empty()
+    }
+
+    pub fn starts_uppercase(&self) -> bool {
+        self.core.chars().next().is_some_and(char::is_uppercase)
+    }
+
+    /// Uppercase the first letter of the word
+    pub fn capitalize(&mut self) {
+        let mut chars = self.core.chars();
+        if let Some(first) = chars.next() {
+            self.core = first.to_uppercase().chain(chars).collect();
+        }
+    }
+
+    /// Lowercase the first letter of the word
+    pub fn decapitalize(&mut self) {
+        let mut chars = self.core.chars();
+        if let Some(first) = chars.next() {
+            self.core = first.to_lowercase().chain(chars).collect();
+        }
+    }
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}", self.lead, self.core, self.trail)
+    }
+}
+
+/// Split text into words
+///
+/// Line breaks are kept in the `lead` of the word after them so stages that
+/// re-join words don't flatten paragraphs.
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut breaks = String::new();
+
+    for line in text.split_inclusive('\n') {
+        for word in line.split_whitespace() {
+            let mut token = Token::parse(word);
+            token.lead = std::mem::take(&mut breaks) + &token.lead;
+            tokens.push(token);
+        }
+        if line.ends_with('\n') {
+            breaks.push('\n');
+        }
+    }
+
+    if !breaks.is_empty() {
+        tokens.push(Token::parse(&breaks));
+    }
+    tokens
+}
+
+/// Join tokens back into text with single spaces, or line breaks where the
+/// original had them
+pub fn join(tokens: &[Token]) -> String {
+    let mut out = String::new();
+    for token in tokens.iter().filter(|t| !t.is_empty()) {
+        if !out.is_empty() && !token.lead.starts_with('\n') {
+            out.push(' ');
+        }
+        out.push_str(&token.to_string());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_line_breaks_through_a_round_trip() {
+        let cases: &[&str] = &[
+            "one two",
+            "(well, maybe)",
+            "first line\nsecond line",
+            "Dear Sam,\n\nThanks for writing.",
+            "trailing break\n",
+        ];
+        for input in cases {
+            assert_eq!(join(&tokenize(input)), *input, "input: {:?}", input);
+        }
+        let tokens = tokenize("end.\nNext");
+        assert_eq!(tokens[1].lead, "\n");
+        assert_eq!(tokens[1].core, "Next");
+    }
+}
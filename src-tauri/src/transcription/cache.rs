@@ -0,0 +1,132 @@
+use super::vocabulary::Vocabulary;
+use super::{TranscribeOptions, TranscriptionResult};
+use crate::storage::database::{self, DatabaseError};
+use rusqlite::OptionalExtension;
+use sha2::{Digest, Sha256};
+
+/// Total size of cached transcripts (text and word timings) before
+/// least-recently-used entries are evicted
+const MAX_CACHE_BYTES: i64 = 2 * 1024 * 1024;
+
+/// Cache key for a transcription request
+///
+/// Covers the encoded audio plus everything that changes what the provider
+/// returns for it: provider, model, requested language and the dictionary,
+/// so adding a word isn't masked by an old transcript. The dictionary is
+/// hashed as stored rather than as the ranked hint sent along, which changes
+/// with every dictation and would make a retry of the same audio miss.
+pub fn cache_key(audio: &[u8], options: &TranscribeOptions, vocabulary: &Vocabulary) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(audio);
+    hasher.update([0]);
+    hasher.update(options.provider.as_str());
+    hasher.update([0]);
+    hasher.update(options.model.as_deref().unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(options.language.as_deref().unwrap_or_default());
+    hasher.update([0]);
+    for (word, phonetic_hint) in vocabulary.entries() {
+        hasher.update(word);
+        hasher.update([0]);
+        hasher.update(phonetic_hint.unwrap_or_default());
+        hasher.update([0]);
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Look up a cached transcript, marking it as recently used
+pub fn get(key: &str) -> Result<Option<TranscriptionResult>, DatabaseError> {
+    let conn = database::connect()?;
+    let cached = conn
+        .query_row(
+            "SELECT text, language, confidence, duration_ms, model, words, language_probability
+             FROM transcript_cache WHERE key = ?1",
+            [key],
+            |row| {
+                Ok(TranscriptionResult {
+                    text: row.get(0)?,
+                    language: row.get(1)?,
+                    language_probability: row.get::<_, Option<f64>>(6)?.map(|p| p as f32),
+                    confidence: row.get::<_, f64>(2)? as f32,
+                    duration_ms: row.get::<_, i64>(3)? as u64,
+                    model: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                    // Nothing is billed for a cache hit
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    cached: true,
+                    words: row
+                        .get::<_, Option<String>>(5)?
+                        .and_then(|json| serde_json::from_str(&json).ok())
+                        .unwrap_or_default(),
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    if cached.is_some() {
+        conn.execute(
+            "UPDATE transcript_cache
+             SET hit_count = hit_count + 1, last_used_at = datetime('now')
+             WHERE key = ?1",
+            [key],
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    }
+
+    Ok(cached)
+}
+
+/// Store a provider response and evict old entries past the size budget
+pub fn put(
+    key: &str,
+    options: &TranscribeOptions,
+    result: &TranscriptionResult,
+) -> Result<(), DatabaseError> {
+    let words = (!result.words.is_empty())
+        .then(|| serde_json::to_string(&result.words).ok())
+        .flatten();
+    let size_bytes = result.text.len() + words.as_ref().map_or(0, String::len);
+
+    let conn = database::connect()?;
+    conn.execute(
+        "INSERT OR REPLACE INTO transcript_cache
+            (key, provider, model, text, language, confidence, duration_ms, size_bytes, words,
+             language_probability)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        rusqlite::params![
+            key,
+            options.provider.as_str(),
+            result.model,
+            result.text,
+            result.language,
+            result.confidence as f64,
+            result.duration_ms as i64,
+            size_bytes as i64,
+            words,
+            result.language_probability.map(|p| p as f64),
+        ],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    // Keep the most recently used entries whose running size fits the budget
+    conn.execute(
+        "DELETE FROM transcript_cache WHERE key IN (
+            SELECT key FROM (
+                SELECT key, SUM(size_bytes) OVER (
+                    ORDER BY last_used_at DESC, created_at DESC
+                ) AS running_bytes
+                FROM transcript_cache
+            ) WHERE running_bytes > ?1
+         )",
+        [MAX_CACHE_BYTES],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use super::{language, TranscribeOptions, TranscriptionError, TranscriptionResult};
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::json;
+
+const GEMINI_API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+const DEFAULT_MODEL: &str = "gemini-2.0-flash";
+
+/// Structured response requested from the model
+#[derive(Debug, Deserialize)]
+struct GeminiTranscript {
+    text: String,
+    #[serde(default)]
+    language: Option<String>,
+    /// The model's own estimate, from 0 to 1
+    #[serde(default)]
+    language_confidence: Option<f32>,
+}
+
+/// Transcribe audio using Gemini's multimodal input
+///
+/// `vocabulary` is an instruction block appended to the transcription prompt.
+pub async fn transcribe(
+    api_key: &str,
+    audio: &[u8],
+    options: &TranscribeOptions,
+    vocabulary: Option<&str>,
+) -> Result<TranscriptionResult, TranscriptionError> {
+    let model = options.model.as_deref().unwrap_or(DEFAULT_MODEL);
+    let url = format!("{}/{}:generateContent", GEMINI_API_BASE, model);
+
+    let mut prompt = String::from(
+        "Transcribe this audio accurately. Put only the transcribed text in `text`. \
+         Preserve natural punctuation and capitalization. \
+         Set `language` to the ISO 639-1 code of the spoken language \
+         and `language_confidence` to how sure you are of it, from 0 to 1.",
+    );
+    if let Some(language) = &options.language {
+        prompt.push_str(&format!(" The audio is in {}.", language));
+    }
+    if let Some(block) = vocabulary {
+        prompt.push_str("\n\n");
+        prompt.push_str(block);
+    }
+
+    let body = json!({
+        "contents": [{
+            "parts": [
+                {
+                    "inline_data": {
+                        "mime_type": "audio/wav",
+                        "data": base64::engine::general_purpose::STANDARD.encode(audio),
+                    }
+                },
+                { "text": prompt }
+            ]
+        }],
+        "generationConfig": {
+            "temperature": 0.1,
+            "maxOutputTokens": 2048,
+            "responseMimeType": "application/json",
+            "responseSchema": {
+                "type": "OBJECT",
+                "properties": {
+                    "text": { "type": "STRING" },
+                    "language": { "type": "STRING" },
+                    "language_confidence": { "type": "NUMBER" },
+                },
+                "required": ["text"],
+            },
+        }
+    });
+
+    let response = reqwest::Client::new()
+        .post(url)
+        .query(&[("key", api_key)])
+        .json(&body)
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(TranscriptionError::ApiError {
+            status: status.as_u16(),
+            body,
+        });
+    }
+
+    let data: serde_json::Value = response.json().await?;
+
+    let raw = data["candidates"][0]["content"]["parts"][0]["text"]
+        .as_str()
+        .unwrap_or_default();
+
+    // Fall back to treating the whole reply as the transcript if the model
+    // ignored the response schema
+    let transcript = serde_json::from_str::<GeminiTranscript>(raw).unwrap_or(GeminiTranscript {
+        text: raw.to_string(),
+        language: None,
+        language_confidence: None,
+    });
+
+    Ok(TranscriptionResult {
+        text: transcript.text.trim().to_string(),
+        language: transcript
+            .language
+            .as_deref()
+            .or(options.language.as_deref())
+            .map(language::normalize)
+            .unwrap_or_else(|| "en".to_string()),
+        // Only a detected language has a probability
+        language_probability: transcript
+            .language_confidence
+            .filter(|_| options.language.is_none())
+            .map(|p| p.clamp(0.0, 1.0)),
+        confidence: 0.95, // Gemini doesn't provide per-segment confidence
+        duration_ms: 0,
+        model: model.to_string(),
+        input_tokens: data["usageMetadata"]["promptTokenCount"]
+            .as_u64()
+            .unwrap_or(0),
+        output_tokens: data["usageMetadata"]["candidatesTokenCount"]
+            .as_u64()
+            .unwrap_or(0),
+        cached: false,
+        // Gemini doesn't return word timings
+        words: Vec::new(),
+    })
+}
@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+
+/// Provider detections below this probability fall back to the configured language
+const MIN_DETECTION_CONFIDENCE: f32 = 0.5;
+
+/// The user's language settings for a dictation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguagePreferences {
+    #[serde(default = "default_auto_detect")]
+    pub auto_detect: bool,
+    #[serde(default = "default_language")]
+    pub language: String,
+    /// Languages the user actually speaks; empty means any
+    #[serde(default)]
+    pub allowed_languages: Vec<String>,
+}
+
+fn default_auto_detect() -> bool {
+    true
+}
+
+fn default_language() -> String {
+    "en".to_string()
+}
+
+impl Default for LanguagePreferences {
+    fn default() -> Self {
+        Self {
+            auto_detect: default_auto_detect(),
+            language: default_language(),
+            allowed_languages: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LanguageSource {
+    /// Reported by the transcription provider
+    Detected,
+    /// Per-app default for the focused application
+    AppDefault,
+    /// The user's global language setting
+    UserDefault,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageDecision {
+    pub language: String,
+    /// The detector's probability for a detected language; `None` when the
+    /// provider doesn't report one or the language wasn't detected
+    pub confidence: Option<f32>,
+    pub source: LanguageSource,
+}
+
+/// Decide which language (if any) to pin in the provider request
+///
+/// Returns `None` when the provider should detect the language itself.
+pub fn request_language(prefs: &LanguagePreferences, app_default: Option<&str>) -> Option<String> {
+    if !prefs.auto_detect {
+        return Some(fallback(prefs, app_default).0);
+    }
+    // Nothing to detect if the user only speaks one language
+    match prefs.allowed_languages.as_slice() {
+        [only] => Some(normalize(only)),
+        _ => None,
+    }
+}
+
+/// Combine provider detection, the app default and the user's settings
+///
+/// `detected` is the provider-reported language and its probability, if
+/// reported, and should only be passed when the request left the language
+/// unpinned. A detection without a probability is trusted if it's allowed.
+pub fn resolve(
+    detected: Option<(&str, Option<f32>)>,
+    app_default: Option<&str>,
+    prefs: &LanguagePreferences,
+) -> LanguageDecision {
+    if prefs.auto_detect {
+        if let Some((language, confidence)) = detected {
+            let language = normalize(language);
+            let likely = match confidence {
+                Some(confidence) => confidence >= MIN_DETECTION_CONFIDENCE,
+                None => true,
+            };
+            if likely && is_allowed(prefs, &language) {
+                return LanguageDecision {
+                    language,
+                    confidence,
+                    source: LanguageSource::Detected,
+                };
+            }
+        }
+    }
+
+    let (language, source) = fallback(prefs, app_default);
+    LanguageDecision {
+        language,
+        confidence: None,
+        source,
+    }
+}
+
+/// App default if it's allowed, otherwise the user's language (or first allowed one)
+fn fallback(prefs: &LanguagePreferences, app_default: Option<&str>) -> (String, LanguageSource) {
+    if let Some(language) = app_default.map(normalize) {
+        if is_allowed(prefs, &language) {
+            return (language, LanguageSource::AppDefault);
+        }
+    }
+
+    let language = normalize(&prefs.language);
+    if is_allowed(prefs, &language) {
+        return (language, LanguageSource::UserDefault);
+    }
+    let first_allowed = prefs.allowed_languages.first().map(|l| normalize(l));
+    (
+        first_allowed.unwrap_or(language),
+        LanguageSource::UserDefault,
+    )
+}
+
+fn is_allowed(prefs: &LanguagePreferences, language: &str) -> bool {
+    prefs.allowed_languages.is_empty()
+        || prefs
+            .allowed_languages
+            .iter()
+            .any(|allowed| normalize(allowed) == language)
+}
+
+/// Normalize a language name or tag to a lowercase ISO 639-1 code
+///
+/// Whisper reports full names ("english"); settings use codes ("en", "pt-BR").
+pub fn normalize(language: &str) -> String {
+    let lower = language.trim().to_lowercase();
+    let code = match lower.as_str() {
+        "english" => "en",
+        "spanish" => "es",
+        "french" => "fr",
+        "german" => "de",
+        "italian" => "it",
+        "portuguese" => "pt",
+        "dutch" => "nl",
+        "japanese" => "ja",
+        "korean" => "ko",
+        "chinese" | "mandarin" => "zh",
+        "russian" => "ru",
+        "arabic" => "ar",
+        "hindi" => "hi",
+        "polish" => "pl",
+        "swedish" => "sv",
+        "danish" => "da",
+        "norwegian" => "no",
+        "finnish" => "fi",
+        "turkish" => "tr",
+        "greek" => "el",
+        "hebrew" => "he",
+        "ukrainian" => "uk",
+        "czech" => "cs",
+        "vietnamese" => "vi",
+        "thai" => "th",
+        "indonesian" => "id",
+        other => other.split(['-', '_']).next().unwrap_or(other),
+    };
+    code.to_string()
+}
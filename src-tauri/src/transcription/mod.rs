@@ -0,0 +1,168 @@
+pub mod cache;
+pub mod gemini;
+pub mod language;
+pub mod vocabulary;
+pub mod whisper;
+
+use crate::storage::keychain;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use vocabulary::{Vocabulary, VocabularyHint};
+
+#[derive(Error, Debug)]
+pub enum TranscriptionError {
+    #[error("Transcription request failed: {0}")]
+    RequestError(String),
+    #[error("Network unavailable: {0}")]
+    NetworkError(String),
+    #[error("Provider returned an error ({status}): {body}")]
+    ApiError { status: u16, body: String },
+    #[error("No API key configured for {0}")]
+    MissingApiKey(String),
+}
+
+impl TranscriptionError {
+    /// Whether the same request may succeed later (offline, rate limited, provider outage)
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            TranscriptionError::NetworkError(_) => true,
+            TranscriptionError::ApiError { status, .. } => *status == 429 || *status >= 500,
+            _ => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for TranscriptionError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_connect() || e.is_timeout() {
+            TranscriptionError::NetworkError(e.to_string())
+        } else {
+            TranscriptionError::RequestError(e.to_string())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Whisper,
+    #[default]
+    Gemini,
+}
+
+impl Provider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Provider::Whisper => "whisper",
+            Provider::Gemini => "gemini",
+        }
+    }
+
+    /// Keychain entry holding this provider's API key
+    fn api_key_name(&self) -> &'static str {
+        match self {
+            Provider::Whisper => "openai_api_key",
+            Provider::Gemini => "gemini_api_key",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranscribeOptions {
+    #[serde(default)]
+    pub provider: Provider,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// When a word was spoken, relative to the start of the audio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordTiming {
+    pub word: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionResult {
+    pub text: String,
+    pub language: String,
+    /// The detector's probability for `language`, for providers that report
+    /// one
+    #[serde(default)]
+    pub language_probability: Option<f32>,
+    pub confidence: f32,
+    pub duration_ms: u64,
+    /// Model that produced the transcript
+    #[serde(default)]
+    pub model: String,
+    /// Billed tokens, for token-priced providers
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    /// Served from the local transcript cache instead of the provider
+    #[serde(default)]
+    pub cached: bool,
+    /// Per-word timing, for providers that return it
+    #[serde(default)]
+    pub words: Vec<WordTiming>,
+}
+
+/// Transcribe encoded audio, reusing a cached result for identical requests
+pub async fn transcribe(
+    audio: &[u8],
+    options: &TranscribeOptions,
+) -> Result<TranscriptionResult, TranscriptionError> {
+    let vocabulary = Vocabulary::load().unwrap_or_else(|e| {
+        log::warn!("Failed to load custom vocabulary: {}", e);
+        Vocabulary::default()
+    });
+    let key = cache::cache_key(audio, options, &vocabulary);
+    match cache::get(&key) {
+        Ok(Some(result)) => return Ok(result),
+        Ok(None) => {}
+        Err(e) => log::warn!("Transcript cache lookup failed: {}", e),
+    }
+
+    let hint = vocabulary.hint_for(options.provider);
+    let result = transcribe_uncached(audio, options, hint).await?;
+    if let Err(e) = cache::put(&key, options, &result) {
+        log::warn!("Failed to cache transcript: {}", e);
+    }
+    Ok(result)
+}
+
+/// Send audio to the provider, biasing the recognizer towards the user's dictionary
+async fn transcribe_uncached(
+    audio: &[u8],
+    options: &TranscribeOptions,
+    hint: Option<VocabularyHint>,
+) -> Result<TranscriptionResult, TranscriptionError> {
+    match options.provider {
+        Provider::Whisper => {
+            let api_key = api_key(options.provider)?;
+            let prompt = match hint {
+                Some(VocabularyHint::Prompt(prompt)) => Some(prompt),
+                _ => None,
+            };
+            whisper::transcribe(&api_key, audio, options, prompt.as_deref()).await
+        }
+        Provider::Gemini => {
+            let api_key = api_key(options.provider)?;
+            let instructions = match hint {
+                Some(VocabularyHint::Instructions(block)) => Some(block),
+                _ => None,
+            };
+            gemini::transcribe(&api_key, audio, options, instructions.as_deref()).await
+        }
+    }
+}
+
+fn api_key(provider: Provider) -> Result<String, TranscriptionError> {
+    keychain::get(keychain::SERVICE, provider.api_key_name())
+        .map_err(|e| TranscriptionError::RequestError(e.to_string()))?
+        .ok_or_else(|| TranscriptionError::MissingApiKey(provider.as_str().to_string()))
+}
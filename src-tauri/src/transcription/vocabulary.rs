@@ -0,0 +1,248 @@
+use super::Provider;
+use crate::storage::database::DatabaseError;
+use crate::storage::dictionary::{self, DictionaryWord};
+use crate::storage::history;
+use std::collections::HashSet;
+
+/// Whisper only looks at the final 224 tokens of its prompt; at roughly
+/// four characters per token this keeps the glossary inside that window
+const WHISPER_PROMPT_MAX_CHARS: usize = 800;
+
+/// Upper bound for the vocabulary block appended to the Gemini prompt
+const GEMINI_INSTRUCTIONS_MAX_CHARS: usize = 4000;
+
+/// How many past dictations to scan when ranking dictionary words
+const RANKING_HISTORY_WINDOW: usize = 200;
+
+/// A dictionary word with its relevance score
+#[derive(Debug, Clone)]
+pub struct VocabularyTerm {
+    pub word: String,
+    pub phonetic_hint: Option<String>,
+    pub score: f32,
+}
+
+/// Vocabulary biasing payload in the form a given provider accepts
+#[derive(Debug, Clone, PartialEq)]
+pub enum VocabularyHint {
+    /// Free-form prompt text (Whisper `prompt`)
+    Prompt(String),
+    /// Instruction block appended to an LLM prompt (Gemini)
+    Instructions(String),
+}
+
+/// The user's custom vocabulary, ranked most relevant first
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    terms: Vec<VocabularyTerm>,
+}
+
+impl Vocabulary {
+    /// Load the dictionary table and rank it against recent history
+    pub fn load() -> Result<Self, DatabaseError> {
+        let words = dictionary::list()?;
+        if words.is_empty() {
+            return Ok(Self::default());
+        }
+        let recent = history::recent_texts(RANKING_HISTORY_WINDOW)?;
+        Ok(Self::rank(words, &recent))
+    }
+
+    /// Rank dictionary words by how often they appear in recent dictations,
+    /// then by recency of creation. `words` is expected newest first.
+    pub fn rank(words: Vec<DictionaryWord>, recent_texts: &[String]) -> Self {
+        let recent_lower: Vec<String> = recent_texts.iter().map(|t| t.to_lowercase()).collect();
+        let total = words.len().max(1) as f32;
+        let mut seen = HashSet::new();
+
+        let mut terms: Vec<VocabularyTerm> = words
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                let word = entry.word.trim().to_string();
+                if word.is_empty() || !seen.insert(word.to_lowercase()) {
+                    return None;
+                }
+
+                let needle = word.to_lowercase();
+                let mentions = recent_lower
+                    .iter()
+                    .filter(|text| text.contains(&needle))
+                    .count() as f32;
+                // Newer entries get up to one point, so they win ties
+                let recency = 1.0 - index as f32 / total;
+
+                Some(VocabularyTerm {
+                    word,
+                    phonetic_hint: entry.phonetic_hint.filter(|h| !h.trim().is_empty()),
+                    score: mentions * 2.0 + recency,
+                })
+            })
+            .collect();
+
+        terms.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Self { terms }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Words and phonetic hints in alphabetical order, which unlike the
+    /// ranking don't shift as new dictations are added to the history
+    pub fn entries(&self) -> Vec<(&str, Option<&str>)> {
+        let mut entries: Vec<(&str, Option<&str>)> = self
+            .terms
+            .iter()
+            .map(|t| (t.word.as_str(), t.phonetic_hint.as_deref()))
+            .collect();
+        entries.sort_unstable();
+        entries
+    }
+
+    /// Build the biasing payload for a provider, or `None` if there is nothing to send
+    pub fn hint_for(&self, provider: Provider) -> Option<VocabularyHint> {
+        if self.is_empty() {
+            return None;
+        }
+        match provider {
+            Provider::Whisper => self.whisper_prompt().map(VocabularyHint::Prompt),
+            Provider::Gemini => self.gemini_instructions().map(VocabularyHint::Instructions),
+        }
+    }
+
+    /// Comma-separated glossary for Whisper's prompt field
+    pub fn whisper_prompt(&self) -> Option<String> {
+        let prefix = "Glossary: ";
+        let mut prompt = String::from(prefix);
+
+        for term in &self.terms {
+            let separator = if prompt.len() > prefix.len() {
+                ", "
+            } else {
+                ""
+            };
+            if prompt.len() + separator.len() + term.word.len() + 1 > WHISPER_PROMPT_MAX_CHARS {
+                break;
+            }
+            prompt.push_str(separator);
+            prompt.push_str(&term.word);
+        }
+
+        if prompt.len() == prefix.len() {
+            return None;
+        }
+        prompt.push('.');
+        Some(prompt)
+    }
+
+    /// Instruction block listing terms (and pronunciations) for Gemini
+    pub fn gemini_instructions(&self) -> Option<String> {
+        let header = "The speaker uses the following custom vocabulary. \
+                      When you hear these terms, spell them exactly as written:";
+        let mut block = String::from(header);
+
+        for term in &self.terms {
+            let line = match &term.phonetic_hint {
+                Some(hint) => format!("\n- {} (sounds like \"{}\")", term.word, hint.trim()),
+                None => format!("\n- {}", term.word),
+            };
+            if block.len() + line.len() > GEMINI_INSTRUCTIONS_MAX_CHARS {
+                break;
+            }
+            block.push_str(&line);
+        }
+
+        if block.len() == header.len() {
+            return None;
+        }
+        Some(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(word: &str, hint: Option<&str>) -> DictionaryWord {
+        DictionaryWord {
+            id: word.to_string(),
+            word: word.to_string(),
+            phonetic_hint: hint.map(str::to_string),
+            category: None,
+            created_at: String::new(),
+        }
+    }
+
+    fn ranked(vocabulary: &Vocabulary) -> Vec<&str> {
+        vocabulary.terms.iter().map(|t| t.word.as_str()).collect()
+    }
+
+    #[test]
+    fn ranks_by_mentions_then_recency() {
+        let words = vec![
+            word("Kubernetes", None),
+            word("Siobhan", Some("shi-vawn")),
+            word("kubernetes", None),
+            word("  ", None),
+            word("Acme", None),
+        ];
+        let recent = vec![
+            "Ask Siobhan about Acme".to_string(),
+            "siobhan said no".to_string(),
+        ];
+        let vocabulary = Vocabulary::rank(words, &recent);
+        // Duplicates and blanks are dropped; mentions beat recency
+        assert_eq!(ranked(&vocabulary), ["Siobhan", "Acme", "Kubernetes"]);
+
+        let unused = Vocabulary::rank(vec![word("Zed", None), word("Yarn", None)], &[]);
+        assert_eq!(ranked(&unused), ["Zed", "Yarn"]);
+    }
+
+    #[test]
+    fn builds_provider_hints() {
+        let vocabulary = Vocabulary::rank(
+            vec![word("Siobhan", Some("shi-vawn")), word("Acme", None)],
+            &[],
+        );
+        assert_eq!(
+            vocabulary.hint_for(Provider::Whisper),
+            Some(VocabularyHint::Prompt(
+                "Glossary: Siobhan, Acme.".to_string()
+            ))
+        );
+        let Some(VocabularyHint::Instructions(block)) = vocabulary.hint_for(Provider::Gemini)
+        else {
+            panic!("expected Gemini instructions");
+        };
+        assert!(block.ends_with("\n- Siobhan (sounds like \"shi-vawn\")\n- Acme"));
+        assert_eq!(Vocabulary::default().hint_for(Provider::Whisper), None);
+    }
+
+    #[test]
+    fn truncates_to_the_provider_limit() {
+        let words: Vec<DictionaryWord> = (0..500)
+            .map(|i| word(&format!("Term{:03}", i), Some("a long pronunciation hint")))
+            .collect();
+        let vocabulary = Vocabulary::rank(words, &[]);
+
+        let prompt = vocabulary.whisper_prompt().unwrap();
+        assert!(prompt.len() <= WHISPER_PROMPT_MAX_CHARS);
+        assert!(prompt.starts_with("Glossary: Term000, Term001"));
+        assert!(prompt.ends_with('.'));
+
+        let block = vocabulary.gemini_instructions().unwrap();
+        assert!(block.len() <= GEMINI_INSTRUCTIONS_MAX_CHARS);
+        assert!(block.contains("- Term000 "));
+        assert!(!block.contains("Term499"));
+    }
+
+    #[test]
+    fn lists_entries_regardless_of_rank() {
+        let words = || vec![word("Siobhan", Some("shi-vawn")), word("Acme", None)];
+        let expected = [("Acme", None), ("Siobhan", Some("shi-vawn"))];
+        assert_eq!(Vocabulary::rank(words(), &[]).entries(), expected);
+        let mentioned = Vocabulary::rank(words(), &["Siobhan again".to_string()]);
+        assert_eq!(mentioned.entries(), expected);
+    }
+}
@@ -0,0 +1,144 @@
+use super::{language, TranscribeOptions, TranscriptionError, TranscriptionResult, WordTiming};
+use serde::Deserialize;
+
+const OPENAI_TRANSCRIPTIONS_URL: &str = "https://api.openai.com/v1/audio/transcriptions";
+const DEFAULT_MODEL: &str = "whisper-1";
+
+#[derive(Debug, Deserialize)]
+struct VerboseTranscription {
+    text: String,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    duration: Option<f64>,
+    #[serde(default)]
+    segments: Vec<Segment>,
+    #[serde(default)]
+    words: Vec<Word>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Segment {
+    avg_logprob: f64,
+    #[serde(default)]
+    no_speech_prob: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Word {
+    word: String,
+    start: f64,
+    end: f64,
+}
+
+impl VerboseTranscription {
+    /// Mean per-token probability across segments
+    fn confidence(&self) -> f32 {
+        if self.segments.is_empty() {
+            return 1.0;
+        }
+        let mean_logprob =
+            self.segments.iter().map(|s| s.avg_logprob).sum::<f64>() / self.segments.len() as f64;
+        mean_logprob.exp().clamp(0.0, 1.0) as f32
+    }
+
+    /// How likely the detected language is right
+    ///
+    /// The API doesn't return the detector's probability, but audio decoded
+    /// in the wrong language comes back with low token probabilities or
+    /// marked as likely non-speech, so both count against it.
+    fn language_probability(&self) -> Option<f32> {
+        if self.segments.is_empty() {
+            return None;
+        }
+        let no_speech = self.segments.iter().map(|s| s.no_speech_prob).sum::<f64>()
+            / self.segments.len() as f64;
+        let speech = (1.0 - no_speech).clamp(0.0, 1.0) as f32;
+        Some(self.confidence() * speech)
+    }
+}
+
+/// Transcribe audio with the OpenAI Whisper API
+///
+/// `prompt` is passed through as Whisper's `prompt` field, which the model
+/// treats as preceding context and uses to bias spelling of rare words.
+pub async fn transcribe(
+    api_key: &str,
+    audio: &[u8],
+    options: &TranscribeOptions,
+    prompt: Option<&str>,
+) -> Result<TranscriptionResult, TranscriptionError> {
+    let file = reqwest::multipart::Part::bytes(audio.to_vec())
+        .file_name("audio.wav")
+        .mime_str("audio/wav")
+        .map_err(|e| TranscriptionError::RequestError(e.to_string()))?;
+
+    let model = options
+        .model
+        .clone()
+        .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+
+    let mut form = reqwest::multipart::Form::new()
+        .part("file", file)
+        .text("model", model.clone())
+        .text("response_format", "verbose_json")
+        // Asking for words alone drops the segments confidence comes from
+        .text("timestamp_granularities[]", "segment")
+        .text("timestamp_granularities[]", "word");
+
+    if let Some(language) = &options.language {
+        form = form.text("language", language.clone());
+    }
+    if let Some(prompt) = prompt {
+        form = form.text("prompt", prompt.to_string());
+    }
+
+    let response = reqwest::Client::new()
+        .post(OPENAI_TRANSCRIPTIONS_URL)
+        .bearer_auth(api_key)
+        .multipart(form)
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(TranscriptionError::ApiError {
+            status: status.as_u16(),
+            body,
+        });
+    }
+
+    let data: VerboseTranscription = response.json().await?;
+
+    Ok(TranscriptionResult {
+        text: data.text.trim().to_string(),
+        language: data
+            .language
+            .as_deref()
+            .or(options.language.as_deref())
+            .map(language::normalize)
+            .unwrap_or_else(|| "en".to_string()),
+        // Only a detected language has a probability
+        language_probability: options
+            .language
+            .is_none()
+            .then(|| data.language_probability())
+            .flatten(),
+        confidence: data.confidence(),
+        duration_ms: data.duration.map(|d| (d * 1000.0) as u64).unwrap_or(0),
+        model,
+        input_tokens: 0,
+        output_tokens: 0,
+        cached: false,
+        words: data
+            .words
+            .iter()
+            .map(|w| WordTiming {
+                word: w.word.clone(),
+                start_ms: (w.start * 1000.0) as u64,
+                end_ms: (w.end * 1000.0) as u64,
+            })
+            .collect(),
+    })
+}
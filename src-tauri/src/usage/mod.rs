@@ -0,0 +1,286 @@
+pub mod pricing;
+
+use crate::storage::database::{self, DatabaseError};
+use crate::storage::settings;
+use serde::{Deserialize, Serialize};
+
+/// Settings key holding the monthly soft limit in USD
+const MONTHLY_LIMIT_KEY: &str = "usage_monthly_limit_usd";
+
+/// Settings key recording the last warning sent, as "<month>:<level>"
+const LAST_WARNING_KEY: &str = "usage_limit_last_warning";
+
+/// Fraction of the monthly limit at which the user is first warned
+const APPROACHING_THRESHOLD: f64 = 0.8;
+
+/// Billable work done by one provider call
+#[derive(Debug, Clone)]
+pub struct UsageRecord {
+    pub history_id: Option<String>,
+    pub provider: String,
+    pub model: String,
+    pub audio_seconds: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub provider: String,
+    pub model: String,
+    pub requests: u64,
+    pub audio_seconds: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyUsage {
+    pub day: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitLevel {
+    Approaching,
+    Exceeded,
+}
+
+impl LimitLevel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LimitLevel::Approaching => "approaching",
+            LimitLevel::Exceeded => "exceeded",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageWarning {
+    pub month: String,
+    pub level: LimitLevel,
+    pub spent_usd: f64,
+    pub limit_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageSummary {
+    /// Calendar month covered by the totals, as YYYY-MM
+    pub month: String,
+    pub cost_usd: f64,
+    pub audio_seconds: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Month-to-date totals per provider and model
+    pub by_provider: Vec<UsageTotals>,
+    /// Per-day totals for the requested window, newest first
+    pub daily: Vec<DailyUsage>,
+    pub monthly_limit_usd: Option<f64>,
+    pub warning: Option<UsageWarning>,
+}
+
+/// Record one provider call and roll it into the daily aggregate
+///
+/// Returns the estimated cost in USD.
+pub fn record(usage: &UsageRecord) -> Result<f64, DatabaseError> {
+    let cost = pricing::price_for(&usage.provider, &usage.model).cost(
+        usage.audio_seconds,
+        usage.input_tokens,
+        usage.output_tokens,
+    );
+
+    let mut conn = database::connect()?;
+    let tx = conn
+        .transaction()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    tx.execute(
+        "INSERT INTO usage_events
+            (id, history_id, provider, model, audio_seconds, input_tokens, output_tokens, cost_usd)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        rusqlite::params![
+            uuid::Uuid::new_v4().to_string(),
+            usage.history_id,
+            usage.provider,
+            usage.model,
+            usage.audio_seconds,
+            usage.input_tokens as i64,
+            usage.output_tokens as i64,
+            cost,
+        ],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    tx.execute(
+        "INSERT INTO usage_daily
+            (day, provider, model, requests, audio_seconds, input_tokens, output_tokens, cost_usd)
+         VALUES (date('now', 'localtime'), ?1, ?2, 1, ?3, ?4, ?5, ?6)
+         ON CONFLICT(day, provider, model) DO UPDATE SET
+            requests = requests + 1,
+            audio_seconds = audio_seconds + excluded.audio_seconds,
+            input_tokens = input_tokens + excluded.input_tokens,
+            output_tokens = output_tokens + excluded.output_tokens,
+            cost_usd = cost_usd + excluded.cost_usd",
+        rusqlite::params![
+            usage.provider,
+            usage.model,
+            usage.audio_seconds,
+            usage.input_tokens as i64,
+            usage.output_tokens as i64,
+            cost,
+        ],
+    )
+    .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    tx.commit()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    Ok(cost)
+}
+
+/// Month-to-date usage plus daily totals for the last `days` days
+pub fn summary(days: u32) -> Result<UsageSummary, DatabaseError> {
+    let conn = database::connect()?;
+    let month: String = conn
+        .query_row("SELECT strftime('%Y-%m', 'now', 'localtime')", [], |row| {
+            row.get(0)
+        })
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    let by_provider = month_totals(&conn, &month)?;
+
+    let window = format!("-{} days", days.saturating_sub(1));
+    let mut stmt = conn
+        .prepare(
+            "SELECT day, provider, model, requests, audio_seconds,
+                    input_tokens, output_tokens, cost_usd
+             FROM usage_daily
+             WHERE day >= date('now', 'localtime', ?1)
+             ORDER BY day DESC, cost_usd DESC",
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    let daily = stmt
+        .query_map([&window], |row| {
+            Ok(DailyUsage {
+                day: row.get(0)?,
+                totals: UsageTotals {
+                    provider: row.get(1)?,
+                    model: row.get(2)?,
+                    requests: row.get::<_, i64>(3)? as u64,
+                    audio_seconds: row.get(4)?,
+                    input_tokens: row.get::<_, i64>(5)? as u64,
+                    output_tokens: row.get::<_, i64>(6)? as u64,
+                    cost_usd: row.get(7)?,
+                },
+            })
+        })
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    let cost_usd = by_provider.iter().map(|t| t.cost_usd).sum();
+    let monthly_limit_usd = monthly_limit()?;
+
+    Ok(UsageSummary {
+        warning: monthly_limit_usd.and_then(|limit| limit_status(&month, cost_usd, limit)),
+        audio_seconds: by_provider.iter().map(|t| t.audio_seconds).sum(),
+        input_tokens: by_provider.iter().map(|t| t.input_tokens).sum(),
+        output_tokens: by_provider.iter().map(|t| t.output_tokens).sum(),
+        month,
+        cost_usd,
+        by_provider,
+        daily,
+        monthly_limit_usd,
+    })
+}
+
+/// Per-provider totals for a YYYY-MM month
+fn month_totals(
+    conn: &rusqlite::Connection,
+    month: &str,
+) -> Result<Vec<UsageTotals>, DatabaseError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT provider, model, SUM(requests), SUM(audio_seconds),
+                    SUM(input_tokens), SUM(output_tokens), SUM(cost_usd)
+             FROM usage_daily
+             WHERE substr(day, 1, 7) = ?1
+             GROUP BY provider, model
+             ORDER BY SUM(cost_usd) DESC",
+        )
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+    let rows = stmt
+        .query_map([month], |row| {
+            Ok(UsageTotals {
+                provider: row.get(0)?,
+                model: row.get(1)?,
+                requests: row.get::<_, i64>(2)? as u64,
+                audio_seconds: row.get(3)?,
+                input_tokens: row.get::<_, i64>(4)? as u64,
+                output_tokens: row.get::<_, i64>(5)? as u64,
+                cost_usd: row.get(6)?,
+            })
+        })
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| DatabaseError::SqliteError(e.to_string()))
+}
+
+/// The user's monthly soft spending limit, if set
+pub fn monthly_limit() -> Result<Option<f64>, DatabaseError> {
+    Ok(settings::get(MONTHLY_LIMIT_KEY)?
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|limit| *limit > 0.0))
+}
+
+/// Set or clear the monthly soft spending limit
+pub fn set_monthly_limit(limit_usd: Option<f64>) -> Result<(), DatabaseError> {
+    match limit_usd.filter(|limit| *limit > 0.0) {
+        Some(limit) => settings::set(MONTHLY_LIMIT_KEY, &limit.to_string()),
+        None => settings::remove(MONTHLY_LIMIT_KEY),
+    }
+}
+
+/// Check month-to-date spend against the soft limit
+///
+/// Returns a warning the first time each level is reached in a month, so
+/// callers can notify once rather than on every dictation.
+pub fn check_limit() -> Result<Option<UsageWarning>, DatabaseError> {
+    let Some(limit) = monthly_limit()? else {
+        return Ok(None);
+    };
+    let summary = summary(1)?;
+    let Some(warning) = limit_status(&summary.month, summary.cost_usd, limit) else {
+        return Ok(None);
+    };
+
+    let marker = format!("{}:{}", warning.month, warning.level.as_str());
+    let already_sent = settings::get(LAST_WARNING_KEY)?
+        .is_some_and(|last| last == marker || (last == format!("{}:exceeded", warning.month)));
+    if already_sent {
+        return Ok(None);
+    }
+
+    settings::set(LAST_WARNING_KEY, &marker)?;
+    Ok(Some(warning))
+}
+
+fn limit_status(month: &str, spent_usd: f64, limit_usd: f64) -> Option<UsageWarning> {
+    let level = if spent_usd >= limit_usd {
+        LimitLevel::Exceeded
+    } else if spent_usd >= limit_usd * APPROACHING_THRESHOLD {
+        LimitLevel::Approaching
+    } else {
+        return None;
+    };
+
+    Some(UsageWarning {
+        month: month.to_string(),
+        level,
+        spent_usd,
+        limit_usd,
+    })
+}
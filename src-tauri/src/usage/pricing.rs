@@ -0,0 +1,72 @@
+/// List prices in USD for a provider/model pair
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Price {
+    pub per_audio_second: f64,
+    pub per_input_token: f64,
+    pub per_output_token: f64,
+}
+
+const PER_MILLION: f64 = 1_000_000.0;
+
+/// Look up the price for a model; unknown models are treated as free
+///
+/// Model names are matched by prefix so dated snapshots
+/// ("claude-sonnet-4-20250514") resolve to their family.
+pub fn price_for(provider: &str, model: &str) -> Price {
+    let model = model.to_lowercase();
+    match provider {
+        "whisper" | "openai" if model.starts_with("gpt-4o-mini-transcribe") => Price {
+            per_audio_second: 0.003 / 60.0,
+            ..Price::default()
+        },
+        "whisper" | "openai" if model.starts_with("whisper") || model.contains("transcribe") => {
+            Price {
+                per_audio_second: 0.006 / 60.0,
+                ..Price::default()
+            }
+        }
+        "openai" if model.starts_with("gpt-4o-mini") => Price {
+            per_input_token: 0.15 / PER_MILLION,
+            per_output_token: 0.60 / PER_MILLION,
+            ..Price::default()
+        },
+        "openai" if model.starts_with("gpt-4o") => Price {
+            per_input_token: 2.50 / PER_MILLION,
+            per_output_token: 10.00 / PER_MILLION,
+            ..Price::default()
+        },
+        // Audio input is billed at the audio token rate
+        "gemini" if model.starts_with("gemini-2.0-flash") => Price {
+            per_input_token: 0.70 / PER_MILLION,
+            per_output_token: 0.40 / PER_MILLION,
+            ..Price::default()
+        },
+        "anthropic" if model.starts_with("claude-sonnet-4") => Price {
+            per_input_token: 3.00 / PER_MILLION,
+            per_output_token: 15.00 / PER_MILLION,
+            ..Price::default()
+        },
+        "anthropic"
+            if model.starts_with("claude-3-5-haiku") || model.starts_with("claude-haiku") =>
+        {
+            Price {
+                per_input_token: 0.80 / PER_MILLION,
+                per_output_token: 4.00 / PER_MILLION,
+                ..Price::default()
+            }
+        }
+        _ => {
+            log::debug!("No price known for {}/{}", provider, model);
+            Price::default()
+        }
+    }
+}
+
+impl Price {
+    /// Cost of one request in USD
+    pub fn cost(&self, audio_seconds: f64, input_tokens: u64, output_tokens: u64) -> f64 {
+        audio_seconds * self.per_audio_second
+            + input_tokens as f64 * self.per_input_token
+            + output_tokens as f64 * self.per_output_token
+    }
+}
@@ -0,0 +1,54 @@
+// ============================================================
+// REDE - LLM Post-processing Service
+// Runs in the Rust backend; API keys are read from the Keychain
+// ============================================================
+
+import type { AppTone } from "../types/index";
+
+export type LlmProvider = "anthropic" | "openai_compatible";
+
+export interface PostprocessOptions {
+  enabled: boolean;
+  provider?: LlmProvider;
+  model?: string;
+  /** Endpoint for OpenAI-compatible servers; defaults to OpenAI */
+  base_url?: string;
+  /** Prompt template version; defaults to the latest */
+  prompt_version?: number;
+  /** Overrides the tone picked from the target app */
+  tone?: AppTone;
+  timeout_ms?: number;
+  /** Hide names, emails, phone and card numbers and addresses from the provider */
+  private_mode?: boolean;
+}
+
+export interface PostprocessOutcome {
+  text: string;
+  /** False when the model failed or timed out and `text` is unchanged */
+  applied: boolean;
+  tone: AppTone;
+  prompt_version: number;
+  model: string;
+  input_tokens: number;
+  output_tokens: number;
+  /** Personal details hidden from the provider */
+  redactions: number;
+}
+
+/**
+ * Rewrite transcribed text with the configured LLM, in the tone of the target app.
+ */
+export async function postprocessText(
+  text: string,
+  language: string,
+  targetApp: string | null,
+  options: PostprocessOptions,
+): Promise<PostprocessOutcome> {
+  const { invoke } = await import("@tauri-apps/api/core");
+  return invoke<PostprocessOutcome>("postprocess_text", {
+    text,
+    language,
+    targetApp,
+    options,
+  });
+}